@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use anyhow::{Result, anyhow};
+use regex::Regex;
+use size::{Base, SizeFormatter, Style};
+
+use super::render_column_chart;
+
+// Runs when the prompt has no number
+const DEFAULT_RUNS: usize = 10;
+// Prevents running the query forever by a typo
+const MAX_RUNS: usize = 1000;
+/// The runs stop after this (the report shows the runs done so far), so that
+/// a slow query with many runs does not keep the server busy for hours.
+pub const BENCHMARK_MAX_DURATION: Duration = Duration::from_secs(600);
+// Top ProfileEvents in the report
+const PROFILE_EVENTS_LIMIT: usize = 20;
+
+/// What to benchmark: `runs` runs of every settings variant.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BenchmarkSpec {
+    pub runs: usize,
+    pub variants: Vec<Vec<(String, String)>>,
+}
+
+/// One run of the benchmarked query, from system.query_log (or only the
+/// client side duration, if the row is not there).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BenchmarkRun {
+    pub duration_ms: f64,
+    pub read_rows: u64,
+    pub read_bytes: u64,
+    pub memory_usage: u64,
+    pub profile_events: HashMap<String, u64>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BenchmarkVariant {
+    pub settings: Vec<(String, String)>,
+    pub runs: Vec<BenchmarkRun>,
+}
+
+impl BenchmarkSpec {
+    /// Parses "N [name=value ...] [| name=value ...]", i.e. the number of
+    /// runs and optionally several settings variants separated with "|".
+    pub fn parse(text: &str) -> Result<Self> {
+        let text = text.trim();
+        let (runs, variants) = match text.split_once(char::is_whitespace) {
+            Some((runs, variants)) => (runs, variants),
+            None => (text, ""),
+        };
+        let runs = if runs.is_empty() {
+            DEFAULT_RUNS
+        } else {
+            runs.parse::<usize>()
+                .map_err(|e| anyhow!("Invalid number of runs '{}': {}", runs, e))?
+        };
+        if runs == 0 || runs > MAX_RUNS {
+            return Err(anyhow!("Number of runs should be in [1, {}]", MAX_RUNS));
+        }
+
+        let mut parsed = Vec::new();
+        for variant in variants.split('|') {
+            let mut settings = Vec::new();
+            for setting in variant.split([' ', ',']).filter(|s| !s.is_empty()) {
+                let (name, value) = setting.split_once('=').ok_or_else(|| {
+                    anyhow!("Invalid setting '{}' (expected name=value)", setting)
+                })?;
+                settings.push((name.trim().to_string(), value.trim().to_string()));
+            }
+            parsed.push(settings);
+        }
+        // "N" and "N |" are the same
+        if parsed.len() > 1 {
+            parsed.retain(|settings| !settings.is_empty());
+            if parsed.is_empty() {
+                parsed.push(Vec::new());
+            }
+        }
+
+        Ok(Self {
+            runs,
+            variants: parsed,
+        })
+    }
+}
+
+static SELECT_QUERY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)^\(*\s*(SELECT|WITH)\b").unwrap());
+// The FORMAT clause goes last (optionally followed by SETTINGS), "format"
+// anywhere else is just a name
+static FORMAT_CLAUSE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?is)\bFORMAT\s+\w+(\s+SETTINGS\b.*)?$").unwrap());
+
+/// The query to run for the benchmark: SELECTs get FORMAT Null (unless they
+/// already have a FORMAT), the result is not needed, only the time of the
+/// query itself.
+pub fn benchmark_query_text(query: &str) -> String {
+    let query = query.trim().trim_end_matches(';').trim_end();
+    if SELECT_QUERY.is_match(query) && !FORMAT_CLAUSE.is_match(query) {
+        format!("{}\nFORMAT Null", query)
+    } else {
+        query.to_string()
+    }
+}
+
+fn format_ms(ms: f64) -> String {
+    if ms < 1000. {
+        format!("{:.1}ms", ms)
+    } else {
+        format!("{:.3}s", ms / 1000.)
+    }
+}
+
+impl BenchmarkVariant {
+    pub fn title(&self) -> String {
+        if self.settings.is_empty() {
+            return "default settings".to_string();
+        }
+        self.settings
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Duration quantile (nearest rank) of the runs.
+    pub fn duration_quantile(&self, level: f64) -> f64 {
+        let mut durations: Vec<f64> = self.runs.iter().map(|r| r.duration_ms).collect();
+        if durations.is_empty() {
+            return 0.;
+        }
+        durations.sort_by(f64::total_cmp);
+        let rank = (level * durations.len() as f64).ceil() as usize;
+        durations[rank.clamp(1, durations.len()) - 1]
+    }
+
+    /// ProfileEvents summed over all runs, in descending order.
+    pub fn profile_events(&self) -> Vec<(String, u64)> {
+        let mut events = HashMap::<&str, u64>::new();
+        for run in &self.runs {
+            for (name, value) in &run.profile_events {
+                *events.entry(name.as_str()).or_default() += value;
+            }
+        }
+        let mut events: Vec<(String, u64)> = events
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        events.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        events
+    }
+
+    /// Text report: durations distribution, per-run chart and the top
+    /// ProfileEvents (averaged per run).
+    pub fn report(&self) -> String {
+        let fmt = SizeFormatter::new()
+            .with_base(Base::Base2)
+            .with_style(Style::Abbreviated);
+        let runs = self.runs.len().max(1) as u64;
+        let avg = |f: fn(&BenchmarkRun) -> u64| self.runs.iter().map(f).sum::<u64>() / runs;
+
+        let mut lines = vec![
+            self.title(),
+            String::new(),
+            format!("Runs:   {}", self.runs.len()),
+            format!(
+                "min:    {}\np50:    {}\np95:    {}\nmax:    {}",
+                format_ms(self.duration_quantile(0.)),
+                format_ms(self.duration_quantile(0.5)),
+                format_ms(self.duration_quantile(0.95)),
+                format_ms(self.duration_quantile(1.)),
+            ),
+            format!(
+                "Read:   {} rows, {} (avg)",
+                avg(|r| r.read_rows),
+                fmt.format(avg(|r| r.read_bytes) as i64)
+            ),
+            format!(
+                "Memory: {} (avg)",
+                fmt.format(avg(|r| r.memory_usage) as i64)
+            ),
+            String::new(),
+            "Duration of every run (ms):".to_string(),
+        ];
+        let durations: Vec<f64> = self.runs.iter().map(|r| r.duration_ms).collect();
+        lines.push(render_column_chart(&durations, 8));
+
+        let events = self.profile_events();
+        if !events.is_empty() {
+            lines.push(String::new());
+            lines.push("ProfileEvents (avg per run):".to_string());
+            let width = events
+                .iter()
+                .take(PROFILE_EVENTS_LIMIT)
+                .map(|(name, _)| name.len())
+                .max()
+                .unwrap_or(0);
+            for (name, value) in events.iter().take(PROFILE_EVENTS_LIMIT) {
+                lines.push(format!("{:width$} {}", name, value / runs, width = width));
+            }
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(settings: &[(&str, &str)]) -> Vec<(String, String)> {
+        settings
+            .iter()
+            .map(|(n, v)| (n.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_spec() {
+        let spec = BenchmarkSpec::parse("").unwrap();
+        assert_eq!(spec.runs, DEFAULT_RUNS);
+        assert_eq!(spec.variants, vec![Vec::new()]);
+
+        let spec = BenchmarkSpec::parse("5 max_threads=1, use_uncompressed_cache=1").unwrap();
+        assert_eq!(spec.runs, 5);
+        assert_eq!(
+            spec.variants,
+            vec![settings(&[
+                ("max_threads", "1"),
+                ("use_uncompressed_cache", "1")
+            ])]
+        );
+
+        let spec = BenchmarkSpec::parse("3 max_threads=1 | max_threads=8").unwrap();
+        assert_eq!(
+            spec.variants,
+            vec![
+                settings(&[("max_threads", "1")]),
+                settings(&[("max_threads", "8")])
+            ]
+        );
+
+        assert!(BenchmarkSpec::parse("x").is_err());
+        assert!(BenchmarkSpec::parse("0").is_err());
+        assert!(BenchmarkSpec::parse("3 max_threads").is_err());
+    }
+
+    #[test]
+    fn test_benchmark_query_text() {
+        assert_eq!(
+            benchmark_query_text("SELECT * FROM t;\n"),
+            "SELECT * FROM t\nFORMAT Null"
+        );
+        assert_eq!(
+            benchmark_query_text("with 1 AS x SELECT x"),
+            "with 1 AS x SELECT x\nFORMAT Null"
+        );
+        assert_eq!(
+            benchmark_query_text("SELECT 1 FORMAT JSON"),
+            "SELECT 1 FORMAT JSON"
+        );
+        assert_eq!(
+            benchmark_query_text("SELECT 1 FORMAT JSON SETTINGS max_threads = 1"),
+            "SELECT 1 FORMAT JSON SETTINGS max_threads = 1"
+        );
+        assert_eq!(
+            benchmark_query_text("SELECT x AS format FROM t"),
+            "SELECT x AS format FROM t\nFORMAT Null"
+        );
+        assert_eq!(
+            benchmark_query_text("INSERT INTO t SELECT 1"),
+            "INSERT INTO t SELECT 1"
+        );
+    }
+
+    #[test]
+    fn test_quantiles_and_profile_events() {
+        let variant = BenchmarkVariant {
+            settings: Vec::new(),
+            runs: [30., 10., 20., 40.]
+                .iter()
+                .map(|&duration_ms| BenchmarkRun {
+                    duration_ms,
+                    profile_events: HashMap::from([
+                        ("SelectedRows".to_string(), 10),
+                        ("SelectedParts".to_string(), 1),
+                    ]),
+                    ..Default::default()
+                })
+                .collect(),
+        };
+        assert_eq!(variant.duration_quantile(0.), 10.);
+        assert_eq!(variant.duration_quantile(0.5), 20.);
+        assert_eq!(variant.duration_quantile(0.95), 40.);
+        assert_eq!(variant.duration_quantile(1.), 40.);
+        assert_eq!(
+            variant.profile_events(),
+            vec![
+                ("SelectedRows".to_string(), 40),
+                ("SelectedParts".to_string(), 4)
+            ]
+        );
+        assert!(variant.report().contains("SelectedRows  10"));
+    }
+}
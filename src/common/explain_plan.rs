@@ -0,0 +1,243 @@
+use anyhow::{Result, anyhow};
+use serde_json::{Map, Value};
+
+/// Index analysis of a ReadFromMergeTree step (`indexes=1`).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlanIndex {
+    /// MinMax, Partition, PrimaryKey, Skip, ...
+    pub index_type: String,
+    /// Name of a skipping index.
+    pub name: String,
+    pub keys: Vec<String>,
+    pub condition: String,
+    pub initial_parts: Option<u64>,
+    pub selected_parts: Option<u64>,
+    pub initial_granules: Option<u64>,
+    pub selected_granules: Option<u64>,
+}
+
+/// Step of the `EXPLAIN PLAN json=1` output.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PlanNode {
+    pub node_type: String,
+    pub description: String,
+    /// All other properties of the step ("Read Type", "Parts", "Keys", ...).
+    pub properties: Vec<(String, String)>,
+    /// Expression actions (`actions=1`).
+    pub actions: Vec<String>,
+    pub indexes: Vec<PlanIndex>,
+    pub children: Vec<PlanNode>,
+}
+
+fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Array(values) if values.iter().all(|v| !v.is_object() && !v.is_array()) => values
+            .iter()
+            .map(value_to_string)
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => value.to_string(),
+    }
+}
+
+fn string_field(object: &Map<String, Value>, name: &str) -> String {
+    object.get(name).map(value_to_string).unwrap_or_default()
+}
+
+fn action_to_string(action: &Value) -> String {
+    let Some(action) = action.as_object() else {
+        return value_to_string(action);
+    };
+    format!(
+        "{} {} :: {}",
+        string_field(action, "Node Type"),
+        string_field(action, "Result Name"),
+        string_field(action, "Result Type"),
+    )
+}
+
+impl PlanIndex {
+    fn from_json(object: &Map<String, Value>) -> Self {
+        let number = |name: &str| object.get(name).and_then(Value::as_u64);
+        Self {
+            index_type: string_field(object, "Type"),
+            name: string_field(object, "Name"),
+            keys: object
+                .get("Keys")
+                .and_then(Value::as_array)
+                .map(|keys| keys.iter().map(value_to_string).collect())
+                .unwrap_or_default(),
+            condition: string_field(object, "Condition"),
+            initial_parts: number("Initial Parts"),
+            selected_parts: number("Selected Parts"),
+            initial_granules: number("Initial Granules"),
+            selected_granules: number("Selected Granules"),
+        }
+    }
+}
+
+impl PlanNode {
+    /// Parses the output of `EXPLAIN PLAN json=1` (an array of {"Plan": ...}).
+    pub fn parse_explain_json(json: &str) -> Result<Vec<Self>> {
+        let value: Value = serde_json::from_str(json)?;
+        let plans = value
+            .as_array()
+            .ok_or_else(|| anyhow!("EXPLAIN json is not an array"))?;
+        plans
+            .iter()
+            .map(|plan| {
+                plan.get("Plan")
+                    .and_then(Value::as_object)
+                    .map(Self::from_json)
+                    .ok_or_else(|| anyhow!("No Plan in EXPLAIN json"))
+            })
+            .collect()
+    }
+
+    fn from_json(object: &Map<String, Value>) -> Self {
+        let mut node = Self {
+            node_type: string_field(object, "Node Type"),
+            description: string_field(object, "Description"),
+            ..Default::default()
+        };
+        for (key, value) in object {
+            match key.as_str() {
+                "Node Type" | "Description" => {}
+                "Plans" => {
+                    node.children = value
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_object)
+                        .map(Self::from_json)
+                        .collect();
+                }
+                "Indexes" => {
+                    node.indexes = value
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(Value::as_object)
+                        .map(PlanIndex::from_json)
+                        .collect();
+                }
+                // Expression of the step, or of its part (e.g. of the Filter step)
+                "Expression" if value.get("Actions").is_some() => {
+                    node.actions.extend(
+                        value["Actions"]
+                            .as_array()
+                            .into_iter()
+                            .flatten()
+                            .map(action_to_string),
+                    );
+                }
+                _ => node.properties.push((key.clone(), value_to_string(value))),
+            }
+        }
+        node
+    }
+
+    /// (selected, initial) granules after all the indexes, i.e. how much of
+    /// the table will be read.
+    pub fn granules_selectivity(&self) -> Option<(u64, u64)> {
+        let initial = self.indexes.iter().find_map(|i| i.initial_granules)?;
+        let selected = self
+            .indexes
+            .iter()
+            .rev()
+            .find_map(|i| i.selected_granules)?;
+        Some((selected, initial))
+    }
+
+    /// Reads (almost) everything in spite of the indexes.
+    pub fn has_poor_selectivity(&self) -> bool {
+        // Small tables are read entirely anyway
+        const MIN_GRANULES: u64 = 16;
+        const MAX_FRACTION: f64 = 0.5;
+        match self.granules_selectivity() {
+            Some((selected, initial)) => {
+                initial >= MIN_GRANULES && selected as f64 / initial as f64 > MAX_FRACTION
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAN: &str = r#"[
+  {
+    "Plan": {
+      "Node Type": "Expression",
+      "Description": "(Project names + Projection)",
+      "Expression": {
+        "Actions": [
+          {"Node Type": "INPUT", "Result Type": "UInt64", "Result Name": "key", "Arguments": [0]}
+        ],
+        "Positions": [0]
+      },
+      "Plans": [
+        {
+          "Node Type": "ReadFromMergeTree",
+          "Description": "default.t",
+          "Read Type": "Default",
+          "Parts": 2,
+          "Granules": 90,
+          "Indexes": [
+            {
+              "Type": "PrimaryKey",
+              "Keys": ["key"],
+              "Condition": "(key in [1, +Inf))",
+              "Initial Parts": 2,
+              "Selected Parts": 2,
+              "Initial Granules": 100,
+              "Selected Granules": 95
+            },
+            {
+              "Type": "Skip",
+              "Name": "idx",
+              "Description": "minmax GRANULARITY 1",
+              "Initial Parts": 2,
+              "Selected Parts": 2,
+              "Initial Granules": 95,
+              "Selected Granules": 90
+            }
+          ]
+        }
+      ]
+    }
+  }
+]"#;
+
+    #[test]
+    fn test_parse_explain_json() {
+        let plans = PlanNode::parse_explain_json(PLAN).unwrap();
+        assert_eq!(plans.len(), 1);
+        let expression = &plans[0];
+        assert_eq!(expression.node_type, "Expression");
+        assert_eq!(expression.actions, vec!["INPUT key :: UInt64"]);
+        assert_eq!(expression.children.len(), 1);
+
+        let read = &expression.children[0];
+        assert_eq!(read.description, "default.t");
+        assert!(
+            read.properties
+                .contains(&("Read Type".to_string(), "Default".to_string()))
+        );
+        assert_eq!(read.indexes.len(), 2);
+        assert_eq!(read.indexes[0].keys, vec!["key"]);
+        assert_eq!(read.indexes[1].name, "idx");
+        assert_eq!(read.granules_selectivity(), Some((90, 100)));
+        assert!(read.has_poor_selectivity());
+        assert!(!expression.has_poor_selectivity());
+    }
+
+    #[test]
+    fn test_parse_explain_json_invalid() {
+        assert!(PlanNode::parse_explain_json("Expression").is_err());
+        assert!(PlanNode::parse_explain_json("[{}]").is_err());
+    }
+}
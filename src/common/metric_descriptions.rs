@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+
+// Prefixes of the system.metric_log columns, used as the keys here as well,
+// since the same name can be both a ProfileEvent and a CurrentMetric (e.g. Query)
+pub const PROFILE_EVENT_PREFIX: &str = "ProfileEvent_";
+pub const CURRENT_METRIC_PREFIX: &str = "CurrentMetric_";
+
+/// Descriptions of ProfileEvents (system.events), CurrentMetrics
+/// (system.metrics) and asynchronous metrics (system.asynchronous_metrics).
+#[derive(Clone, Debug, Default)]
+pub struct MetricDescriptions {
+    descriptions: HashMap<String, String>,
+}
+
+impl MetricDescriptions {
+    pub fn insert(&mut self, name: String, description: String) {
+        self.descriptions.insert(name, description);
+    }
+
+    /// Description by a system.metric_log column name (ProfileEvent_*,
+    /// CurrentMetric_*), an asynchronous metric name or a bare ProfileEvent
+    /// name (as in the ProfileEvents of the queries).
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.descriptions
+            .get(name)
+            .or_else(|| {
+                self.descriptions
+                    .get(&format!("{}{}", PROFILE_EVENT_PREFIX, name))
+            })
+            .map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get() {
+        let mut descriptions = MetricDescriptions::default();
+        descriptions.insert("ProfileEvent_Query".into(), "Queries started".into());
+        descriptions.insert("CurrentMetric_Query".into(), "Queries running".into());
+        descriptions.insert("jemalloc.active".into(), "Active bytes".into());
+
+        assert_eq!(descriptions.get("Query"), Some("Queries started"));
+        assert_eq!(
+            descriptions.get("ProfileEvent_Query"),
+            Some("Queries started")
+        );
+        assert_eq!(
+            descriptions.get("CurrentMetric_Query"),
+            Some("Queries running")
+        );
+        assert_eq!(descriptions.get("jemalloc.active"), Some("Active bytes"));
+        assert_eq!(descriptions.get("Unknown"), None);
+    }
+}
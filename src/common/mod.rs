@@ -1,9 +1,43 @@
+mod benchmark;
 mod chart;
+mod explain_plan;
+mod metric_descriptions;
+mod part_lineage;
+mod pipeline_graph;
+mod query_analysis;
 mod relative_date_time;
+mod schema_drift;
 pub mod sparkline;
 mod stopwatch;
+mod table_dependencies;
+mod table_growth;
+mod table_overview;
+mod text_diff;
+mod trace_spans;
+mod view_chain;
 
+pub use benchmark::{
+    BENCHMARK_MAX_DURATION, BenchmarkRun, BenchmarkSpec, BenchmarkVariant, benchmark_query_text,
+};
 pub use chart::render_column_chart;
+pub use explain_plan::{PlanIndex, PlanNode};
+pub use metric_descriptions::{CURRENT_METRIC_PREFIX, MetricDescriptions, PROFILE_EVENT_PREFIX};
+pub use part_lineage::{PartEvent, PartLineage};
+pub use pipeline_graph::{PipelineGraph, PipelineNode, ProcessorProfile};
+pub use query_analysis::{QueryFinding, analyze_query, query_analysis_report};
 pub use relative_date_time::RelativeDateTime;
 pub use relative_date_time::parse_datetime_or_date;
+pub use schema_drift::{HostSchema, SchemaVariant, schema_variants};
 pub use stopwatch::Stopwatch;
+pub use table_dependencies::{
+    DependencyDirection, DependencyNode, TableDefinition, TableDependency, TableName,
+    dependencies_tree, table_dependencies,
+};
+pub use table_growth::{GrowthBucket, TableGrowth};
+pub use table_overview::{
+    PartLogError, PartLogStats, ReplicaStatus, TableOverview, merge_tree_setting_override,
+    merge_tree_table_settings,
+};
+pub use text_diff::{DiffRow, DiffSegment, diff_texts};
+pub use trace_spans::{TraceSpan, spans_tree_order};
+pub use view_chain::{ViewChainNode, ViewChainRow, ViewRun, views_chain};
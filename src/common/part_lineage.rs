@@ -0,0 +1,199 @@
+use std::collections::{HashMap, HashSet};
+
+use size::{Base, SizeFormatter, Style};
+
+/// Event of the system.part_log that created a part.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PartEvent {
+    pub part_name: String,
+    pub event_type: String,
+    pub event_time: String,
+    pub duration_ms: u64,
+    pub size_in_bytes: u64,
+    pub rows: u64,
+    pub merged_from: Vec<String>,
+    pub query_id: String,
+}
+
+impl PartEvent {
+    /// Fetched parts were created on another replica (the ancestry is there),
+    /// so the local events are preferred.
+    fn is_fetch(&self) -> bool {
+        self.event_type == "DownloadPart"
+    }
+}
+
+/// Events of the parts by name (one per part, see insert()).
+#[derive(Clone, Debug, Default)]
+pub struct PartLineage {
+    events: HashMap<String, PartEvent>,
+}
+
+impl PartLineage {
+    pub fn insert(&mut self, event: PartEvent) {
+        match self.events.get(&event.part_name) {
+            Some(existing) if !existing.is_fetch() || event.is_fetch() => {}
+            _ => {
+                self.events.insert(event.part_name.clone(), event);
+            }
+        }
+    }
+
+    /// Source parts of the known events, that are not known yet.
+    pub fn unknown_sources(&self) -> Vec<String> {
+        let mut sources: Vec<String> = self
+            .events
+            .values()
+            .flat_map(|event| event.merged_from.iter())
+            .filter(|name| !self.events.contains_key(*name))
+            .cloned()
+            .collect();
+        sources.sort_unstable();
+        sources.dedup();
+        sources
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    /// Parts in the tree order (the part, then its sources) with the depth.
+    pub fn tree<'a>(&'a self, part_name: &'a str) -> Vec<(usize, &'a str)> {
+        let mut order = Vec::new();
+        // The same part cannot be merged twice, but the logs can be broken
+        let mut seen = HashSet::new();
+        let mut stack = vec![(0, part_name)];
+        while let Some((depth, name)) = stack.pop() {
+            if !seen.insert(name) {
+                continue;
+            }
+            order.push((depth, name));
+            if let Some(event) = self.events.get(name) {
+                stack.extend(
+                    event
+                        .merged_from
+                        .iter()
+                        .rev()
+                        .map(|source| (depth + 1, source.as_str())),
+                );
+            }
+        }
+        order
+    }
+
+    /// Tree of the ancestors of the part down to the original inserts.
+    pub fn report(&self, part_name: &str) -> String {
+        let fmt = SizeFormatter::new()
+            .with_base(Base::Base2)
+            .with_style(Style::Abbreviated);
+        let tree = self.tree(part_name);
+
+        let labels: Vec<String> = tree
+            .iter()
+            .map(|(depth, name)| format!("{}{}", "  ".repeat(*depth), name))
+            .collect();
+        let width = labels.iter().map(|l| l.len()).max().unwrap_or(0);
+        let mut lines = vec![format!(
+            "{:width$} {:12} {:19} {:>10} {:>10} {:>12} query_id",
+            "part",
+            "event",
+            "event_time",
+            "duration",
+            "size",
+            "rows",
+            width = width,
+        )];
+        let mut inserts = 0;
+        for ((_, name), label) in tree.iter().zip(labels) {
+            let Some(event) = self.events.get(*name) else {
+                lines.push(format!("{:width$} (not in part_log)", label, width = width));
+                continue;
+            };
+            // Only the inserts have the query_id
+            let query_id = if event.merged_from.is_empty() {
+                inserts += 1;
+                event.query_id.as_str()
+            } else {
+                ""
+            };
+            lines.push(format!(
+                "{:width$} {:12} {:19} {:>10} {:>10} {:>12} {}",
+                label,
+                event.event_type,
+                event.event_time,
+                format!("{}ms", event.duration_ms),
+                fmt.format(event.size_in_bytes as i64),
+                event.rows,
+                query_id,
+                width = width,
+            ));
+        }
+        lines.insert(
+            0,
+            format!(
+                "{} parts, {} without sources (inserts)\n",
+                tree.len(),
+                inserts
+            ),
+        );
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(part_name: &str, event_type: &str, merged_from: &[&str]) -> PartEvent {
+        PartEvent {
+            part_name: part_name.to_string(),
+            event_type: event_type.to_string(),
+            merged_from: merged_from.iter().map(|s| s.to_string()).collect(),
+            query_id: format!("q_{}", part_name),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_part_lineage() {
+        let mut lineage = PartLineage::default();
+        lineage.insert(event(
+            "all_1_3_1",
+            "MergeParts",
+            &["all_1_2_1", "all_3_3_0"],
+        ));
+        lineage.insert(event(
+            "all_1_2_1",
+            "MergeParts",
+            &["all_1_1_0", "all_2_2_0"],
+        ));
+        lineage.insert(event("all_1_1_0", "NewPart", &[]));
+        // Fetched on the other replica, the local event wins
+        lineage.insert(event("all_2_2_0", "NewPart", &[]));
+        lineage.insert(event("all_2_2_0", "DownloadPart", &[]));
+        assert_eq!(lineage.unknown_sources(), vec!["all_3_3_0".to_string()]);
+        lineage.insert(event("all_3_3_0", "DownloadPart", &[]));
+        lineage.insert(event("all_3_3_0", "NewPart", &[]));
+        assert!(lineage.unknown_sources().is_empty());
+
+        assert_eq!(
+            lineage.tree("all_1_3_1"),
+            vec![
+                (0, "all_1_3_1"),
+                (1, "all_1_2_1"),
+                (2, "all_1_1_0"),
+                (2, "all_2_2_0"),
+                (1, "all_3_3_0"),
+            ]
+        );
+        let report = lineage.report("all_1_3_1");
+        assert!(report.starts_with("5 parts, 3 without sources (inserts)"));
+        assert!(report.contains("q_all_3_3_0"));
+        assert!(!report.contains("q_all_1_2_1"));
+        assert!(!report.contains("DownloadPart"));
+    }
+}
@@ -0,0 +1,355 @@
+use std::collections::HashMap;
+
+/// Processor (or several parallel ones, see `count`) of the
+/// `EXPLAIN PIPELINE graph=1` output.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PipelineNode {
+    /// Processor name, without the "× N" suffix of the compact output.
+    pub name: String,
+    /// Query plan step (the label of the DOT cluster), empty if none.
+    pub step: String,
+    /// Index of the step cluster: steps with the same name are different steps.
+    pub step_id: Option<usize>,
+    pub count: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PipelineEdge {
+    pub from: usize,
+    pub to: usize,
+    pub count: usize,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PipelineGraph {
+    pub nodes: Vec<PipelineNode>,
+    pub edges: Vec<PipelineEdge>,
+}
+
+/// processors_profile_log totals of all processors with one name.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProcessorProfile {
+    pub processors: u64,
+    pub elapsed_us: u64,
+    pub input_wait_us: u64,
+    pub output_wait_us: u64,
+}
+
+/// "ExpressionTransform × 16" -> ("ExpressionTransform", 16)
+fn split_count(label: &str) -> (&str, usize) {
+    if let Some((name, count)) = label.rsplit_once(" × ")
+        && let Ok(count) = count.trim().parse::<usize>()
+    {
+        return (name, count);
+    }
+    (label, 1)
+}
+
+/// Value of the `label="..."` (or `label ="..."`) attribute, till the last
+/// quote (names can have nested quotes).
+fn label_attribute(line: &str) -> Option<&str> {
+    let start = line.find("label")?;
+    let value = line[start + "label".len()..]
+        .trim_start()
+        .strip_prefix('=')?
+        .trim_start()
+        .strip_prefix('"')?;
+    let end = value.rfind('"')?;
+    Some(&value[..end])
+}
+
+/// The line without the quoted strings (braces in names are not scopes).
+fn unquoted(line: &str) -> String {
+    line.split('"').step_by(2).collect()
+}
+
+impl PipelineGraph {
+    /// Parses the DOT of `EXPLAIN PIPELINE graph=1` (both compact=0 and
+    /// compact=1). This is not a generic DOT parser, only the subset that
+    /// ClickHouse prints (one statement per line).
+    pub fn parse(dot: &str) -> Self {
+        let mut graph = Self::default();
+        let mut ids = HashMap::<String, usize>::new();
+        // Open braces: Some(cluster index) for "subgraph cluster_N {"
+        let mut scopes: Vec<Option<usize>> = Vec::new();
+        let mut step_names = HashMap::<usize, String>::new();
+        // Multi-line edge labels (header=1)
+        let mut in_string = false;
+
+        for line in dot.lines() {
+            let line = line.trim();
+            let quotes = line.matches('"').count() - line.matches("\\\"").count();
+            if in_string {
+                in_string = quotes % 2 == 0;
+                continue;
+            }
+            in_string = quotes % 2 == 1;
+
+            if let Some(rest) = line.strip_prefix("subgraph cluster_") {
+                let id = rest.trim_end_matches('{').trim().parse::<usize>().ok();
+                scopes.push(id);
+                continue;
+            }
+            let step_id = scopes.iter().rev().find_map(|s| *s);
+            for c in unquoted(line).chars() {
+                match c {
+                    '{' => scopes.push(None),
+                    '}' => {
+                        scopes.pop();
+                    }
+                    _ => {}
+                }
+            }
+
+            if line.starts_with("label") {
+                if let (Some(step_id), Some(label)) = (step_id, label_attribute(line)) {
+                    step_names.insert(step_id, label.to_string());
+                }
+                continue;
+            }
+            if let Some((from, rest)) = line.split_once(" -> ") {
+                let to = rest.split([' ', ';', '[']).next().unwrap_or_default();
+                let count = label_attribute(rest)
+                    .and_then(|label| label.trim().strip_prefix("× "))
+                    .and_then(|count| count.trim().parse::<usize>().ok())
+                    .unwrap_or(1);
+                if let (Some(&from), Some(&to)) = (ids.get(from.trim()), ids.get(to)) {
+                    graph.edges.push(PipelineEdge { from, to, count });
+                }
+                continue;
+            }
+            if line.starts_with('n')
+                && let Some((id, attributes)) = line.split_once('[')
+                && let Some(label) = label_attribute(attributes)
+            {
+                let (name, count) = split_count(label);
+                ids.insert(id.trim().to_string(), graph.nodes.len());
+                graph.nodes.push(PipelineNode {
+                    name: name.to_string(),
+                    step: String::new(),
+                    step_id,
+                    count,
+                });
+            }
+        }
+
+        // Cluster label goes before its nodes, but be tolerant
+        for node in &mut graph.nodes {
+            if let Some(step) = node.step_id.and_then(|id| step_names.get(&id)) {
+                node.step = step.clone();
+            }
+        }
+        graph
+    }
+
+    /// Nodes in topological order (nodes of cycles, if any, go last).
+    pub fn topological_order(&self) -> Vec<usize> {
+        let mut in_degree = vec![0; self.nodes.len()];
+        let mut successors = vec![Vec::new(); self.nodes.len()];
+        for edge in &self.edges {
+            in_degree[edge.to] += 1;
+            successors[edge.from].push(edge.to);
+        }
+        let mut order: Vec<usize> = (0..self.nodes.len())
+            .filter(|&i| in_degree[i] == 0)
+            .collect();
+        let mut i = 0;
+        while i < order.len() {
+            for &next in &successors[order[i]] {
+                in_degree[next] -= 1;
+                if in_degree[next] == 0 {
+                    order.push(next);
+                }
+            }
+            i += 1;
+        }
+        if order.len() < self.nodes.len() {
+            let mut seen = vec![false; self.nodes.len()];
+            order.iter().for_each(|&i| seen[i] = true);
+            order.extend((0..self.nodes.len()).filter(|&i| !seen[i]));
+        }
+        order
+    }
+
+    /// Group of parallel streams for every node: processors with the same
+    /// name within the same step, fed by the same groups.
+    pub fn parallel_groups(&self) -> Vec<usize> {
+        let mut predecessors = vec![Vec::new(); self.nodes.len()];
+        for edge in &self.edges {
+            predecessors[edge.to].push(edge.from);
+        }
+
+        let mut groups = vec![usize::MAX; self.nodes.len()];
+        let mut keys = HashMap::<(Option<usize>, &str, Vec<usize>), usize>::new();
+        for i in self.topological_order() {
+            let mut input_groups: Vec<usize> = predecessors[i]
+                .iter()
+                .map(|&p| groups[p])
+                .filter(|&g| g != usize::MAX)
+                .collect();
+            input_groups.sort_unstable();
+            input_groups.dedup();
+            let node = &self.nodes[i];
+            let next = keys.len();
+            groups[i] = *keys
+                .entry((node.step_id, node.name.as_str(), input_groups))
+                .or_insert(next);
+        }
+        groups
+    }
+
+    /// Merges the nodes with equal keys (counts are summed, edges between
+    /// merged nodes are merged as well). Returns the new graph and the key of
+    /// each of its nodes.
+    pub fn merge_by(&self, keys: &[usize]) -> (Self, Vec<usize>) {
+        let mut graph = Self::default();
+        let mut node_keys = Vec::new();
+        let mut index = HashMap::<usize, usize>::new();
+        let mut merged = Vec::with_capacity(self.nodes.len());
+        for (node, &key) in self.nodes.iter().zip(keys) {
+            let i = *index.entry(key).or_insert_with(|| {
+                graph.nodes.push(PipelineNode {
+                    count: 0,
+                    ..node.clone()
+                });
+                node_keys.push(key);
+                graph.nodes.len() - 1
+            });
+            graph.nodes[i].count += node.count;
+            merged.push(i);
+        }
+
+        let mut edges = HashMap::<(usize, usize), usize>::new();
+        for edge in &self.edges {
+            let (from, to) = (merged[edge.from], merged[edge.to]);
+            if from == to {
+                continue;
+            }
+            match edges.get(&(from, to)) {
+                Some(&i) => graph.edges[i].count += edge.count,
+                None => {
+                    edges.insert((from, to), graph.edges.len());
+                    graph.edges.push(PipelineEdge {
+                        from,
+                        to,
+                        count: edge.count,
+                    });
+                }
+            }
+        }
+        (graph, node_keys)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PIPELINE: &str = r#"digraph
+{
+  rankdir="LR";
+  { node [shape = rect]
+    subgraph cluster_0 {
+      label ="Expression";
+      style=filled;
+      color=lightgrey;
+      node [style=filled,color=white];
+      { rank = same;
+        n4 [label="ExpressionTransform"];
+        n5 [label="ExpressionTransform"];
+      }
+    }
+    subgraph cluster_1 {
+      label ="ReadFromMergeTree";
+      style=filled;
+      color=lightgrey;
+      node [style=filled,color=white];
+      { rank = same;
+        n1 [label="MergeTreeSelect(pool: ReadPool, algorithm: Thread)"];
+        n2 [label="MergeTreeSelect(pool: ReadPool, algorithm: Thread)"];
+      }
+    }
+    subgraph cluster_2 {
+      label ="Expression";
+      style=filled;
+      color=lightgrey;
+      node [style=filled,color=white];
+      { rank = same;
+        n6 [label="Resize"];
+      }
+    }
+  }
+  n1 -> n4;
+  n2 -> n5 [label="
+Header: number UInt64"];
+  n4 -> n6;
+  n5 -> n6;
+}"#;
+
+    #[test]
+    fn test_parse() {
+        let graph = PipelineGraph::parse(PIPELINE);
+        let names: Vec<(&str, &str)> = graph
+            .nodes
+            .iter()
+            .map(|n| (n.name.as_str(), n.step.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("ExpressionTransform", "Expression"),
+                ("ExpressionTransform", "Expression"),
+                (
+                    "MergeTreeSelect(pool: ReadPool, algorithm: Thread)",
+                    "ReadFromMergeTree"
+                ),
+                (
+                    "MergeTreeSelect(pool: ReadPool, algorithm: Thread)",
+                    "ReadFromMergeTree"
+                ),
+                ("Resize", "Expression"),
+            ]
+        );
+        let edges: Vec<(usize, usize)> = graph.edges.iter().map(|e| (e.from, e.to)).collect();
+        assert_eq!(edges, vec![(2, 0), (3, 1), (0, 4), (1, 4)]);
+    }
+
+    #[test]
+    fn test_parse_compact() {
+        let graph = PipelineGraph::parse(
+            r#"digraph
+{
+  n1 [label="NumbersRange × 4"];
+  n2 [label="Resize"];
+  n1 -> n2 [label="× 4"];
+}"#,
+        );
+        assert_eq!(graph.nodes[0].name, "NumbersRange");
+        assert_eq!(graph.nodes[0].count, 4);
+        assert_eq!(graph.nodes[1].count, 1);
+        assert_eq!(graph.edges[0].count, 4);
+    }
+
+    #[test]
+    fn test_parallel_groups() {
+        let graph = PipelineGraph::parse(PIPELINE);
+        let groups = graph.parallel_groups();
+        // Both streams (source and expression) are collapsed, the Resize of
+        // the other "Expression" step is not merged with them
+        assert_eq!(groups[0], groups[1]);
+        assert_eq!(groups[2], groups[3]);
+        assert_ne!(groups[0], groups[2]);
+        assert_ne!(groups[4], groups[0]);
+
+        let (collapsed, keys) = graph.merge_by(&groups);
+        assert_eq!(keys.len(), 3);
+        let counts: Vec<usize> = collapsed.nodes.iter().map(|n| n.count).collect();
+        assert_eq!(counts, vec![2, 2, 1]);
+        let edges: Vec<(usize, usize, usize)> = collapsed
+            .edges
+            .iter()
+            .map(|e| (e.from, e.to, e.count))
+            .collect();
+        assert_eq!(edges, vec![(1, 0, 2), (0, 2, 2)]);
+    }
+}
@@ -0,0 +1,297 @@
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+/// What the time of a query could have been spent on, by ProfileEvents.
+struct Rule {
+    name: &'static str,
+    /// Events of the time spent, with the multiplier to microseconds.
+    time: &'static [(&'static str, u64)],
+    /// The time events overlap (the same read is counted in several of them),
+    /// so the maximum is taken instead of the sum.
+    overlapping_time: bool,
+    /// Other events that explain the time (shown only if non-zero).
+    evidence: &'static [&'static str],
+    /// For the reasons without the time events: at least one of these has to
+    /// be non-zero (the rest of the evidence is not specific enough).
+    required: &'static [&'static str],
+    hint: &'static str,
+}
+
+const RULES: &[Rule] = &[
+    Rule {
+        name: "CPU",
+        time: &[("UserTimeMicroseconds", 1), ("SystemTimeMicroseconds", 1)],
+        overlapping_time: false,
+        evidence: &["SelectedRows", "SelectedMarks", "FunctionExecute"],
+        required: &[],
+        hint: "read less (filter by the primary key, skipping indexes, PREWHERE) or compute less",
+    },
+    Rule {
+        name: "Disk read",
+        time: &[("DiskReadElapsedMicroseconds", 1)],
+        overlapping_time: false,
+        evidence: &[
+            "OSIOWaitMicroseconds",
+            "ReadBufferFromFileDescriptorReadBytes",
+            "OSReadBytes",
+            "MarkCacheMisses",
+            "UncompressedCacheMisses",
+        ],
+        required: &[],
+        hint: "the data is not in the page cache, read less or use faster disks",
+    },
+    Rule {
+        name: "Remote/S3 read",
+        time: &[
+            ("ReadBufferFromS3Microseconds", 1),
+            ("ReadBufferFromAzureMicroseconds", 1),
+            ("CachedReadBufferReadFromSourceMicroseconds", 1),
+        ],
+        // A read from the source on a cache miss is the S3/Azure read itself
+        overlapping_time: true,
+        evidence: &[
+            "ReadBufferFromS3Bytes",
+            "ReadBufferFromS3RequestsErrors",
+            "CachedReadBufferReadFromSourceBytes",
+            "CachedReadBufferReadFromCacheBytes",
+        ],
+        required: &[],
+        hint: "the object storage is slow, check the filesystem cache hit rate",
+    },
+    Rule {
+        name: "Network",
+        time: &[
+            ("NetworkReceiveElapsedMicroseconds", 1),
+            ("NetworkSendElapsedMicroseconds", 1),
+        ],
+        overlapping_time: false,
+        evidence: &["NetworkReceiveBytes", "NetworkSendBytes"],
+        required: &[],
+        hint: "waiting for the remote shards/replicas or the client",
+    },
+    Rule {
+        name: "Lock contention",
+        time: &[
+            ("ContextLockWaitMicroseconds", 1),
+            ("RWLockReadersWaitMilliseconds", 1000),
+            ("RWLockWritersWaitMilliseconds", 1000),
+            ("MemoryOvercommitWaitTimeMicroseconds", 1),
+        ],
+        overlapping_time: false,
+        evidence: &[
+            "ContextLock",
+            "RWLockAcquiredReadLocks",
+            "RWLockAcquiredWriteLocks",
+        ],
+        required: &[],
+        hint: "contention with other queries (e.g. DDL, or too many concurrent queries)",
+    },
+    Rule {
+        name: "OS CPU wait",
+        time: &[("OSCPUWaitMicroseconds", 1)],
+        overlapping_time: false,
+        evidence: &["OSCPUVirtualTimeMicroseconds"],
+        required: &[],
+        hint: "threads were ready to run but waited for a CPU, the server is overloaded",
+    },
+    Rule {
+        name: "Throttling",
+        time: &[("ThrottlerSleepMicroseconds", 1)],
+        overlapping_time: false,
+        evidence: &[],
+        required: &[],
+        hint: "bandwidth limits (max_*_bandwidth settings) slowed the query down",
+    },
+    Rule {
+        name: "Aggregation states merge",
+        time: &[],
+        overlapping_time: false,
+        evidence: &[
+            "ExternalAggregationWritePart",
+            "ExternalAggregationMerge",
+            "ExternalAggregationCompressedBytes",
+            "AggregationHashTablesInitializedAsTwoLevel",
+        ],
+        // Two level hash tables are used for any large GROUP BY, only the
+        // external aggregation means the spill to disk
+        required: &["ExternalAggregationWritePart", "ExternalAggregationMerge"],
+        hint: "aggregation states were spilled to disk and merged, increase max_bytes_before_external_group_by or reduce the number of keys",
+    },
+];
+
+/// One possible reason of the slowness with the evidence.
+#[derive(Clone, Debug, PartialEq)]
+pub struct QueryFinding {
+    pub name: &'static str,
+    /// Time (summed over all threads), if the reason has the time events.
+    pub time_us: Option<u64>,
+    pub evidence: Vec<(&'static str, u64)>,
+    pub hint: &'static str,
+}
+
+/// Findings for the ProfileEvents of a query, ranked by the time.
+pub fn analyze_query(profile_events: &HashMap<String, u64>) -> Vec<QueryFinding> {
+    let get = |name: &str| profile_events.get(name).copied().unwrap_or(0);
+    let mut findings: Vec<QueryFinding> = RULES
+        .iter()
+        .filter_map(|rule| {
+            let time: Vec<(&'static str, u64)> = rule
+                .time
+                .iter()
+                .map(|(name, _)| (*name, get(name)))
+                .collect();
+            let times = rule
+                .time
+                .iter()
+                .map(|(name, multiplier)| get(name) * multiplier);
+            let time_us = if rule.overlapping_time {
+                times.max().unwrap_or(0)
+            } else {
+                times.sum::<u64>()
+            };
+            let evidence: Vec<(&'static str, u64)> = time
+                .into_iter()
+                .chain(rule.evidence.iter().map(|name| (*name, get(name))))
+                .filter(|(_, value)| *value > 0)
+                .collect();
+            // The evidence alone is not enough if the reason has the time events
+            if evidence.is_empty() || (!rule.time.is_empty() && time_us == 0) {
+                return None;
+            }
+            if !rule.required.is_empty() && rule.required.iter().all(|name| get(name) == 0) {
+                return None;
+            }
+            Some(QueryFinding {
+                name: rule.name,
+                time_us: (!rule.time.is_empty()).then_some(time_us),
+                evidence,
+                hint: rule.hint,
+            })
+        })
+        .collect();
+    findings.sort_by_key(|f| Reverse(f.time_us));
+    findings
+}
+
+/// Text report of the findings, `elapsed` is the query duration in seconds.
+pub fn query_analysis_report(elapsed: f64, findings: &[QueryFinding]) -> String {
+    if findings.is_empty() {
+        return "No ProfileEvents to analyze".to_string();
+    }
+
+    let total_us = findings
+        .iter()
+        .filter_map(|f| f.time_us)
+        .sum::<u64>()
+        .max(1);
+    let mut lines = vec![
+        format!("Elapsed: {:.3}s", elapsed),
+        "Time is summed over all threads, so it can be greater than elapsed".to_string(),
+        String::new(),
+    ];
+    for (i, finding) in findings.iter().enumerate() {
+        let time = match finding.time_us {
+            Some(us) => format!(
+                "{:.3}s ({:.1}% of accounted, {:.1}x elapsed)",
+                us as f64 / 1e6,
+                us as f64 * 100. / total_us as f64,
+                us as f64 / 1e6 / elapsed.max(1e-6),
+            ),
+            None => "no time events".to_string(),
+        };
+        lines.push(format!("{}. {}: {}", i + 1, finding.name, time));
+        let width = finding
+            .evidence
+            .iter()
+            .map(|(name, _)| name.len())
+            .max()
+            .unwrap_or(0);
+        for (name, value) in &finding.evidence {
+            lines.push(format!("   {:width$} {}", name, value, width = width));
+        }
+        lines.push(format!("   -> {}", finding.hint));
+        lines.push(String::new());
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_analyze_query() {
+        let profile_events: HashMap<String, u64> = [
+            ("UserTimeMicroseconds", 1_000_000),
+            ("SystemTimeMicroseconds", 500_000),
+            ("ReadBufferFromS3Microseconds", 3_000_000),
+            ("ReadBufferFromS3Bytes", 1 << 30),
+            ("RWLockReadersWaitMilliseconds", 2_000),
+            ("RWLockAcquiredReadLocks", 3),
+            ("ExternalAggregationWritePart", 4),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+
+        let findings = analyze_query(&profile_events);
+        let ranked: Vec<(&str, Option<u64>)> =
+            findings.iter().map(|f| (f.name, f.time_us)).collect();
+        assert_eq!(
+            ranked,
+            vec![
+                ("Remote/S3 read", Some(3_000_000)),
+                ("Lock contention", Some(2_000_000)),
+                ("CPU", Some(1_500_000)),
+                ("Aggregation states merge", None),
+            ]
+        );
+        assert_eq!(
+            findings[1].evidence,
+            vec![
+                ("RWLockReadersWaitMilliseconds", 2_000),
+                ("RWLockAcquiredReadLocks", 3)
+            ]
+        );
+
+        let report = query_analysis_report(2., &findings);
+        assert!(report.contains("1. Remote/S3 read: 3.000s (46.2% of accounted, 1.5x elapsed)"));
+    }
+
+    #[test]
+    fn test_analyze_query_overlapping_events() {
+        let profile_events: HashMap<String, u64> = [
+            // A cache miss is counted in both
+            ("ReadBufferFromS3Microseconds", 2_000_000),
+            ("CachedReadBufferReadFromSourceMicroseconds", 2_500_000),
+            ("UserTimeMicroseconds", 1_000_000),
+            // Any large GROUP BY, no spill to disk
+            ("AggregationHashTablesInitializedAsTwoLevel", 16),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+
+        let findings = analyze_query(&profile_events);
+        let ranked: Vec<(&str, Option<u64>)> =
+            findings.iter().map(|f| (f.name, f.time_us)).collect();
+        assert_eq!(
+            ranked,
+            vec![
+                ("Remote/S3 read", Some(2_500_000)),
+                ("CPU", Some(1_000_000))
+            ]
+        );
+        let report = query_analysis_report(1., &findings);
+        assert!(report.contains("1. Remote/S3 read: 2.500s (71.4% of accounted, 2.5x elapsed)"));
+    }
+
+    #[test]
+    fn test_analyze_query_empty() {
+        assert!(analyze_query(&HashMap::new()).is_empty());
+        assert_eq!(
+            query_analysis_report(1., &[]),
+            "No ProfileEvents to analyze"
+        );
+    }
+}
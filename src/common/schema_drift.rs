@@ -0,0 +1,106 @@
+/// Definition of a table on one host.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HostSchema {
+    pub host: String,
+    pub create_table_query: String,
+    /// "name type" per line, in the order of the columns.
+    pub columns: String,
+}
+
+/// Definition of a table shared by some hosts.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SchemaVariant {
+    pub hosts: Vec<String>,
+    pub create_table_query: String,
+    pub columns: String,
+}
+
+impl SchemaVariant {
+    /// Text to diff, the columns are added only if they differ between the
+    /// variants (they are in the CREATE statement in most cases anyway).
+    pub fn text(&self, create_table_query: &str, with_columns: bool) -> String {
+        if !with_columns {
+            return create_table_query.to_string();
+        }
+        let columns: Vec<String> = self
+            .columns
+            .lines()
+            .map(|column| format!("-- {}", column))
+            .collect();
+        format!(
+            "{}\n\n-- system.columns:\n{}",
+            create_table_query,
+            columns.join("\n")
+        )
+    }
+}
+
+/// Distinct definitions of the table, the most common first (the reference
+/// for the diffs), and whether the columns differ.
+pub fn schema_variants(schemas: Vec<HostSchema>) -> (Vec<SchemaVariant>, bool) {
+    let mut variants: Vec<SchemaVariant> = Vec::new();
+    for schema in schemas {
+        match variants.iter_mut().find(|v| {
+            v.create_table_query == schema.create_table_query && v.columns == schema.columns
+        }) {
+            Some(variant) => variant.hosts.push(schema.host),
+            None => variants.push(SchemaVariant {
+                hosts: vec![schema.host],
+                create_table_query: schema.create_table_query,
+                columns: schema.columns,
+            }),
+        }
+    }
+    for variant in &mut variants {
+        variant.hosts.sort();
+    }
+    variants.sort_by(|a, b| {
+        b.hosts
+            .len()
+            .cmp(&a.hosts.len())
+            .then(a.hosts.cmp(&b.hosts))
+    });
+    let columns_differ = variants.iter().any(|v| v.columns != variants[0].columns);
+    (variants, columns_differ)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema(host: &str, create_table_query: &str, columns: &str) -> HostSchema {
+        HostSchema {
+            host: host.to_string(),
+            create_table_query: create_table_query.to_string(),
+            columns: columns.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_schema_variants() {
+        let (variants, columns_differ) = schema_variants(vec![
+            schema("c", "CREATE TABLE t (a UInt8, b UInt8)", "a UInt8\nb UInt8"),
+            schema("b", "CREATE TABLE t (a UInt8)", "a UInt8"),
+            schema("a", "CREATE TABLE t (a UInt8, b UInt8)", "a UInt8\nb UInt8"),
+        ]);
+        assert!(columns_differ);
+        assert_eq!(variants.len(), 2);
+        assert_eq!(variants[0].hosts, vec!["a", "c"]);
+        assert_eq!(variants[1].hosts, vec!["b"]);
+        assert_eq!(
+            variants[1].text("CREATE TABLE t (a UInt8)", true),
+            "CREATE TABLE t (a UInt8)\n\n-- system.columns:\n-- a UInt8"
+        );
+
+        let (variants, columns_differ) = schema_variants(vec![
+            schema("a", "CREATE TABLE t (a UInt8) SETTINGS x = 1", "a UInt8"),
+            schema("b", "CREATE TABLE t (a UInt8)", "a UInt8"),
+        ]);
+        assert!(!columns_differ);
+        assert_eq!(variants[0].hosts, vec!["a"]);
+        assert_eq!(
+            variants[1].text("CREATE TABLE t (a UInt8)", false),
+            "CREATE TABLE t (a UInt8)"
+        );
+    }
+}
@@ -0,0 +1,457 @@
+use std::collections::{HashMap, HashSet};
+
+use regex::Regex;
+
+/// (database, table)
+pub type TableName = (String, String);
+
+/// Table of the system.tables with everything the dependencies are built from.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TableDefinition {
+    pub database: String,
+    pub name: String,
+    pub engine: String,
+    pub uuid: String,
+    pub create_table_query: String,
+    /// Objects that depend on the table (dependencies_database/table, i.e.
+    /// materialized views that read from it, dictionaries, ...).
+    pub dependencies: Vec<TableName>,
+    /// Objects the table needs to be loaded (loading_dependencies_database/table).
+    pub loading_dependencies: Vec<TableName>,
+}
+
+/// Edge of the graph, the data flows from -> to.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableDependency {
+    pub from: TableName,
+    pub to: TableName,
+    pub relation: &'static str,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DependencyDirection {
+    Selected,
+    /// Where the data of the selected table comes from.
+    Upstream,
+    /// Where the data of the selected table goes to.
+    Downstream,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DependencyNode {
+    pub table: TableName,
+    pub direction: DependencyDirection,
+    pub depth: usize,
+    /// Relation to the parent node.
+    pub relation: &'static str,
+}
+
+/// Unquoted name (`a`, 'a', "a" or a).
+fn unquote(name: &str) -> String {
+    let name = name.trim();
+    for quote in ['`', '\'', '"'] {
+        if let Some(name) = name
+            .strip_prefix(quote)
+            .and_then(|name| name.strip_suffix(quote))
+        {
+            return name.to_string();
+        }
+    }
+    name.to_string()
+}
+
+/// Qualified name (db.table, with optional quotes), the database defaults to
+/// the given one.
+fn parse_table_name(name: &str, database: &str) -> TableName {
+    let name = name.trim();
+    // Dots inside of the quotes are not separators
+    let mut quoted = None;
+    for (i, c) in name.char_indices() {
+        match (quoted, c) {
+            (None, '`' | '"') => quoted = Some(c),
+            (Some(q), c) if q == c => quoted = None,
+            (None, '.') => return (unquote(&name[..i]), unquote(&name[i + 1..])),
+            _ => {}
+        }
+    }
+    (database.to_string(), unquote(name))
+}
+
+/// Arguments of the ENGINE = <engine>(...) clause (top level commas only).
+fn engine_arguments(create_table_query: &str, engine: &str) -> Option<Vec<String>> {
+    let start = create_table_query.find(&format!("ENGINE = {}(", engine))? + engine.len() + 10;
+    let mut arguments = Vec::new();
+    let mut depth = 0;
+    let mut quoted = None;
+    let mut argument = String::new();
+    for c in create_table_query[start..].chars() {
+        match (quoted, c) {
+            (Some(q), c) if q == c => quoted = None,
+            (Some(_), _) => {}
+            (None, '\'' | '`' | '"') => quoted = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') if depth == 0 => {
+                arguments.push(argument.trim().to_string());
+                return Some(arguments);
+            }
+            (None, ')') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                arguments.push(argument.trim().to_string());
+                argument.clear();
+                continue;
+            }
+            _ => {}
+        }
+        argument.push(c);
+    }
+    None
+}
+
+/// Database argument of the engine (the current one for currentDatabase()).
+fn database_argument(argument: &str, database: &str) -> String {
+    match unquote(argument).as_str() {
+        "" | "currentDatabase()" => database.to_string(),
+        argument => argument.to_string(),
+    }
+}
+
+/// Table of the dictionary with the ClickHouse source (SOURCE(CLICKHOUSE(TABLE 'x' DB 'y'))).
+fn dictionary_source(create_table_query: &str, database: &str) -> Option<TableName> {
+    let source = Regex::new(r"(?is)SOURCE\(CLICKHOUSE\((.*?)\)\)").ok()?;
+    let source = source.captures(create_table_query)?.get(1)?.as_str();
+    let option = |name: &str| -> Option<String> {
+        let option = Regex::new(&format!(r"(?i)\b{}\s+'([^']*)'", name)).ok()?;
+        Some(option.captures(source)?.get(1)?.as_str().to_string())
+    };
+    // Dictionaries that read from a query (or another server) are not dependencies
+    if option("HOST").is_some_and(|host| host != "localhost" && host != "127.0.0.1") {
+        return None;
+    }
+    let table = option("TABLE").filter(|table| !table.is_empty())?;
+    Some((option("DB").unwrap_or_else(|| database.to_string()), table))
+}
+
+/// Edges of the engines that read/write other tables, parsed from the CREATE
+/// statement (neither of them is in the dependencies of system.tables).
+fn engine_dependencies(
+    table: &TableDefinition,
+    tables: &[TableDefinition],
+) -> Vec<TableDependency> {
+    let this = (table.database.clone(), table.name.clone());
+    let query = table.create_table_query.as_str();
+    let mut edges = Vec::new();
+    let mut edge = |from: TableName, to: TableName, relation| {
+        edges.push(TableDependency { from, to, relation });
+    };
+    match table.engine.as_str() {
+        "MaterializedView" => {
+            let to =
+                Regex::new(r"^CREATE MATERIALIZED VIEW (?:`[^`]+`|\S)+ TO ((?:`[^`]+`|[^\s(])+)")
+                    .ok()
+                    .and_then(|re| Some(re.captures(query)?.get(1)?.as_str().to_string()));
+            match to {
+                Some(to) => edge(this, parse_table_name(&to, &table.database), "TO table"),
+                None => {
+                    // Implicit target (.inner_id.<uuid> for Atomic, .inner.<name> for Ordinary)
+                    let inner = [
+                        format!(".inner_id.{}", table.uuid),
+                        format!(".inner.{}", table.name),
+                    ];
+                    if let Some(inner) = tables
+                        .iter()
+                        .find(|t| t.database == table.database && inner.contains(&t.name))
+                    {
+                        edge(
+                            this,
+                            (inner.database.clone(), inner.name.clone()),
+                            "inner table",
+                        );
+                    }
+                }
+            }
+        }
+        "Distributed" => {
+            if let Some(arguments) = engine_arguments(query, "Distributed")
+                && arguments.len() >= 3
+            {
+                let database = database_argument(&arguments[1], &table.database);
+                edge((database, unquote(&arguments[2])), this, "Distributed");
+            }
+        }
+        "Buffer" => {
+            if let Some(arguments) = engine_arguments(query, "Buffer")
+                && arguments.len() >= 2
+                && !unquote(&arguments[1]).is_empty()
+            {
+                let database = database_argument(&arguments[0], &table.database);
+                edge(this, (database, unquote(&arguments[1])), "Buffer flush");
+            }
+        }
+        "Merge" => {
+            if let Some(arguments) = engine_arguments(query, "Merge")
+                && arguments.len() >= 2
+            {
+                let database = match arguments[0].strip_prefix("REGEXP(") {
+                    Some(regexp) => unquote(regexp.trim_end_matches(')')),
+                    None => format!(
+                        "^{}$",
+                        regex::escape(&database_argument(&arguments[0], &table.database))
+                    ),
+                };
+                if let (Ok(database), Ok(name)) =
+                    (Regex::new(&database), Regex::new(&unquote(&arguments[1])))
+                {
+                    for source in tables {
+                        if database.is_match(&source.database)
+                            && name.is_match(&source.name)
+                            && (source.database != table.database || source.name != table.name)
+                        {
+                            edge(
+                                (source.database.clone(), source.name.clone()),
+                                this.clone(),
+                                "Merge",
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        "Dictionary" => {
+            if query.starts_with("CREATE DICTIONARY") {
+                if let Some(source) = dictionary_source(query, &table.database) {
+                    edge(source, this, "dictionary source");
+                }
+            } else if let Some(arguments) = engine_arguments(query, "Dictionary")
+                && let Some(dictionary) = arguments.first()
+            {
+                edge(
+                    parse_table_name(dictionary, &table.database),
+                    this,
+                    "Dictionary",
+                );
+            }
+        }
+        _ => {}
+    }
+    edges
+}
+
+/// All the edges between the tables (duplicates are removed, the engines
+/// relations have precedence, since they are more specific).
+pub fn table_dependencies(tables: &[TableDefinition]) -> Vec<TableDependency> {
+    let engines: HashMap<TableName, &str> = tables
+        .iter()
+        .map(|t| ((t.database.clone(), t.name.clone()), t.engine.as_str()))
+        .collect();
+
+    let mut edges: Vec<TableDependency> = tables
+        .iter()
+        .flat_map(|table| engine_dependencies(table, tables))
+        .collect();
+    for table in tables {
+        let this = (table.database.clone(), table.name.clone());
+        for dependent in &table.dependencies {
+            let relation = match engines.get(dependent).copied() {
+                Some("MaterializedView") => "materialized view",
+                Some("Dictionary") => "dictionary",
+                Some("View") => "view",
+                _ => "dependency",
+            };
+            edges.push(TableDependency {
+                from: this.clone(),
+                to: dependent.clone(),
+                relation,
+            });
+        }
+        for dependency in &table.loading_dependencies {
+            edges.push(TableDependency {
+                from: dependency.clone(),
+                to: this.clone(),
+                relation: "loading dependency",
+            });
+        }
+    }
+
+    let mut seen = HashSet::new();
+    edges.retain(|edge| {
+        edge.from != edge.to
+            && seen.insert((edge.from.clone(), edge.to.clone()))
+            && !seen.contains(&(edge.to.clone(), edge.from.clone()))
+    });
+    edges
+}
+
+/// Rows of the subtree of the node (depth first, every table once).
+fn dependencies_subtree(
+    edges: &[TableDependency],
+    node: DependencyNode,
+    seen: &mut HashSet<TableName>,
+) -> Vec<DependencyNode> {
+    let direction = node.direction;
+    let depth = node.depth;
+    let table = node.table.clone();
+    let mut nodes = vec![node];
+    for edge in edges {
+        let (parent, child) = match direction {
+            DependencyDirection::Upstream => (&edge.to, &edge.from),
+            _ => (&edge.from, &edge.to),
+        };
+        if *parent == table && seen.insert(child.clone()) {
+            let child = DependencyNode {
+                table: child.clone(),
+                direction,
+                depth: depth + 1,
+                relation: edge.relation,
+            };
+            nodes.extend(dependencies_subtree(edges, child, seen));
+        }
+    }
+    nodes
+}
+
+/// Upstream tree of the table (the sources, recursively), the table itself and
+/// the downstream tree (the destinations, recursively).
+pub fn dependencies_tree(edges: &[TableDependency], table: &TableName) -> Vec<DependencyNode> {
+    let mut edges = edges.to_vec();
+    edges.sort_by(|a, b| (&a.from, &a.to).cmp(&(&b.from, &b.to)));
+
+    let mut nodes = Vec::new();
+    for direction in [
+        DependencyDirection::Upstream,
+        DependencyDirection::Downstream,
+    ] {
+        let mut seen = HashSet::from([table.clone()]);
+        let root = DependencyNode {
+            table: table.clone(),
+            direction,
+            depth: 0,
+            relation: "",
+        };
+        let subtree = dependencies_subtree(&edges, root, &mut seen);
+        if direction == DependencyDirection::Downstream {
+            nodes.push(DependencyNode {
+                direction: DependencyDirection::Selected,
+                ..subtree[0].clone()
+            });
+        }
+        nodes.extend(subtree.into_iter().skip(1));
+    }
+    nodes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(database: &str, name: &str, engine: &str, query: &str) -> TableDefinition {
+        TableDefinition {
+            database: database.to_string(),
+            name: name.to_string(),
+            engine: engine.to_string(),
+            create_table_query: query.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn name(database: &str, name: &str) -> TableName {
+        (database.to_string(), name.to_string())
+    }
+
+    #[test]
+    fn test_engine_arguments() {
+        assert_eq!(
+            engine_arguments(
+                "CREATE TABLE db.d (`a` UInt8) ENGINE = Distributed('c', 'db', 'data', cityHash64(a, ','))",
+                "Distributed"
+            ),
+            Some(vec![
+                "'c'".to_string(),
+                "'db'".to_string(),
+                "'data'".to_string(),
+                "cityHash64(a, ',')".to_string()
+            ])
+        );
+        assert_eq!(
+            engine_arguments("CREATE TABLE t ENGINE = Log", "Buffer"),
+            None
+        );
+        assert_eq!(parse_table_name("`my.db`.t", "default"), name("my.db", "t"));
+        assert_eq!(parse_table_name("t", "default"), name("default", "t"));
+    }
+
+    #[test]
+    fn test_dependencies_tree() {
+        let mut data = table(
+            "db",
+            "data",
+            "MergeTree",
+            "CREATE TABLE db.data (`a` UInt8) ENGINE = MergeTree ORDER BY a",
+        );
+        data.dependencies = vec![name("db", "mv"), name("db", "dict")];
+        let tables = vec![
+            data,
+            table(
+                "db",
+                "data_dist",
+                "Distributed",
+                "CREATE TABLE db.data_dist (`a` UInt8) ENGINE = Distributed('c', currentDatabase(), 'data', rand())",
+            ),
+            table(
+                "db",
+                "data_buffer",
+                "Buffer",
+                "CREATE TABLE db.data_buffer (`a` UInt8) ENGINE = Buffer('db', 'data', 1, 10, 100, 10000, 1000000, 10000000, 100000000)",
+            ),
+            table(
+                "db",
+                "mv",
+                "MaterializedView",
+                "CREATE MATERIALIZED VIEW db.mv TO db.agg (`a` UInt8) AS SELECT a FROM db.data",
+            ),
+            table(
+                "db",
+                "agg",
+                "MergeTree",
+                "CREATE TABLE db.agg (`a` UInt8) ENGINE = MergeTree ORDER BY a",
+            ),
+            table(
+                "db",
+                "all",
+                "Merge",
+                "CREATE TABLE db.all (`a` UInt8) ENGINE = Merge('db', '^(data|agg)$')",
+            ),
+            table(
+                "db",
+                "dict",
+                "Dictionary",
+                "CREATE DICTIONARY db.dict (`a` UInt8) PRIMARY KEY a SOURCE(CLICKHOUSE(TABLE 'data' DB 'db')) LIFETIME(MIN 0 MAX 300) LAYOUT(FLAT())",
+            ),
+        ];
+        let edges = table_dependencies(&tables);
+        // Engine relation wins over the dependencies one
+        assert!(edges.contains(&TableDependency {
+            from: name("db", "data"),
+            to: name("db", "dict"),
+            relation: "dictionary source",
+        }));
+
+        let nodes: Vec<(String, DependencyDirection, usize, &str)> =
+            dependencies_tree(&edges, &name("db", "data"))
+                .into_iter()
+                .map(|n| (n.table.1, n.direction, n.depth, n.relation))
+                .collect();
+        use DependencyDirection::*;
+        assert_eq!(
+            nodes,
+            vec![
+                ("data_buffer".to_string(), Upstream, 1, "Buffer flush"),
+                ("data".to_string(), Selected, 0, ""),
+                ("all".to_string(), Downstream, 1, "Merge"),
+                ("data_dist".to_string(), Downstream, 1, "Distributed"),
+                ("dict".to_string(), Downstream, 1, "dictionary source"),
+                ("mv".to_string(), Downstream, 1, "materialized view"),
+                ("agg".to_string(), Downstream, 2, "TO table"),
+            ]
+        );
+    }
+}
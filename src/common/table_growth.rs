@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use size::{Base, SizeFormatter, Style};
+
+/// Data added to and removed from the table over one bucket of time.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GrowthBucket {
+    pub bytes_added: f64,
+    pub bytes_removed: f64,
+    pub rows_added: f64,
+    pub rows_removed: f64,
+}
+
+/// Growth of the table over the interval, the buckets are summed over the
+/// hosts, while the projection is per host (each of them has its own disks).
+#[derive(Clone, Debug, Default)]
+pub struct TableGrowth {
+    pub buckets: Vec<GrowthBucket>,
+    /// Length of the interval in seconds.
+    pub interval: f64,
+    /// Net bytes added over the interval by host.
+    pub host_growth: HashMap<String, f64>,
+    /// Free space of the disks of the table by host.
+    pub free_space: HashMap<String, u64>,
+}
+
+fn format_duration(seconds: f64) -> String {
+    let hours = seconds / 3600.;
+    if hours >= 48. {
+        format!("{:.1} days", hours / 24.)
+    } else if hours >= 1. {
+        format!("{:.1} hours", hours)
+    } else {
+        format!("{:.0} minutes", seconds / 60.)
+    }
+}
+
+impl TableGrowth {
+    pub fn new(buckets: usize, interval: f64) -> Self {
+        Self {
+            buckets: vec![GrowthBucket::default(); buckets],
+            interval,
+            ..Default::default()
+        }
+    }
+
+    /// Adds the bucket of the host (the ones out of range are ignored).
+    pub fn add(&mut self, host: &str, bucket: usize, growth: GrowthBucket) {
+        let Some(total) = self.buckets.get_mut(bucket) else {
+            return;
+        };
+        total.bytes_added += growth.bytes_added;
+        total.bytes_removed += growth.bytes_removed;
+        total.rows_added += growth.rows_added;
+        total.rows_removed += growth.rows_removed;
+        *self.host_growth.entry(host.to_string()).or_default() +=
+            growth.bytes_added - growth.bytes_removed;
+    }
+
+    pub fn series(&self, value: fn(&GrowthBucket) -> f64) -> Vec<f64> {
+        self.buckets.iter().map(value).collect()
+    }
+
+    /// Projected time (in seconds) until the disks of the first host run out
+    /// of space with the growth rate of the interval, and the host. None if
+    /// the table does not grow.
+    pub fn time_to_full(&self) -> Option<(f64, &str)> {
+        self.host_growth
+            .iter()
+            .filter(|(_, growth)| **growth > 0.)
+            .filter_map(|(host, growth)| {
+                let free_space = *self.free_space.get(host)? as f64;
+                Some((free_space / (growth / self.interval), host.as_str()))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+    }
+
+    pub fn report(&self) -> String {
+        let fmt = SizeFormatter::new()
+            .with_base(Base::Base2)
+            .with_style(Style::Abbreviated);
+        let sum = |value: fn(&GrowthBucket) -> f64| self.series(value).iter().sum::<f64>();
+
+        let bytes_added = sum(|b| b.bytes_added);
+        let bytes_removed = sum(|b| b.bytes_removed);
+        let net = bytes_added - bytes_removed;
+        let mut lines = vec![
+            format!(
+                "Added: {} ({:.0} rows), removed: {} ({:.0} rows)",
+                fmt.format(bytes_added as i64),
+                sum(|b| b.rows_added),
+                fmt.format(bytes_removed as i64),
+                sum(|b| b.rows_removed),
+            ),
+            format!(
+                "Net: {}{} ({}{} per day)",
+                if net < 0. { "-" } else { "+" },
+                fmt.format(net.abs() as i64),
+                if net < 0. { "-" } else { "+" },
+                fmt.format((net.abs() / self.interval.max(1.) * 86400.) as i64),
+            ),
+        ];
+        lines.push(match self.time_to_full() {
+            Some((seconds, host)) => format!(
+                "Time to full: {} ({} free on {})",
+                format_duration(seconds),
+                fmt.format(self.free_space[host] as i64),
+                host
+            ),
+            None if self.host_growth.values().any(|growth| *growth > 0.) => {
+                "Time to full: unknown (no free space of the disks)".to_string()
+            }
+            None => "Time to full: never (the table does not grow)".to_string(),
+        });
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bucket(bytes_added: f64, bytes_removed: f64) -> GrowthBucket {
+        GrowthBucket {
+            bytes_added,
+            bytes_removed,
+            rows_added: bytes_added / 10.,
+            rows_removed: bytes_removed / 10.,
+        }
+    }
+
+    #[test]
+    fn test_table_growth() {
+        let mut growth = TableGrowth::new(2, 3600.);
+        growth.add("a", 0, bucket(1000., 0.));
+        growth.add("a", 1, bucket(1000., 200.));
+        growth.add("b", 1, bucket(100., 100.));
+        growth.add("b", 5, bucket(100., 0.));
+
+        assert_eq!(growth.series(|b| b.bytes_added), vec![1000., 1100.]);
+        assert_eq!(growth.series(|b| b.rows_removed), vec![0., 30.]);
+        assert_eq!(growth.time_to_full(), None);
+        assert!(
+            growth
+                .report()
+                .ends_with("Time to full: unknown (no free space of the disks)")
+        );
+
+        // 1800 bytes per hour, "b" does not grow
+        growth.free_space.insert("a".to_string(), 3600);
+        growth.free_space.insert("b".to_string(), 10);
+        assert_eq!(growth.time_to_full(), Some((7200., "a")));
+        assert_eq!(
+            growth.report(),
+            "Added: 2.05 KiB (210 rows), removed: 300 B (30 rows)\n\
+             Net: +1.76 KiB (+42.2 KiB per day)\n\
+             Time to full: 2.0 hours (3.52 KiB free on a)"
+        );
+    }
+
+    #[test]
+    fn test_table_growth_shrinks() {
+        let mut growth = TableGrowth::new(1, 60.);
+        growth.add("a", 0, bucket(0., 1024.));
+        growth.free_space.insert("a".to_string(), 1);
+        assert_eq!(growth.time_to_full(), None);
+        assert_eq!(
+            growth.report(),
+            "Added: 0 B (0 rows), removed: 1.00 KiB (102 rows)\n\
+             Net: -1.00 KiB (-1.41 MiB per day)\n\
+             Time to full: never (the table does not grow)"
+        );
+    }
+}
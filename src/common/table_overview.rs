@@ -0,0 +1,365 @@
+use size::{Base, SizeFormatter, Style};
+
+// Share of parts_to_delay_insert from which the parts count is a warning
+const PARTS_WARNING_RATIO: f64 = 0.5;
+
+/// Byte offsets of the top-level words (outside quotes and parentheses) of
+/// the statement, with the offsets of the top-level commas.
+fn top_level_tokens(statement: &str) -> (Vec<(usize, &str)>, Vec<usize>) {
+    let mut words = Vec::new();
+    let mut commas = Vec::new();
+    let mut depth = 0_usize;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut word_start: Option<usize> = None;
+    for (i, c) in statement.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        let is_word = c.is_ascii_alphanumeric() || c == '_';
+        if let Some(start) = word_start
+            && !is_word
+        {
+            words.push((start, &statement[start..i]));
+            word_start = None;
+        }
+        match c {
+            '\'' | '"' | '`' => quote = Some(c),
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => commas.push(i),
+            _ if is_word && depth == 0 && word_start.is_none() => word_start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(start) = word_start {
+        words.push((start, &statement[start..]));
+    }
+    (words, commas)
+}
+
+/// (name, value) pairs of the SETTINGS clause of the CREATE statement (the
+/// table one, i.e. the last top-level one, columns can have SETTINGS as well),
+/// up to the COMMENT clause. Commas in quotes and parentheses (e.g.
+/// `disk = disk(type = s3, ...)`) do not separate the settings.
+fn table_settings(create_statement: &str) -> Vec<(&str, &str)> {
+    let (words, commas) = top_level_tokens(create_statement);
+    let Some(start) = words
+        .iter()
+        .rposition(|(_, word)| *word == "SETTINGS")
+        .map(|i| words[i].0 + "SETTINGS".len())
+    else {
+        return Vec::new();
+    };
+    let end = words
+        .iter()
+        .find(|(offset, word)| *offset > start && *word == "COMMENT")
+        .map_or(create_statement.len(), |(offset, _)| *offset);
+
+    let mut settings = Vec::new();
+    let mut setting_start = start;
+    for offset in commas
+        .into_iter()
+        .filter(|offset| *offset > start && *offset < end)
+        .chain([end])
+    {
+        if let Some((name, value)) = create_statement[setting_start..offset].split_once('=') {
+            settings.push((name.trim(), value.trim()));
+        }
+        setting_start = offset + 1;
+    }
+    settings
+}
+
+/// Unquoted value of a setting: a string literal is unescaped, anything else
+/// (numbers, `disk(...)`, ...) is kept as is.
+fn setting_value(value: &str) -> String {
+    let Some(quoted) = value
+        .strip_prefix('\'')
+        .and_then(|value| value.strip_suffix('\''))
+    else {
+        return value.to_string();
+    };
+    let mut unquoted = String::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unquoted.extend(chars.next()),
+            // '' is an escaped quote as well
+            '\'' => {
+                unquoted.push(c);
+                chars.next();
+            }
+            _ => unquoted.push(c),
+        }
+    }
+    unquoted
+}
+
+/// MergeTree settings overridden in the SETTINGS clause of the CREATE
+/// statement of the table, with the values unquoted.
+pub fn merge_tree_table_settings(create_statement: &str) -> Vec<(String, String)> {
+    table_settings(create_statement)
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), setting_value(value)))
+        .collect()
+}
+
+/// Value of the MergeTree setting from the SETTINGS clause of the CREATE
+/// statement (the per-table override), if any.
+pub fn merge_tree_setting_override(create_statement: &str, name: &str) -> Option<String> {
+    merge_tree_table_settings(create_statement)
+        .into_iter()
+        .find(|(setting_name, _)| setting_name == name)
+        .map(|(_, value)| value)
+        .filter(|value| !value.is_empty())
+}
+
+/// system.replicas of a replicated table (the worst over the hosts).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReplicaStatus {
+    pub is_readonly: bool,
+    pub absolute_delay: u64,
+    pub queue_size: u64,
+    pub active_replicas: u64,
+    pub total_replicas: u64,
+}
+
+/// Failed event of the system.part_log.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PartLogError {
+    pub event_time: String,
+    pub event_type: String,
+    pub part_name: String,
+    pub exception: String,
+}
+
+/// Recent activity of the table from the system.part_log.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PartLogStats {
+    pub new_parts_last_hour: u64,
+    pub inserted_rows_last_hour: u64,
+    pub errors: Vec<PartLogError>,
+}
+
+/// Health of a table at a glance (see TableOverview::report()).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TableOverview {
+    pub database: String,
+    pub table: String,
+    pub create_statement: String,
+    pub active_parts: u64,
+    pub partitions: u64,
+    pub max_parts_in_partition: u64,
+    pub rows: u64,
+    pub compressed_bytes: u64,
+    pub uncompressed_bytes: u64,
+    pub parts_to_delay_insert: u64,
+    pub parts_to_throw_insert: u64,
+    /// None for non-replicated tables.
+    pub replica: Option<ReplicaStatus>,
+    pub merges: u64,
+    pub mutations: u64,
+    pub failed_mutation: Option<String>,
+    /// None if system.part_log is not available.
+    pub part_log: Option<PartLogStats>,
+}
+
+impl TableOverview {
+    /// Whether the parts of a partition get close to parts_to_delay_insert.
+    pub fn too_many_parts(&self) -> bool {
+        self.parts_to_delay_insert > 0
+            && self.max_parts_in_partition as f64
+                >= self.parts_to_delay_insert as f64 * PARTS_WARNING_RATIO
+    }
+
+    /// Text of the sections, the keys in brackets open the detailed views.
+    pub fn report(&self) -> String {
+        let fmt = SizeFormatter::new()
+            .with_base(Base::Base2)
+            .with_style(Style::Abbreviated);
+
+        let mut lines = vec![
+            "Parts [p], partitions [P]".to_string(),
+            format!(
+                "  Active parts: {} in {} partitions",
+                self.active_parts, self.partitions
+            ),
+            format!(
+                "  Max parts in partition: {} (parts_to_delay_insert: {}, parts_to_throw_insert: {}){}",
+                self.max_parts_in_partition,
+                self.parts_to_delay_insert,
+                self.parts_to_throw_insert,
+                if self.too_many_parts() {
+                    " - too many parts!"
+                } else {
+                    ""
+                },
+            ),
+            format!("  Rows: {}", self.rows),
+            format!(
+                "  Size: {} compressed, {} uncompressed (ratio {:.2})",
+                fmt.format(self.compressed_bytes as i64),
+                fmt.format(self.uncompressed_bytes as i64),
+                self.uncompressed_bytes as f64 / self.compressed_bytes.max(1) as f64,
+            ),
+            String::new(),
+            "Replication".to_string(),
+        ];
+        match &self.replica {
+            Some(replica) => {
+                lines.push(format!(
+                    "  Replicas: {}/{} active{}",
+                    replica.active_replicas,
+                    replica.total_replicas,
+                    if replica.is_readonly {
+                        ", readonly!"
+                    } else {
+                        ""
+                    },
+                ));
+                lines.push(format!(
+                    "  Delay: {}s, queue: {}",
+                    replica.absolute_delay, replica.queue_size
+                ));
+            }
+            None => lines.push("  Not replicated".to_string()),
+        }
+
+        lines.push(String::new());
+        lines.push("Merges [m] and mutations [u]".to_string());
+        lines.push(format!("  Active merges: {}", self.merges));
+        lines.push(format!("  Unfinished mutations: {}", self.mutations));
+        if let Some(reason) = &self.failed_mutation {
+            lines.push(format!("  Failed mutation: {}", reason));
+        }
+
+        lines.push(String::new());
+        lines.push("Part log [l]".to_string());
+        match &self.part_log {
+            Some(part_log) => {
+                lines.push(format!(
+                    "  Last hour: {} new parts, {} rows ({:.2} parts/min)",
+                    part_log.new_parts_last_hour,
+                    part_log.inserted_rows_last_hour,
+                    part_log.new_parts_last_hour as f64 / 60.,
+                ));
+                if part_log.errors.is_empty() {
+                    lines.push("  No errors for the last day".to_string());
+                } else {
+                    lines.push("  Recent errors:".to_string());
+                }
+                for error in &part_log.errors {
+                    lines.push(format!(
+                        "  {} {} {}: {}",
+                        error.event_time,
+                        error.event_type,
+                        error.part_name,
+                        error.exception.lines().next().unwrap_or_default()
+                    ));
+                }
+            }
+            None => lines.push("  system.part_log is not available".to_string()),
+        }
+
+        lines.push(String::new());
+        // Pending inserts (system.asynchronous_inserts), nothing to summarize
+        lines.push("Asynchronous inserts queue [i]".to_string());
+        lines.push(String::new());
+        lines.push("CREATE [c]".to_string());
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_tree_setting_override() {
+        let create = "CREATE TABLE db.t (`s` String SETTINGS (max_compress_block_size = 1))\n\
+                      ENGINE = MergeTree ORDER BY s\n\
+                      SETTINGS index_granularity = 8192, parts_to_delay_insert = 500, storage_policy = 'default'";
+        assert_eq!(
+            merge_tree_setting_override(create, "parts_to_delay_insert"),
+            Some("500".to_string())
+        );
+        assert_eq!(
+            merge_tree_setting_override(create, "storage_policy"),
+            Some("default".to_string())
+        );
+        assert_eq!(
+            merge_tree_setting_override(create, "max_compress_block_size"),
+            None
+        );
+        assert_eq!(
+            merge_tree_setting_override("CREATE TABLE t ENGINE = Log", "parts_to_delay_insert"),
+            None
+        );
+
+        // The table comment goes after the settings
+        let create = "CREATE TABLE db.t (`s` String COMMENT 'SETTINGS a = 1')\n\
+                      ENGINE = MergeTree ORDER BY s\n\
+                      SETTINGS index_granularity = 8192 COMMENT 'x, SETTINGS y = 1'";
+        assert_eq!(
+            merge_tree_setting_override(create, "index_granularity"),
+            Some("8192".to_string())
+        );
+        assert_eq!(merge_tree_setting_override(create, "y"), None);
+        assert_eq!(merge_tree_setting_override(create, "a"), None);
+
+        // Commas in the values
+        let create = "CREATE TABLE db.t (`s` String) ENGINE = MergeTree ORDER BY s \
+                      SETTINGS disk = disk(type = s3, endpoint = 'http://s3/a,b'), index_granularity = 1024";
+        assert_eq!(
+            merge_tree_setting_override(create, "disk"),
+            Some("disk(type = s3, endpoint = 'http://s3/a,b')".to_string())
+        );
+        assert_eq!(
+            merge_tree_setting_override(create, "index_granularity"),
+            Some("1024".to_string())
+        );
+
+        // Any depth of the nested disks, escaped quotes
+        let create = "CREATE TABLE db.t (`s` String) ENGINE = MergeTree ORDER BY s \
+                      SETTINGS disk = disk(type = cache, path = '/c/', disk = disk(type = s3, endpoint = 'http://s3/(a)')), \
+                      storage_policy = 'it\\'s', merge_selector_algorithm = 'a''b'";
+        assert_eq!(
+            merge_tree_table_settings(create),
+            vec![
+                (
+                    "disk".to_string(),
+                    "disk(type = cache, path = '/c/', disk = disk(type = s3, endpoint = 'http://s3/(a)'))"
+                        .to_string()
+                ),
+                ("storage_policy".to_string(), "it's".to_string()),
+                ("merge_selector_algorithm".to_string(), "a'b".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_report() {
+        let overview = TableOverview {
+            active_parts: 200,
+            partitions: 2,
+            max_parts_in_partition: 160,
+            parts_to_delay_insert: 300,
+            parts_to_throw_insert: 600,
+            ..Default::default()
+        };
+        assert!(overview.too_many_parts());
+        let report = overview.report();
+        assert!(report.contains("Max parts in partition: 160 (parts_to_delay_insert: 300, parts_to_throw_insert: 600) - too many parts!"));
+        assert!(report.contains("  Not replicated"));
+        assert!(report.contains("Part log [l]\n  system.part_log is not available"));
+        assert!(report.contains("Asynchronous inserts queue [i]"));
+    }
+}
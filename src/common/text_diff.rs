@@ -0,0 +1,218 @@
+// Larger inputs are not aligned (the LCS table is quadratic), but paired
+// line by line, which is still readable for the formatted queries
+const MAX_LCS_CELLS: usize = 4_000_000;
+
+/// Part of a line, and whether it is missing on the other side.
+pub type DiffSegment = (String, bool);
+
+/// Row of a side by side diff, `None` - there is no such line on this side.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiffRow {
+    pub left: Option<Vec<DiffSegment>>,
+    pub right: Option<Vec<DiffSegment>>,
+}
+
+impl DiffRow {
+    pub fn is_changed(&self) -> bool {
+        let changed = |side: &Option<Vec<DiffSegment>>| match side {
+            Some(segments) => segments.iter().any(|(_, changed)| *changed),
+            None => true,
+        };
+        changed(&self.left) || changed(&self.right)
+    }
+}
+
+/// Indexes of the matched items of the longest common subsequence.
+fn lcs_pairs<T: PartialEq>(a: &[T], b: &[T]) -> Vec<(usize, usize)> {
+    if a.len().saturating_mul(b.len()) > MAX_LCS_CELLS {
+        return Vec::new();
+    }
+    // lengths[i][j] - LCS of a[i..] and b[j..]
+    let width = b.len() + 1;
+    let mut lengths = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i * width + j] = if a[i] == b[j] {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut pairs = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] == b[j] {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+/// Words, runs of whitespace and single punctuation characters.
+fn tokenize(line: &str) -> Vec<&str> {
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            0
+        } else if c.is_whitespace() {
+            1
+        } else {
+            2
+        }
+    };
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut prev: Option<u8> = None;
+    for (i, c) in line.char_indices() {
+        let current = class(c);
+        if let Some(prev) = prev
+            && (prev != current || current == 2)
+        {
+            tokens.push(&line[start..i]);
+            start = i;
+        }
+        prev = Some(current);
+    }
+    if start < line.len() {
+        tokens.push(&line[start..]);
+    }
+    tokens
+}
+
+/// Segments of both lines, unmatched tokens are changed.
+fn diff_line(left: &str, right: &str) -> (Vec<DiffSegment>, Vec<DiffSegment>) {
+    let left_tokens = tokenize(left);
+    let right_tokens = tokenize(right);
+    let pairs = lcs_pairs(&left_tokens, &right_tokens);
+
+    let segments = |tokens: &[&str], matched: &mut dyn Iterator<Item = usize>| {
+        let mut matched = matched.peekable();
+        let mut segments: Vec<DiffSegment> = Vec::new();
+        for (i, token) in tokens.iter().enumerate() {
+            let changed = matched.next_if_eq(&i).is_none();
+            match segments.last_mut() {
+                Some((text, last_changed)) if *last_changed == changed => text.push_str(token),
+                _ => segments.push((token.to_string(), changed)),
+            }
+        }
+        segments
+    };
+    (
+        segments(&left_tokens, &mut pairs.iter().map(|(i, _)| *i)),
+        segments(&right_tokens, &mut pairs.iter().map(|(_, j)| *j)),
+    )
+}
+
+/// Side by side diff of two texts: the equal lines are aligned, the changed
+/// ones are paired and their changed tokens are marked.
+pub fn diff_texts(left: &str, right: &str) -> Vec<DiffRow> {
+    let left_lines: Vec<&str> = left.lines().collect();
+    let right_lines: Vec<&str> = right.lines().collect();
+    let whole = |line: &str, changed: bool| Some(vec![(line.to_string(), changed)]);
+
+    let mut rows = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    let anchors = lcs_pairs(&left_lines, &right_lines)
+        .into_iter()
+        .chain(std::iter::once((left_lines.len(), right_lines.len())));
+    for (next_i, next_j) in anchors {
+        // Changed lines between the equal ones
+        while i < next_i || j < next_j {
+            let row = match (i < next_i, j < next_j) {
+                (true, true) => {
+                    let (left, right) = diff_line(left_lines[i], right_lines[j]);
+                    i += 1;
+                    j += 1;
+                    DiffRow {
+                        left: Some(left),
+                        right: Some(right),
+                    }
+                }
+                (true, false) => {
+                    i += 1;
+                    DiffRow {
+                        left: whole(left_lines[i - 1], true),
+                        right: None,
+                    }
+                }
+                _ => {
+                    j += 1;
+                    DiffRow {
+                        left: None,
+                        right: whole(right_lines[j - 1], true),
+                    }
+                }
+            };
+            rows.push(row);
+        }
+        if next_i < left_lines.len() {
+            rows.push(DiffRow {
+                left: whole(left_lines[next_i], false),
+                right: whole(right_lines[next_j], false),
+            });
+            i = next_i + 1;
+            j = next_j + 1;
+        }
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segments(segments: &[(&str, bool)]) -> Option<Vec<DiffSegment>> {
+        Some(
+            segments
+                .iter()
+                .map(|(text, changed)| (text.to_string(), *changed))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize("WHERE (id = 1)"),
+            vec!["WHERE", " ", "(", "id", " ", "=", " ", "1", ")"]
+        );
+        assert!(tokenize("").is_empty());
+    }
+
+    #[test]
+    fn test_diff_texts() {
+        let left = "SELECT a\nFROM t\nWHERE id = 1\nLIMIT 10";
+        let right = "SELECT a\nFROM t\nWHERE id = 2\nORDER BY a\nLIMIT 10";
+        let rows = diff_texts(left, right);
+        assert_eq!(rows.len(), 5);
+        assert!(!rows[0].is_changed());
+        assert!(!rows[1].is_changed());
+        assert_eq!(
+            rows[2].left,
+            segments(&[("WHERE id = ", false), ("1", true)])
+        );
+        assert_eq!(
+            rows[2].right,
+            segments(&[("WHERE id = ", false), ("2", true)])
+        );
+        assert_eq!(rows[3].left, None);
+        assert_eq!(rows[3].right, segments(&[("ORDER BY a", true)]));
+        assert!(!rows[4].is_changed());
+
+        assert!(
+            diff_texts("SELECT 1", "SELECT 1")
+                .iter()
+                .all(|r| !r.is_changed())
+        );
+        let rows = diff_texts("", "SELECT 1");
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].left, None);
+    }
+}
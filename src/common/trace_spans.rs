@@ -0,0 +1,111 @@
+use std::collections::{HashMap, HashSet};
+
+/// Span of the system.opentelemetry_span_log.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TraceSpan {
+    pub trace_id: String,
+    pub span_id: u64,
+    pub parent_span_id: u64,
+    pub operation_name: String,
+    pub host_name: String,
+    pub start_time_us: u64,
+    pub finish_time_us: u64,
+    pub attributes: Vec<(String, String)>,
+}
+
+impl TraceSpan {
+    pub fn duration_us(&self) -> u64 {
+        self.finish_time_us.saturating_sub(self.start_time_us)
+    }
+}
+
+/// Spans in the tree order (parents before children, siblings by the start
+/// time) with their depth. Spans without a parent among `spans` (i.e. the
+/// parent is outside of the time window or had not been flushed yet) are
+/// roots.
+pub fn spans_tree_order(spans: &[TraceSpan]) -> Vec<(usize, usize)> {
+    let ids: HashMap<(&str, u64), usize> = spans
+        .iter()
+        .enumerate()
+        .map(|(i, span)| ((span.trace_id.as_str(), span.span_id), i))
+        .collect();
+
+    let mut roots = Vec::new();
+    let mut children = vec![Vec::new(); spans.len()];
+    for (i, span) in spans.iter().enumerate() {
+        match ids.get(&(span.trace_id.as_str(), span.parent_span_id)) {
+            Some(&parent) if span.parent_span_id != 0 && parent != i => children[parent].push(i),
+            _ => roots.push(i),
+        }
+    }
+    let by_start = |a: &usize, b: &usize| {
+        (spans[*a].start_time_us, spans[*a].span_id)
+            .cmp(&(spans[*b].start_time_us, spans[*b].span_id))
+    };
+    roots.sort_by(by_start);
+    children.iter_mut().for_each(|c| c.sort_by(by_start));
+
+    let mut order = Vec::with_capacity(spans.len());
+    // Broken logs may have cycles
+    let mut seen = HashSet::new();
+    let mut stack: Vec<(usize, usize)> = roots.iter().rev().map(|&i| (i, 0)).collect();
+    while let Some((i, depth)) = stack.pop() {
+        if !seen.insert(i) {
+            continue;
+        }
+        order.push((i, depth));
+        stack.extend(children[i].iter().rev().map(|&c| (c, depth + 1)));
+    }
+    // Spans of cycles are not reachable from the roots
+    order.extend(
+        (0..spans.len())
+            .filter(|i| !seen.contains(i))
+            .map(|i| (i, 0)),
+    );
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn span(span_id: u64, parent_span_id: u64, start_time_us: u64) -> TraceSpan {
+        TraceSpan {
+            trace_id: "t".to_string(),
+            span_id,
+            parent_span_id,
+            operation_name: format!("op{}", span_id),
+            start_time_us,
+            finish_time_us: start_time_us + 10,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_spans_tree_order() {
+        let spans = vec![
+            span(3, 1, 30),
+            span(1, 0, 10),
+            span(2, 1, 20),
+            span(4, 2, 25),
+            // Parent is not there
+            span(5, 100, 5),
+        ];
+        let order: Vec<(u64, usize)> = spans_tree_order(&spans)
+            .into_iter()
+            .map(|(i, depth)| (spans[i].span_id, depth))
+            .collect();
+        assert_eq!(order, vec![(5, 0), (1, 0), (2, 1), (4, 2), (3, 1)]);
+    }
+
+    #[test]
+    fn test_spans_tree_order_cycle() {
+        let spans = vec![span(1, 2, 10), span(2, 1, 20)];
+        let mut order: Vec<usize> = spans_tree_order(&spans)
+            .into_iter()
+            .map(|(i, _)| i)
+            .collect();
+        order.sort();
+        assert_eq!(order, vec![0, 1]);
+    }
+}
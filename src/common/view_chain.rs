@@ -0,0 +1,179 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+
+/// Materialized view pushed during an insert (system.query_views_log, summed
+/// over the hosts).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ViewRun {
+    pub view_name: String,
+    pub view_type: String,
+    /// Table the view reads from (empty if unknown, e.g. it had been dropped).
+    pub source: String,
+    pub target: String,
+    pub status: String,
+    pub exception: String,
+    pub duration_ms: u64,
+    pub read_rows: u64,
+    pub written_rows: u64,
+    pub written_bytes: u64,
+}
+
+impl ViewRun {
+    pub fn is_failed(&self) -> bool {
+        !self.exception.is_empty() || self.status.contains("Exception")
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ViewChainNode {
+    /// Table the insert went to (or unknown source for the empty name).
+    Table(String),
+    /// Index in the views.
+    View(usize),
+}
+
+/// Row of the source table -> MV -> (target) -> next MV tree.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ViewChainRow {
+    pub node: ViewChainNode,
+    pub depth: usize,
+    /// Time of the node and of all the views below it (MVs of one table are
+    /// pushed one after another, unless parallel_view_processing is enabled).
+    pub total_ms: u64,
+    /// Whether the node is on the slowest path from the slowest root.
+    pub slowest: bool,
+}
+
+/// Rows of the node and of its descendants (children first, to get the totals).
+fn chain_subtree(
+    views: &[ViewRun],
+    by_source: &HashMap<&str, Vec<usize>>,
+    node: ViewChainNode,
+    depth: usize,
+    seen: &mut HashSet<usize>,
+) -> Vec<ViewChainRow> {
+    let (table, own_ms) = match &node {
+        ViewChainNode::Table(table) => (table.as_str(), 0),
+        ViewChainNode::View(i) => (views[*i].target.as_str(), views[*i].duration_ms),
+    };
+    let mut children: Vec<Vec<ViewChainRow>> = Vec::new();
+    for &child in by_source.get(table).map(Vec::as_slice).unwrap_or_default() {
+        if seen.insert(child) {
+            let rows = chain_subtree(
+                views,
+                by_source,
+                ViewChainNode::View(child),
+                depth + 1,
+                seen,
+            );
+            children.push(rows);
+        }
+    }
+    children.sort_by_key(|rows| Reverse(rows[0].total_ms));
+    let total_ms = own_ms + children.iter().map(|rows| rows[0].total_ms).sum::<u64>();
+    let mut rows = vec![ViewChainRow {
+        node,
+        depth,
+        total_ms,
+        slowest: false,
+    }];
+    rows.extend(children.into_iter().flatten());
+    rows
+}
+
+/// Views in the tree order: roots are the tables that are not the targets of
+/// the other views, children of a view are the views that read from its
+/// target, siblings are ordered by the total time (slowest first).
+pub fn views_chain(views: &[ViewRun]) -> Vec<ViewChainRow> {
+    let mut by_source: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, view) in views.iter().enumerate() {
+        by_source.entry(view.source.as_str()).or_default().push(i);
+    }
+    let targets: HashSet<&str> = views.iter().map(|v| v.target.as_str()).collect();
+    let mut roots: Vec<&str> = by_source
+        .keys()
+        .copied()
+        .filter(|source| !targets.contains(source))
+        .collect();
+    // Cycles (or a view writing into its own source) have no roots, use all
+    // the sources then, the visited views are skipped anyway
+    if roots.is_empty() {
+        roots = by_source.keys().copied().collect();
+    }
+    roots.sort_unstable();
+
+    let mut seen = HashSet::new();
+    let mut trees: Vec<Vec<ViewChainRow>> = roots
+        .into_iter()
+        .map(|root| {
+            let node = ViewChainNode::Table(root.to_string());
+            chain_subtree(views, &by_source, node, 0, &mut seen)
+        })
+        .collect();
+    trees.sort_by_key(|rows| Reverse(rows[0].total_ms));
+    let mut rows: Vec<ViewChainRow> = trees.into_iter().flatten().collect();
+
+    // Slowest path: the first (slowest) child on every level
+    if let Some(first) = rows.first_mut() {
+        first.slowest = true;
+    }
+    let mut depth = 0;
+    for row in rows.iter_mut().skip(1) {
+        if row.depth <= depth {
+            break;
+        }
+        if row.depth == depth + 1 {
+            row.slowest = true;
+            depth += 1;
+        }
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn view(name: &str, source: &str, target: &str, duration_ms: u64) -> ViewRun {
+        ViewRun {
+            view_name: name.to_string(),
+            source: source.to_string(),
+            target: target.to_string(),
+            duration_ms,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_views_chain() {
+        let views = vec![
+            view("db.mv_a", "db.src", "db.a", 10),
+            view("db.mv_b", "db.src", "db.b", 5),
+            view("db.mv_b2", "db.b", "db.b2", 20),
+            view("db.mv_a2", "db.a", "db.a2", 1),
+        ];
+        let rows: Vec<(ViewChainNode, usize, u64, bool)> = views_chain(&views)
+            .into_iter()
+            .map(|r| (r.node, r.depth, r.total_ms, r.slowest))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (ViewChainNode::Table("db.src".to_string()), 0, 36, true),
+                (ViewChainNode::View(1), 1, 25, true),
+                (ViewChainNode::View(2), 2, 20, true),
+                (ViewChainNode::View(0), 1, 11, false),
+                (ViewChainNode::View(3), 2, 1, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_views_chain_cycle() {
+        let views = vec![view("db.mv", "db.t", "db.t", 3)];
+        let rows = views_chain(&views);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1].node, ViewChainNode::View(0));
+        assert!(views_chain(&[]).is_empty());
+    }
+}
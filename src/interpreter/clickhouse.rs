@@ -1,5 +1,10 @@
 use crate::{
-    common::RelativeDateTime,
+    common::{
+        BenchmarkRun, CURRENT_METRIC_PREFIX, GrowthBucket, HostSchema, MetricDescriptions,
+        PROFILE_EVENT_PREFIX, PartEvent, PartLineage, PartLogError, PartLogStats, ProcessorProfile,
+        RelativeDateTime, ReplicaStatus, TableDefinition, TableGrowth, TableName, TableOverview,
+        TraceSpan, ViewRun, merge_tree_setting_override, merge_tree_table_settings,
+    },
     interpreter::{
         ClickHouseAvailableQuirks, ClickHouseQuirks,
         options::{ClickHouseOptions, LogsOrder},
@@ -13,9 +18,9 @@ use clickhouse_rs::{
     types::{ColumnType, Complex, Enum8, Enum16, FromSql, ProgressCallback, Query, SqlType},
 };
 use futures_util::StreamExt;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
-use std::sync::{RwLock, RwLockReadGuard};
+use std::sync::{Arc, RwLock, RwLockReadGuard};
 
 // TODO:
 // - implement parsing using serde
@@ -58,6 +63,12 @@ pub struct ClickHouse {
     // tables whose Enum8 definitions don't match exactly (e.g. --history spanning ClickHouse
     // versions that added new trace types). Queries then use this instead of the bare column.
     trace_type_cast_expr: Option<String>,
+    // system.query_log has the skip_indices column (names of the data skipping indices that were
+    // used by the query, the same way as projections).
+    query_log_skip_indices: bool,
+    // Descriptions of ProfileEvents/CurrentMetrics/asynchronous metrics, loaded once per
+    // connection, since they depend only on the server version.
+    metric_descriptions: Arc<MetricDescriptions>,
     // Guarded so that the settings view can change them at runtime (queries read
     // them per call). Never hold the guard across an await.
     options: RwLock<ClickHouseOptions>,
@@ -129,6 +140,12 @@ pub struct QueriesFilter {
     pub like: String,
     /// `query_kind IN (...)` restriction; empty = all kinds.
     pub query_kind: Vec<String>,
+    /// Extra SQL condition over system.query_log of the drill-down views
+    /// (e.g. `user = 'foo'`); empty = none. Ignored for system.processes.
+    pub condition: String,
+    /// Condition of the "Find queries by table/column" prompt, ANDed with
+    /// `condition` (so it narrows the drill-down instead of replacing it).
+    pub accessed: String,
 }
 
 impl QueriesFilter {
@@ -146,6 +163,15 @@ impl QueriesFilter {
                 .join(",")
         )
     }
+
+    /// ` AND (condition) AND (accessed)` (the non-empty ones) or empty.
+    fn condition_clause(&self) -> String {
+        [&self.condition, &self.accessed]
+            .iter()
+            .filter(|condition| !condition.is_empty())
+            .map(|condition| format!(" AND ({})", condition))
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -383,6 +409,13 @@ fn collect_values<'b, T: FromSql<'b>>(block: &'b Columns, column: &str) -> Vec<T
         .collect();
 }
 
+// Old parts can be merged from (tens of) thousands of inserts, stop somewhere
+const PART_LINEAGE_MAX_PARTS: usize = 10_000;
+// How long before a merge its source parts are looked up in the part_log
+const PART_LINEAGE_LOOKBACK_DAYS: u32 = 30;
+// table_uuid of the tables of the Ordinary databases
+const NIL_UUID: &str = "00000000-0000-0000-0000-000000000000";
+
 const CHDIG_CLIENT_NAME: [&str; 2] = ["chdig", env!("CARGO_PKG_VERSION")];
 fn get_client_name() -> String {
     return CHDIG_CLIENT_NAME.join("-");
@@ -460,6 +493,8 @@ impl ClickHouse {
             quirks,
             shared_log_pipeline,
             trace_type_cast_expr: None,
+            query_log_skip_indices: false,
+            metric_descriptions: Arc::default(),
             options: RwLock::new(options),
             pool,
             progress_callback: RwLock::new(None),
@@ -469,6 +504,8 @@ impl ClickHouse {
             Some(false) => {}
             None => clickhouse.detect_trace_type_cast().await,
         }
+        clickhouse.detect_query_log_skip_indices().await;
+        clickhouse.load_metric_descriptions().await;
         return Ok(clickhouse);
     }
 
@@ -539,6 +576,74 @@ impl ClickHouse {
         }
     }
 
+    // Newer column, not all of the versions have it, and it is easier to check the column itself
+    // (from system.columns, like detect_trace_type_cast()) than to track the version.
+    async fn detect_query_log_skip_indices(&mut self) {
+        let database = self.system_database().replace('\'', "''");
+        let sql = format!(
+            "SELECT count() FROM system.columns \
+             WHERE database = '{database}' AND table = 'query_log' AND name = 'skip_indices'"
+        );
+        match self.execute(&sql).await {
+            Ok(block) => {
+                self.query_log_skip_indices = block.get::<u64, _>(0, 0).unwrap_or_default() > 0;
+            }
+            Err(e) => {
+                log::debug!("Failed to detect query_log.skip_indices column: {}", e);
+            }
+        }
+    }
+
+    /// Whether system.query_log has the skip_indices column.
+    pub fn has_query_log_skip_indices(&self) -> bool {
+        self.query_log_skip_indices
+    }
+
+    // Live tables, not the --database copy: the descriptions are of the server itself. Each of
+    // them is optional (system.asynchronous_metrics has no description in old versions).
+    async fn load_metric_descriptions(&mut self) {
+        let sources = [
+            (
+                PROFILE_EVENT_PREFIX,
+                "SELECT name, description FROM system.events \
+                 SETTINGS system_events_show_zero_values = 1",
+            ),
+            (
+                CURRENT_METRIC_PREFIX,
+                "SELECT name, description FROM system.metrics",
+            ),
+            (
+                "",
+                "SELECT metric AS name, any(description) AS description \
+                 FROM system.asynchronous_metrics GROUP BY metric",
+            ),
+        ];
+        let mut descriptions = MetricDescriptions::default();
+        for (prefix, sql) in sources {
+            let block = match self.execute(sql).await {
+                Ok(block) => block,
+                Err(e) => {
+                    log::debug!("Failed to load metric descriptions ({}): {}", sql, e);
+                    continue;
+                }
+            };
+            for i in 0..block.row_count() {
+                let (Ok(name), Ok(description)) = (
+                    block.get::<String, _>(i, "name"),
+                    block.get::<String, _>(i, "description"),
+                ) else {
+                    continue;
+                };
+                descriptions.insert(format!("{}{}", prefix, name), description);
+            }
+        }
+        self.metric_descriptions = Arc::new(descriptions);
+    }
+
+    pub fn metric_descriptions(&self) -> Arc<MetricDescriptions> {
+        self.metric_descriptions.clone()
+    }
+
     // Bare column (or an explicit cast to it) usable in WHERE/IN clauses.
     fn trace_type_expr(&self) -> &str {
         self.trace_type_cast_expr.as_deref().unwrap_or("trace_type")
@@ -577,6 +682,7 @@ impl ClickHouse {
         // *initial* query's kind: the whole query group (subqueries included)
         // is still pulled by the outer query.
         filter_clause.push_str(&filter.query_kind_clause());
+        filter_clause.push_str(&filter.condition_clause());
         let peak_threads_usage = if self
             .quirks
             .has(ClickHouseAvailableQuirks::QueryLogPeakThreadsUsage)
@@ -735,6 +841,7 @@ impl ClickHouse {
         };
         // The initial query's kind (see get_slow_query_log).
         filter_clause.push_str(&filter.query_kind_clause());
+        filter_clause.push_str(&filter.condition_clause());
         let peak_threads_usage = if self
             .quirks
             .has(ClickHouseAvailableQuirks::QueryLogPeakThreadsUsage)
@@ -1299,6 +1406,93 @@ impl ClickHouse {
         Self::execute_simple_on(&mut client, self.apply_query_settings(query)).await
     }
 
+    /// Runs the query discarding the result (see benchmark_query_text() for
+    /// how it is not sent in the first place), `log_comment` marks the run in
+    /// system.query_log.
+    pub async fn benchmark_query(
+        &self,
+        database: &str,
+        query: &str,
+        settings: &[(String, String)],
+        log_comment: &str,
+    ) -> Result<()> {
+        // USE and the query must run on the same connection - see execute_query()'s comment.
+        let mut client = self.pool.get_handle().await?;
+        Self::execute_simple_on(&mut client, &format!("USE {}", database)).await?;
+
+        let mut query = self.apply_query_settings(Query::new(query)).with_setting(
+            "log_comment",
+            log_comment,
+            false,
+        );
+        for (name, value) in settings {
+            query = query.with_setting(name, value.as_str(), false);
+        }
+        let mut stream = client.query(query).stream_blocks();
+        while let Some(block) = stream.next().await {
+            block?;
+        }
+        Ok(())
+    }
+
+    /// query_log rows of the benchmark runs (see benchmark_query()) by their
+    /// log_comment, that starts with `tag`.
+    pub async fn get_benchmark_runs(
+        &self,
+        tag: &str,
+        start: DateTime<Local>,
+    ) -> Result<HashMap<String, BenchmarkRun>> {
+        let dbtable = self.get_log_table_name("query_log");
+        let sql = format!(
+            r#"
+                    WITH fromUnixTimestamp64Nano({start}) AS start_
+                    SELECT
+                        log_comment,
+                        toFloat64(query_duration_ms) AS duration_ms,
+                        toUInt64(read_rows) AS read_rows,
+                        toUInt64(read_bytes) AS read_bytes,
+                        toUInt64(memory_usage) AS memory_usage,
+                        mapKeys(ProfileEvents)   AS `ProfileEvents.Names`,
+                        mapValues(ProfileEvents) AS `ProfileEvents.Values`
+                    FROM {dbtable}
+                    WHERE
+                        event_date >= toDate(start_) AND
+                        event_time >= toDateTime(start_) AND
+                        type = 'QueryFinish' AND
+                        is_initial_query AND
+                        startsWith(log_comment, '{tag}')
+                    "#,
+            start = start
+                .timestamp_nanos_opt()
+                .ok_or(Error::msg("Invalid start"))?,
+            dbtable = dbtable,
+            tag = tag.replace('\\', "\\\\").replace('\'', "\\'"),
+        );
+
+        let block = self.execute(&sql).await?;
+        let mut runs = HashMap::new();
+        for i in 0..block.row_count() {
+            let names = block.get::<Vec<String>, _>(i, "ProfileEvents.Names")?;
+            let values = block.get::<Vec<u64>, _>(i, "ProfileEvents.Values")?;
+            runs.insert(
+                block.get::<String, _>(i, "log_comment")?,
+                BenchmarkRun {
+                    duration_ms: block.get::<f64, _>(i, "duration_ms")?,
+                    read_rows: block.get::<u64, _>(i, "read_rows")?,
+                    read_bytes: block.get::<u64, _>(i, "read_bytes")?,
+                    memory_usage: block.get::<u64, _>(i, "memory_usage")?,
+                    profile_events: names.into_iter().zip(values).collect(),
+                },
+            );
+        }
+        return Ok(runs);
+    }
+
+    /// Flushes system.*_log tables (requires SYSTEM FLUSH LOGS grant).
+    pub async fn flush_logs(&self) -> Result<()> {
+        self.execute_simple("SYSTEM FLUSH LOGS").await
+    }
+
     pub async fn explain_syntax(
         &self,
         database: &str,
@@ -1314,14 +1508,89 @@ impl ClickHouse {
         return self.explain("PLAN actions=1", database, query, None).await;
     }
 
+    /// JSON of the plan with actions and indexes analysis (for the plan tree).
+    pub async fn explain_plan_json(&self, database: &str, query: &str) -> Result<String> {
+        let json = self
+            .explain("PLAN json=1, actions=1, indexes=1", database, query, None)
+            .await?;
+        return Ok(json.join("\n"));
+    }
+
+    /// Query formatted by the server (formatQuery(), 23.10+).
+    pub async fn format_query(&self, query: &str) -> Result<String> {
+        let block = self
+            .execute(format!(
+                "SELECT formatQuery('{}') AS query",
+                query.replace('\\', "\\\\").replace('\'', "\\'")
+            ))
+            .await?;
+        return Ok(block.get::<String, _>(0, "query")?);
+    }
+
     pub async fn explain_pipeline(&self, database: &str, query: &str) -> Result<Vec<String>> {
         return self.explain("PIPELINE", database, query, None).await;
     }
 
-    pub async fn explain_pipeline_graph(&self, database: &str, query: &str) -> Result<Vec<String>> {
-        return self
-            .explain("PIPELINE graph=1", database, query, None)
-            .await;
+    /// DOT of the pipeline, `compact` merges parallel processors into one
+    /// node (non-compact graph is for the TUI renderer, which collapses them
+    /// on its own, so that they can be expanded back).
+    pub async fn explain_pipeline_graph(
+        &self,
+        database: &str,
+        query: &str,
+        compact: bool,
+    ) -> Result<Vec<String>> {
+        let what = format!("PIPELINE graph=1, compact={}", compact as u8);
+        return self.explain(&what, database, query, None).await;
+    }
+
+    /// processors_profile_log of the query aggregated by the processor name.
+    pub async fn get_query_processors_profile(
+        &self,
+        query_id: &str,
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    ) -> Result<HashMap<String, ProcessorProfile>> {
+        let dbtable = self.get_log_table_name("processors_profile_log");
+        let sql = format!(
+            r#"
+                    WITH
+                        fromUnixTimestamp64Nano({start}) AS start_,
+                        fromUnixTimestamp64Nano({end}) AS end_
+                    SELECT
+                        name,
+                        count() AS processors,
+                        sum(elapsed_us) AS elapsed_us,
+                        sum(input_wait_elapsed_us) AS input_wait_us,
+                        sum(output_wait_elapsed_us) AS output_wait_us
+                    FROM {dbtable}
+                    WHERE query_id = '{query_id}'
+                      AND event_date >= toDate(start_) AND event_time >= toDateTime(start_)
+                      AND event_date <= toDate(end_)   AND event_time <= toDateTime(end_)
+                    GROUP BY name
+                    "#,
+            dbtable = dbtable,
+            start = start
+                .timestamp_nanos_opt()
+                .ok_or(Error::msg("Invalid start"))?,
+            end = end.timestamp_nanos_opt().ok_or(Error::msg("Invalid end"))?,
+            query_id = query_id,
+        );
+
+        let block = self.execute(sql.as_str()).await?;
+        let mut profile = HashMap::new();
+        for i in 0..block.row_count() {
+            profile.insert(
+                block.get::<String, _>(i, "name")?,
+                ProcessorProfile {
+                    processors: block.get::<u64, _>(i, "processors")?,
+                    elapsed_us: block.get::<u64, _>(i, "elapsed_us")?,
+                    input_wait_us: block.get::<u64, _>(i, "input_wait_us")?,
+                    output_wait_us: block.get::<u64, _>(i, "output_wait_us")?,
+                },
+            );
+        }
+        return Ok(profile);
     }
 
     // NOTE: can we benefit from json=1?
@@ -1340,6 +1609,557 @@ impl ClickHouse {
         return Ok(statement);
     }
 
+    /// MergeTree settings of the table: the per-table overrides from the CREATE
+    /// statement, or the server defaults (system.merge_tree_settings).
+    pub async fn get_merge_tree_settings(
+        &self,
+        create_statement: &str,
+        names: &[&str],
+    ) -> Result<HashMap<String, String>> {
+        let block = self
+            .execute(format!(
+                "SELECT name, any(value) AS value FROM {} WHERE name IN ('{}') GROUP BY name",
+                self.get_live_table_name("merge_tree_settings"),
+                names.join("','"),
+            ))
+            .await?;
+        let mut settings = HashMap::new();
+        for i in 0..block.row_count() {
+            let name = block.get::<String, _>(i, "name")?;
+            let value = match merge_tree_setting_override(create_statement, &name) {
+                Some(value) => value,
+                None => block.get::<String, _>(i, "value")?,
+            };
+            settings.insert(name, value);
+        }
+        return Ok(settings);
+    }
+
+    /// MergeTree settings overridden by the tables: (setting, "db.table",
+    /// value), parsed from the create_table_query (see
+    /// merge_tree_table_settings()).
+    pub async fn get_merge_tree_settings_overrides(&self) -> Result<Vec<(String, String, String)>> {
+        let block = self
+            .execute(format!(
+                r#"
+                SELECT DISTINCT ON (database, name) database, name, create_table_query
+                FROM {}
+                WHERE engine LIKE '%MergeTree%' AND create_table_query LIKE '%SETTINGS%'
+                "#,
+                self.get_table_name_no_history("tables"),
+            ))
+            .await?;
+        let mut overrides = Vec::new();
+        for i in 0..block.row_count() {
+            let table = format!(
+                "{}.{}",
+                block.get::<String, _>(i, "database")?,
+                block.get::<String, _>(i, "name")?
+            );
+            let create_statement = block.get::<String, _>(i, "create_table_query")?;
+            for (name, value) in merge_tree_table_settings(&create_statement) {
+                overrides.push((name, table.clone(), value));
+            }
+        }
+        return Ok(overrides);
+    }
+
+    /// Parts, replication, merges/mutations and part_log of the table (see
+    /// TableOverview).
+    pub async fn get_table_overview(&self, database: &str, table: &str) -> Result<TableOverview> {
+        let create_statement = self.show_create_table(database, table).await?;
+        let table_filter = format!(
+            "database = '{}' AND table = '{}'",
+            database.replace('\'', "''"),
+            table.replace('\'', "''")
+        );
+
+        // Per host first, the replicas of a replicated table (in --cluster
+        // mode) have the same parts, so the largest replica is taken
+        let parts = self
+            .execute(format!(
+                r#"
+                SELECT
+                    toUInt64(max(host_parts)) AS active_parts,
+                    toUInt64(max(host_partitions)) AS partitions,
+                    toUInt64(max(host_max_parts)) AS max_parts_in_partition,
+                    toUInt64(max(host_rows)) AS rows,
+                    toUInt64(max(host_compressed)) AS compressed_bytes,
+                    toUInt64(max(host_uncompressed)) AS uncompressed_bytes
+                FROM (
+                    SELECT
+                        host,
+                        sum(parts) AS host_parts,
+                        count() AS host_partitions,
+                        max(parts) AS host_max_parts,
+                        sum(rows) AS host_rows,
+                        sum(compressed) AS host_compressed,
+                        sum(uncompressed) AS host_uncompressed
+                    FROM (
+                        SELECT
+                            hostName() AS host,
+                            partition_id,
+                            count() AS parts,
+                            sum(rows) AS rows,
+                            sum(data_compressed_bytes) AS compressed,
+                            sum(data_uncompressed_bytes) AS uncompressed
+                        FROM {parts}
+                        WHERE active AND {table_filter}
+                        GROUP BY host, partition_id
+                    )
+                    GROUP BY host
+                )
+                "#,
+                parts = self.get_live_table_name("parts"),
+                table_filter = table_filter,
+            ))
+            .await?;
+
+        let mut overview = TableOverview {
+            database: database.to_string(),
+            table: table.to_string(),
+            active_parts: parts.get::<u64, _>(0, "active_parts")?,
+            partitions: parts.get::<u64, _>(0, "partitions")?,
+            max_parts_in_partition: parts.get::<u64, _>(0, "max_parts_in_partition")?,
+            rows: parts.get::<u64, _>(0, "rows")?,
+            compressed_bytes: parts.get::<u64, _>(0, "compressed_bytes")?,
+            uncompressed_bytes: parts.get::<u64, _>(0, "uncompressed_bytes")?,
+            ..Default::default()
+        };
+
+        let settings = self
+            .get_merge_tree_settings(
+                &create_statement,
+                &["parts_to_delay_insert", "parts_to_throw_insert"],
+            )
+            .await?;
+        let setting = |name: &str| {
+            settings
+                .get(name)
+                .and_then(|value| value.parse::<u64>().ok())
+                .unwrap_or_default()
+        };
+        overview.parts_to_delay_insert = setting("parts_to_delay_insert");
+        overview.parts_to_throw_insert = setting("parts_to_throw_insert");
+        overview.create_statement = create_statement;
+
+        let replicas = self
+            .execute(format!(
+                r#"
+                SELECT
+                    count() AS replicas,
+                    toUInt8(max(is_readonly)) AS is_readonly,
+                    toUInt64(max(absolute_delay)) AS absolute_delay,
+                    toUInt64(max(queue_size)) AS queue_size,
+                    toUInt64(min(active_replicas)) AS active_replicas,
+                    toUInt64(max(total_replicas)) AS total_replicas
+                FROM {replicas}
+                WHERE {table_filter}
+                "#,
+                replicas = self.get_live_table_name("replicas"),
+                table_filter = table_filter,
+            ))
+            .await?;
+        if replicas.get::<u64, _>(0, "replicas")? > 0 {
+            overview.replica = Some(ReplicaStatus {
+                is_readonly: replicas.get::<u8, _>(0, "is_readonly")? != 0,
+                absolute_delay: replicas.get::<u64, _>(0, "absolute_delay")?,
+                queue_size: replicas.get::<u64, _>(0, "queue_size")?,
+                active_replicas: replicas.get::<u64, _>(0, "active_replicas")?,
+                total_replicas: replicas.get::<u64, _>(0, "total_replicas")?,
+            });
+        }
+
+        let background = self
+            .execute(format!(
+                r#"
+                SELECT
+                    (SELECT count() FROM {merges} WHERE {table_filter}) AS merges,
+                    countIf(NOT is_done) AS mutations,
+                    anyIf(latest_fail_reason, NOT is_done AND latest_fail_reason != '') AS failed_mutation
+                FROM {mutations}
+                WHERE {table_filter}
+                "#,
+                merges = self.get_live_table_name("merges"),
+                mutations = self.get_live_table_name("mutations"),
+                table_filter = table_filter,
+            ))
+            .await?;
+        overview.merges = background.get::<u64, _>(0, "merges")?;
+        overview.mutations = background.get::<u64, _>(0, "mutations")?;
+        overview.failed_mutation = Some(background.get::<String, _>(0, "failed_mutation")?)
+            .filter(|reason| !reason.is_empty());
+
+        // part_log is optional (and may not exist yet)
+        match self.get_table_part_log_stats(&table_filter).await {
+            Ok(stats) => overview.part_log = Some(stats),
+            Err(err) => log::debug!("Cannot get part_log of {}.{}: {}", database, table, err),
+        }
+
+        return Ok(overview);
+    }
+
+    async fn get_table_part_log_stats(&self, table_filter: &str) -> Result<PartLogStats> {
+        let dbtable = self.get_log_table_name("part_log");
+        let inserts = self
+            .execute(format!(
+                r#"
+                SELECT count() AS new_parts, toUInt64(sum(rows)) AS rows
+                FROM {dbtable}
+                WHERE
+                    event_date >= toDate(now() - INTERVAL 1 HOUR) AND event_time > now() - INTERVAL 1 HOUR
+                    AND event_type = 'NewPart' AND {table_filter}
+                "#,
+                dbtable = dbtable,
+                table_filter = table_filter,
+            ))
+            .await?;
+        let errors = self
+            .execute(format!(
+                r#"
+                SELECT
+                    toString(event_time) AS event_time,
+                    toString(event_type) AS event_type,
+                    part_name,
+                    exception
+                FROM {dbtable}
+                WHERE
+                    event_date >= yesterday() AND event_time > now() - INTERVAL 1 DAY
+                    AND error != 0 AND {table_filter}
+                ORDER BY event_time DESC
+                LIMIT 5
+                "#,
+                dbtable = dbtable,
+                table_filter = table_filter,
+            ))
+            .await?;
+
+        let mut stats = PartLogStats {
+            new_parts_last_hour: inserts.get::<u64, _>(0, "new_parts")?,
+            inserted_rows_last_hour: inserts.get::<u64, _>(0, "rows")?,
+            errors: Vec::with_capacity(errors.row_count()),
+        };
+        for i in 0..errors.row_count() {
+            stats.errors.push(PartLogError {
+                event_time: errors.get::<String, _>(i, "event_time")?,
+                event_type: errors.get::<String, _>(i, "event_type")?,
+                part_name: errors.get::<String, _>(i, "part_name")?,
+                exception: errors.get::<String, _>(i, "exception")?,
+            });
+        }
+        return Ok(stats);
+    }
+
+    /// Events of the part and of all its ancestors (merged_from of the
+    /// system.part_log, level by level) down to the original inserts.
+    ///
+    /// Every level reads only the event_date range where its parts can be:
+    /// not after the parts of the previous level (starting from `created`,
+    /// the time the part was created) and at most
+    /// PART_LINEAGE_LOOKBACK_DAYS before them (older sources are shown without
+    /// the details, like the ones already gone from the part_log).
+    pub async fn get_part_lineage(
+        &self,
+        database: &str,
+        table: &str,
+        table_uuid: &str,
+        part_name: &str,
+        created: DateTime<Local>,
+    ) -> Result<PartLineage> {
+        let dbtable = self.get_log_table_name("part_log");
+        let table_filter = if table_uuid.is_empty() || table_uuid == NIL_UUID {
+            format!(
+                "database = '{}' AND table = '{}'",
+                database.replace('\'', "''"),
+                table.replace('\'', "''")
+            )
+        } else {
+            format!("table_uuid = '{}'", table_uuid.replace('\'', "''"))
+        };
+        // A day of slack, the time of the part may be a bit later than the event
+        let created_date = format!("toDate(fromUnixTimestamp({}))", created.timestamp());
+        let (mut min_date, mut max_date) = (created_date.clone(), format!("{} + 1", created_date));
+
+        let mut lineage = PartLineage::default();
+        let mut part_names = vec![part_name.to_string()];
+        let mut queried: HashSet<String> = part_names.iter().cloned().collect();
+        while !part_names.is_empty() && lineage.len() < PART_LINEAGE_MAX_PARTS {
+            let block = self
+                .execute(format!(
+                    r#"
+                    SELECT
+                        part_name,
+                        toString(event_type) AS event_type,
+                        toString(event_time) AS event_time,
+                        toString(event_date) AS event_date,
+                        duration_ms,
+                        size_in_bytes,
+                        rows,
+                        merged_from,
+                        query_id
+                    FROM {dbtable}
+                    WHERE
+                        event_date BETWEEN {min_date} - {lookback_days} AND {max_date}
+                        AND {table_filter}
+                        AND event_type IN ('NewPart', 'MergeParts', 'MutatePart', 'DownloadPart')
+                        AND error = 0
+                        AND part_name IN ('{part_names}')
+                    "#,
+                    dbtable = dbtable,
+                    min_date = min_date,
+                    lookback_days = PART_LINEAGE_LOOKBACK_DAYS,
+                    max_date = max_date,
+                    table_filter = table_filter,
+                    part_names = part_names.join("','"),
+                ))
+                .await?;
+            let mut dates = Vec::new();
+            for i in 0..block.row_count() {
+                dates.push(block.get::<String, _>(i, "event_date")?);
+                lineage.insert(PartEvent {
+                    part_name: block.get::<String, _>(i, "part_name")?,
+                    event_type: block.get::<String, _>(i, "event_type")?,
+                    event_time: block.get::<String, _>(i, "event_time")?,
+                    duration_ms: block.get::<u64, _>(i, "duration_ms")?,
+                    size_in_bytes: block.get::<u64, _>(i, "size_in_bytes")?,
+                    rows: block.get::<u64, _>(i, "rows")?,
+                    merged_from: block.get::<Vec<String>, _>(i, "merged_from")?,
+                    query_id: block.get::<String, _>(i, "query_id")?,
+                });
+            }
+            // The sources of this level are created before its parts
+            if let (Some(min), Some(max)) = (dates.iter().min(), dates.iter().max()) {
+                min_date = format!("toDate('{}')", min);
+                max_date = format!("toDate('{}')", max);
+            }
+            // Sources that are not in the part_log (TTL) are not looked up again
+            part_names = lineage
+                .unknown_sources()
+                .into_iter()
+                .filter(|name| queried.insert(name.clone()))
+                .collect();
+        }
+        return Ok(lineage);
+    }
+
+    /// Bytes and rows added to and removed from the table per bucket of the
+    /// interval (from the part_log) and the free space of its disks.
+    ///
+    /// Per host the net change is exact: the created parts (NewPart,
+    /// DownloadPart, MergeParts, MutatePart) minus the removed ones
+    /// (RemovePart). Inserts and fetches are "added", and "removed" is what is
+    /// gone after the merges (TTL, dropped partitions, deduplication), i.e.
+    /// RemovePart minus the merged/mutated parts. Old parts are removed a few
+    /// minutes after the merge, so a single bucket may be negative.
+    pub async fn get_table_growth(
+        &self,
+        database: &str,
+        table: &str,
+        start: RelativeDateTime,
+        end: RelativeDateTime,
+        buckets: u32,
+        selected_host: Option<&String>,
+    ) -> Result<TableGrowth> {
+        let start_sql = start
+            .to_sql_datetime_64()
+            .ok_or_else(|| Error::msg("Invalid start"))?;
+        let end_sql = end
+            .to_sql_datetime_64()
+            .ok_or_else(|| Error::msg("Invalid end"))?;
+        let table_filter = format!(
+            "database = '{}' AND table = '{}'",
+            database.replace('\'', "''"),
+            table.replace('\'', "''")
+        );
+        // See get_log_host_filter_clause()
+        let host = if self.shared_log_pipeline {
+            "hostname"
+        } else {
+            "hostName()"
+        };
+
+        let block = self
+            .execute(format!(
+                r#"
+                WITH {start} AS start_, {end} AS end_
+                SELECT
+                    {host} AS host,
+                    toUInt32(least({buckets} - 1, floor((toUnixTimestamp(event_time) - toUnixTimestamp(toDateTime(start_))) * {buckets} / greatest(1, toUnixTimestamp(toDateTime(end_)) - toUnixTimestamp(toDateTime(start_)))))) AS bucket,
+                    toFloat64(sumIf(size_in_bytes, event_type IN ('NewPart', 'DownloadPart'))) AS bytes_added,
+                    toFloat64(sumIf(rows, event_type IN ('NewPart', 'DownloadPart'))) AS rows_added,
+                    toFloat64(sumIf(size_in_bytes, event_type = 'RemovePart')) - toFloat64(sumIf(size_in_bytes, event_type IN ('MergeParts', 'MutatePart'))) AS bytes_removed,
+                    toFloat64(sumIf(rows, event_type = 'RemovePart')) - toFloat64(sumIf(rows, event_type IN ('MergeParts', 'MutatePart'))) AS rows_removed,
+                    toFloat64(greatest(1, dateDiff('second', toDateTime(start_), toDateTime(end_)))) AS interval
+                FROM {dbtable}
+                WHERE
+                    event_date BETWEEN toDate(start_) AND toDate(end_) AND
+                    event_time BETWEEN toDateTime(start_) AND toDateTime(end_) AND
+                    {table_filter} AND
+                    error = 0
+                    {host_filter}
+                GROUP BY host, bucket
+                "#,
+                start = start_sql,
+                end = end_sql,
+                host = host,
+                buckets = buckets,
+                dbtable = self.get_log_table_name("part_log"),
+                table_filter = table_filter,
+                host_filter = self.get_log_host_filter_clause(selected_host),
+            ))
+            .await?;
+        if block.row_count() == 0 {
+            return Err(Error::msg(format!(
+                "No events for {}.{} in part_log over the interval (is it enabled?)",
+                database, table
+            )));
+        }
+
+        let mut growth = TableGrowth::new(buckets as usize, block.get::<f64, _>(0, "interval")?);
+        for i in 0..block.row_count() {
+            growth.add(
+                &block.get::<String, _>(i, "host")?,
+                block.get::<u32, _>(i, "bucket")? as usize,
+                GrowthBucket {
+                    bytes_added: block.get::<f64, _>(i, "bytes_added")?,
+                    bytes_removed: block.get::<f64, _>(i, "bytes_removed")?,
+                    rows_added: block.get::<f64, _>(i, "rows_added")?,
+                    rows_removed: block.get::<f64, _>(i, "rows_removed")?,
+                },
+            );
+        }
+
+        // Disks with the data of the table (the same disk may have several
+        // paths of it)
+        let block = self
+            .execute(format!(
+                r#"
+                SELECT host, sum(free_space) AS free_space
+                FROM (
+                    SELECT DISTINCT disks.host AS host, disks.name AS name, disks.free_space AS free_space
+                    FROM (SELECT hostName() AS host, name, path, free_space FROM {disks}) AS disks
+                    JOIN (
+                        SELECT hostName() AS host, arrayJoin(data_paths) AS data_path
+                        FROM {tables}
+                        WHERE database = '{database}' AND name = '{table}'
+                    ) AS tables ON tables.host = disks.host
+                    WHERE startsWith(tables.data_path, disks.path)
+                )
+                GROUP BY host
+                "#,
+                disks = self.get_table_name_no_history("disks"),
+                tables = self.get_table_name_no_history("tables"),
+                database = database.replace('\'', "''"),
+                table = table.replace('\'', "''"),
+            ))
+            .await?;
+        for i in 0..block.row_count() {
+            growth.free_space.insert(
+                block.get::<String, _>(i, "host")?,
+                block.get::<u64, _>(i, "free_space")?,
+            );
+        }
+        return Ok(growth);
+    }
+
+    /// Tables with their dependencies and CREATE statements, to build the
+    /// dependencies graph (see table_dependencies()).
+    pub async fn get_table_definitions(&self) -> Result<Vec<TableDefinition>> {
+        let loading_dependencies = if self
+            .quirks
+            .has(ClickHouseAvailableQuirks::SystemTablesLoadingDependencies)
+        {
+            "loading_dependencies_database, loading_dependencies_table"
+        } else {
+            "[]::Array(String) AS loading_dependencies_database, []::Array(String) AS loading_dependencies_table"
+        };
+        let block = self
+            .execute(format!(
+                r#"
+                SELECT DISTINCT ON (database, name)
+                    database,
+                    name,
+                    engine,
+                    toString(uuid) AS uuid,
+                    create_table_query,
+                    dependencies_database,
+                    dependencies_table,
+                    {loading_dependencies}
+                FROM {tables}
+                WHERE database NOT IN ('system', 'INFORMATION_SCHEMA', 'information_schema')
+                "#,
+                loading_dependencies = loading_dependencies,
+                tables = self.get_table_name_no_history("tables"),
+            ))
+            .await?;
+
+        let mut tables = Vec::with_capacity(block.row_count());
+        for i in 0..block.row_count() {
+            // (database, table) pairs of the parallel arrays
+            let names = |database_column: &str, table_column: &str| -> Result<Vec<TableName>> {
+                Ok(block
+                    .get::<Vec<String>, _>(i, database_column)?
+                    .into_iter()
+                    .zip(block.get::<Vec<String>, _>(i, table_column)?)
+                    .collect())
+            };
+            tables.push(TableDefinition {
+                database: block.get::<String, _>(i, "database")?,
+                name: block.get::<String, _>(i, "name")?,
+                engine: block.get::<String, _>(i, "engine")?,
+                uuid: block.get::<String, _>(i, "uuid")?,
+                create_table_query: block.get::<String, _>(i, "create_table_query")?,
+                dependencies: names("dependencies_database", "dependencies_table")?,
+                loading_dependencies: names(
+                    "loading_dependencies_database",
+                    "loading_dependencies_table",
+                )?,
+            });
+        }
+        return Ok(tables);
+    }
+
+    /// CREATE statement and the columns of the table on every host.
+    pub async fn get_table_schemas(&self, database: &str, table: &str) -> Result<Vec<HostSchema>> {
+        let block = self
+            .execute(format!(
+                r#"
+                SELECT
+                    tables.host AS host,
+                    tables.create_table_query AS create_table_query,
+                    columns.columns AS columns
+                FROM (
+                    SELECT hostName() AS host, create_table_query
+                    FROM {tables}
+                    WHERE database = '{database}' AND name = '{table}'
+                ) AS tables
+                LEFT JOIN (
+                    SELECT
+                        hostName() AS host,
+                        arrayStringConcat(arrayMap(c -> concat(c.2, ' ', c.3), arraySort(groupArray((position, name, type)))), '\n') AS columns
+                    FROM {columns}
+                    WHERE database = '{database}' AND table = '{table}'
+                    GROUP BY host
+                ) AS columns ON columns.host = tables.host
+                "#,
+                tables = self.get_table_name_no_history("tables"),
+                columns = self.get_table_name_no_history("columns"),
+                database = database.replace('\'', "''"),
+                table = table.replace('\'', "''"),
+            ))
+            .await?;
+
+        let mut schemas = Vec::with_capacity(block.row_count());
+        for i in 0..block.row_count() {
+            schemas.push(HostSchema {
+                host: block.get::<String, _>(i, "host")?,
+                create_table_query: block.get::<String, _>(i, "create_table_query")?,
+                columns: block.get::<String, _>(i, "columns")?,
+            });
+        }
+        return Ok(schemas);
+    }
+
     async fn explain(
         &self,
         what: &str,
@@ -1725,6 +2545,142 @@ impl ClickHouse {
         self.execute_for_each(&sql, on_block).await
     }
 
+    /// Spans of the traces of the queries (by trace_id, so the spans of the
+    /// other hosts and of the client are there as well).
+    pub async fn get_query_trace_spans(
+        &self,
+        query_ids: &[String],
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    ) -> Result<Vec<TraceSpan>> {
+        let dbtable = self.get_log_table_name("opentelemetry_span_log");
+        let sql = format!(
+            r#"
+                    WITH
+                        {start_us} AS start_us_,
+                        {end_us} AS end_us_
+                    SELECT
+                        toString(trace_id) AS trace_id,
+                        span_id,
+                        parent_span_id,
+                        toString(operation_name) AS operation_name,
+                        start_time_us,
+                        finish_time_us,
+                        {host_expr} AS host_name,
+                        CAST(mapKeys(attribute), 'Array(String)') AS attribute_names,
+                        mapValues(attribute) AS attribute_values
+                    FROM {dbtable}
+                    WHERE
+                        finish_date >= toDate(fromUnixTimestamp64Micro(toInt64(start_us_))) AND
+                        finish_time_us >= start_us_ AND start_time_us <= end_us_ AND
+                        trace_id GLOBAL IN (
+                            SELECT trace_id
+                            FROM {dbtable}
+                            WHERE
+                                finish_date >= toDate(fromUnixTimestamp64Micro(toInt64(start_us_))) AND
+                                finish_time_us >= start_us_ AND start_time_us <= end_us_ AND
+                                attribute['clickhouse.query_id'] IN ('{query_ids}')
+                        )
+                    ORDER BY start_time_us
+                    "#,
+            dbtable = dbtable,
+            start_us = start.timestamp_micros(),
+            end_us = end.timestamp_micros(),
+            host_expr = self.get_log_hostname_column(),
+            query_ids = query_ids.join("','"),
+        );
+
+        let block = self.execute(&sql).await?;
+        let mut spans = Vec::with_capacity(block.row_count());
+        for i in 0..block.row_count() {
+            let names = block.get::<Vec<String>, _>(i, "attribute_names")?;
+            let values = block.get::<Vec<String>, _>(i, "attribute_values")?;
+            spans.push(TraceSpan {
+                trace_id: block.get::<String, _>(i, "trace_id")?,
+                span_id: block.get::<u64, _>(i, "span_id")?,
+                parent_span_id: block.get::<u64, _>(i, "parent_span_id")?,
+                operation_name: block.get::<String, _>(i, "operation_name")?,
+                host_name: block.get::<String, _>(i, "host_name")?,
+                start_time_us: block.get::<u64, _>(i, "start_time_us")?,
+                finish_time_us: block.get::<u64, _>(i, "finish_time_us")?,
+                attributes: names.into_iter().zip(values).collect(),
+            });
+        }
+        return Ok(spans);
+    }
+
+    /// Materialized views pushed by the inserts (system.query_views_log) with
+    /// the tables they read from (system.tables), summed over the hosts.
+    pub async fn get_query_views_chain(
+        &self,
+        query_ids: &[String],
+        start: DateTime<Local>,
+        end: DateTime<Local>,
+    ) -> Result<Vec<ViewRun>> {
+        let sql = format!(
+            r#"
+                    WITH
+                        fromUnixTimestamp64Nano({start}) AS start_time_,
+                        fromUnixTimestamp64Nano({end}) AS end_time_
+                    SELECT views.*, sources.source AS source
+                    FROM (
+                        SELECT
+                            toString(view_name) AS view_name,
+                            toString(any(view_type)) AS view_type,
+                            toString(any(view_target)) AS view_target,
+                            arrayStringConcat(groupUniqArray(toString(status)), ',') AS status,
+                            anyIf(exception, exception != '') AS exception,
+                            sum(view_duration_ms) AS duration_ms,
+                            sum(read_rows) AS read_rows,
+                            sum(written_rows) AS written_rows,
+                            sum(written_bytes) AS written_bytes
+                        FROM {query_views_log}
+                        WHERE
+                                event_date >= toDate(start_time_) AND event_time >  toDateTime(start_time_) AND event_time_microseconds > start_time_
+                            AND event_date <= toDate(end_time_)   AND event_time <= toDateTime(end_time_)   AND event_time_microseconds <= end_time_
+                            AND initial_query_id IN ('{query_ids}')
+                        GROUP BY view_name
+                    ) AS views
+                    LEFT JOIN (
+                        -- view_name is database.table with the names back quoted if needed
+                        SELECT
+                            arrayJoin(arrayMap((d, t) -> concat(backQuoteIfNeed(d), '.', backQuoteIfNeed(t)),
+                                dependencies_database, dependencies_table)) AS view,
+                            any(concat(backQuoteIfNeed(database), '.', backQuoteIfNeed(name))) AS source
+                        FROM {tables}
+                        GROUP BY view
+                    ) AS sources ON sources.view = views.view_name
+                    "#,
+            start = start
+                .timestamp_nanos_opt()
+                .ok_or(Error::msg("Invalid time"))?,
+            end = end
+                .timestamp_nanos_opt()
+                .ok_or(Error::msg("Invalid time"))?,
+            query_views_log = self.get_log_table_name("query_views_log"),
+            tables = self.get_live_table_name("tables"),
+            query_ids = query_ids.join("','"),
+        );
+
+        let block = self.execute(&sql).await?;
+        let mut views = Vec::with_capacity(block.row_count());
+        for i in 0..block.row_count() {
+            views.push(ViewRun {
+                view_name: block.get::<String, _>(i, "view_name")?,
+                view_type: block.get::<String, _>(i, "view_type")?,
+                source: block.get::<String, _>(i, "source")?,
+                target: block.get::<String, _>(i, "view_target")?,
+                status: block.get::<String, _>(i, "status")?,
+                exception: block.get::<String, _>(i, "exception")?,
+                duration_ms: block.get::<u64, _>(i, "duration_ms")?,
+                read_rows: block.get::<u64, _>(i, "read_rows")?,
+                written_rows: block.get::<u64, _>(i, "written_rows")?,
+                written_bytes: block.get::<u64, _>(i, "written_bytes")?,
+            });
+        }
+        return Ok(views);
+    }
+
     pub async fn trace_log_counters_for_perfetto(
         &self,
         query_ids: Option<&[String]>,
@@ -2733,3 +3689,29 @@ impl ClickHouse {
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tui::views::providers::accessed_objects_condition;
+
+    #[test]
+    fn test_queries_filter_condition_clause() {
+        let mut filter = QueriesFilter {
+            condition: "user = 'foo'".to_string(),
+            accessed: accessed_objects_condition("db.t").unwrap(),
+            ..Default::default()
+        };
+        assert_eq!(
+            filter.condition_clause(),
+            " AND (user = 'foo') AND ((has(tables, 'db.t') OR has(views, 'db.t')))"
+        );
+
+        // An empty "Find queries by table/column" keeps the drill-down
+        filter.accessed = accessed_objects_condition("").unwrap_or_default();
+        assert_eq!(filter.condition_clause(), " AND (user = 'foo')");
+
+        filter.condition.clear();
+        assert_eq!(filter.condition_clause(), "");
+    }
+}
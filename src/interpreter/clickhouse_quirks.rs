@@ -12,10 +12,12 @@ pub enum ClickHouseAvailableQuirks {
     SystemBackgroundSchedulePool = 64,
     AdditionalTableFiltersInSubquery = 128,
     ProcessesQueryKind = 256,
+    SystemTablesLoadingDependencies = 512,
+    SystemReplicatedMergeTreeSettings = 1024,
 }
 
 // List of quirks (that requires workaround) or new features.
-const QUIRKS: [(&str, ClickHouseAvailableQuirks); 10] = [
+const QUIRKS: [(&str, ClickHouseAvailableQuirks); 12] = [
     // https://github.com/ClickHouse/ClickHouse/pull/46047
     //
     // NOTE: I use here 22.13 because I have such version in production, which is more or less the
@@ -51,6 +53,17 @@ const QUIRKS: [(&str, ClickHouseAvailableQuirks); 10] = [
     // query_kind is available in system.processes since 23.2
     // https://github.com/ClickHouse/ClickHouse/pull/45872
     (">=23.2", ClickHouseAvailableQuirks::ProcessesQueryKind),
+    // loading_dependencies_database/loading_dependencies_table are available in system.tables
+    // since 23.3
+    (
+        ">=23.3",
+        ClickHouseAvailableQuirks::SystemTablesLoadingDependencies,
+    ),
+    // system.replicated_merge_tree_settings is available since 23.3
+    (
+        ">=23.3",
+        ClickHouseAvailableQuirks::SystemReplicatedMergeTreeSettings,
+    ),
     // ClickHouse before 26.3 did not applied additional_table_filters to tables read from within a
     // subquery, so on such versions the initial_query_id selection is run as its own top-level
     // query first, and its result is spliced into the main query as a literal IN (...) list instead
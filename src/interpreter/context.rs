@@ -44,9 +44,17 @@ pub struct Context {
     /// Per-view '/'-filters of the queries views, keyed by view name; entries
     /// outlive the views so the filter survives switching views.
     queries_filters: std::collections::HashMap<String, Arc<Mutex<String>>>,
+    /// Per-view extra SQL conditions of the drill-down queries views (see
+    /// QueriesFilter::condition), keyed by view name.
+    queries_conditions: std::collections::HashMap<String, String>,
+    /// Per-view "Find queries by table/column" conditions, kept apart from
+    /// queries_conditions so that they do not replace the drill-down one.
+    queries_accessed_conditions: std::collections::HashMap<String, String>,
     pub queries_limit: Arc<Mutex<u64>>,
     pub query_patterns_metric:
         &'static crate::tui::views::providers::query_patterns_metrics::Metric,
+    pub query_leaderboard_dimension:
+        &'static crate::tui::views::providers::query_leaderboard::Dimension,
 
     pub debug_metrics: Arc<DebugMetrics>,
 }
@@ -67,6 +75,8 @@ impl Context {
         let queries_limit = Arc::new(Mutex::new(options.view.queries_limit));
         let query_patterns_metric =
             crate::tui::views::providers::query_patterns_metrics::default_metric();
+        let query_leaderboard_dimension =
+            crate::tui::views::providers::query_leaderboard::default_dimension();
 
         // Metrics are always collected; display is toggled with `!`. The refresh thread
         // sleeps when hidden, so this is free when unused.
@@ -91,8 +101,11 @@ impl Context {
             view_history: Vec::new(),
             perfetto_server: None,
             queries_filters: std::collections::HashMap::new(),
+            queries_conditions: std::collections::HashMap::new(),
+            queries_accessed_conditions: std::collections::HashMap::new(),
             queries_limit,
             query_patterns_metric,
+            query_leaderboard_dimension,
             debug_metrics,
         }));
 
@@ -155,11 +168,26 @@ impl Context {
             .unwrap_or_default()
     }
 
+    /// Configured extra ProfileEvents columns of a queries view.
+    pub fn view_profile_event_columns(
+        &self,
+        view_name: &str,
+    ) -> Vec<crate::interpreter::options::ProfileEventColumn> {
+        self.view_settings(view_name)
+            .map(|settings| settings.profile_events.clone())
+            .unwrap_or_default()
+    }
+
     /// Configured maximum log level for the view (`level <= '...'`).
     pub fn view_level(&self, view_name: &str) -> Option<crate::interpreter::options::LogLevel> {
         self.view_settings(view_name)?.level
     }
 
+    /// Configured small inserts threshold of the inserts view.
+    pub fn view_min_rows_per_insert(&self, view_name: &str) -> Option<u64> {
+        self.view_settings(view_name)?.min_rows_per_insert
+    }
+
     /// The row limit for the view's own query: per-view override or `default`.
     pub fn view_limit(&self, view_name: &str, default: u64) -> u64 {
         self.view_limit_override(view_name).unwrap_or(default)
@@ -190,6 +218,32 @@ impl Context {
         filter
     }
 
+    /// Extra SQL condition of a queries view (empty - none).
+    pub fn queries_condition(&self, view_name: &str) -> String {
+        self.queries_conditions
+            .get(view_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn set_queries_condition(&mut self, view_name: &str, condition: String) {
+        self.queries_conditions
+            .insert(view_name.to_string(), condition);
+    }
+
+    /// Accessed objects condition of a queries view (empty - none).
+    pub fn queries_accessed_condition(&self, view_name: &str) -> String {
+        self.queries_accessed_conditions
+            .get(view_name)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    pub fn set_queries_accessed_condition(&mut self, view_name: &str, condition: String) {
+        self.queries_accessed_conditions
+            .insert(view_name.to_string(), condition);
+    }
+
     /// Queries filter edited in the settings dialog: the current queries
     /// view's one (falls back to the processes view when the current view is
     /// not a queries view).
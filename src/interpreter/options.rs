@@ -9,7 +9,7 @@ use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
 use quick_xml::de::Deserializer as XmlDeserializer;
 use serde::Deserialize;
 use serde_yaml::Deserializer as YamlDeserializer;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::ffi::OsString;
 use std::fmt;
@@ -19,6 +19,7 @@ use std::net::{SocketAddr, ToSocketAddrs};
 use std::path;
 use std::process;
 use std::str::FromStr;
+use std::sync::{LazyLock, Mutex};
 use std::time;
 
 #[derive(Deserialize, Debug, PartialEq)]
@@ -112,6 +113,10 @@ pub enum ChDigViews {
     SlowQueries,
     /// Show queries grouped by normalized_query_hash with duration distribution (from system.query_log)
     QueryPatterns,
+    /// Show failed queries grouped by exception code and normalized_query_hash (from system.query_log)
+    FailedQueries,
+    /// Show top users, clients, user agents, tables and interfaces (from system.query_log)
+    QueryLeaderboard,
     /// Show merges for MergeTree engine (system.merges)
     Merges,
     /// Show S3 Queue (system.s3queue_metadata_cache)
@@ -128,6 +133,10 @@ pub enum ChDigViews {
     Replicas,
     /// Tables
     Tables,
+    /// Show tables with definitions that differ between the hosts of the cluster (system.tables, system.columns)
+    SchemaDrift,
+    /// Show MergeTree settings with the per-table overrides (system.merge_tree_settings)
+    MergeTreeSettings,
     /// Show all errors that happened in a server since start (system.errors)
     Errors,
     /// Show errors over time (system.error_log)
@@ -148,6 +157,8 @@ pub enum ChDigViews {
     TableParts,
     /// Show asynchronous inserts (system.asynchronous_inserts)
     AsynchronousInserts,
+    /// Show insert rate and small inserts per table (system.query_log, system.part_log)
+    Inserts,
     /// Show part log (system.part_log)
     PartLog,
     /// Show metrics with average per-second rates (system.metric_log)
@@ -186,6 +197,8 @@ impl ChDigViews {
         ("last_queries", ChDigViews::LastQueries),
         ("slow_queries", ChDigViews::SlowQueries),
         ("query_patterns", ChDigViews::QueryPatterns),
+        ("failed_queries", ChDigViews::FailedQueries),
+        ("query_leaderboard", ChDigViews::QueryLeaderboard),
         ("merges", ChDigViews::Merges),
         ("s3_queue", ChDigViews::S3Queue),
         ("azure_queue", ChDigViews::AzureQueue),
@@ -194,6 +207,8 @@ impl ChDigViews {
         ("replicated_fetches", ChDigViews::ReplicatedFetches),
         ("replicas", ChDigViews::Replicas),
         ("tables", ChDigViews::Tables),
+        ("schema_drift", ChDigViews::SchemaDrift),
+        ("merge_tree_settings", ChDigViews::MergeTreeSettings),
         ("errors", ChDigViews::Errors),
         ("error_log", ChDigViews::ErrorLog),
         ("backups", ChDigViews::Backups),
@@ -210,6 +225,7 @@ impl ChDigViews {
         ),
         ("table_parts", ChDigViews::TableParts),
         ("asynchronous_inserts", ChDigViews::AsynchronousInserts),
+        ("inserts", ChDigViews::Inserts),
         ("part_log", ChDigViews::PartLog),
         ("metric_log", ChDigViews::MetricLog),
         ("asynchronous_metric_log", ChDigViews::AsynchronousMetricLog),
@@ -242,6 +258,13 @@ impl ChDigViews {
             .unwrap()
     }
 
+    /// Views of the queries (with the queries view columns, i.e. the ones
+    /// that take `profile_events`).
+    pub fn is_queries_view(self) -> bool {
+        use ChDigViews::*;
+        matches!(self, Queries | LastQueries | SlowQueries)
+    }
+
     /// Base types that named `views:` instances may use (their widget names,
     /// worker events and settings lookups are instance-aware).
     pub fn supports_instances(self) -> bool {
@@ -293,8 +316,23 @@ pub const RESERVED_VIEW_NAMES: &[&str] = &[
     "logger_logs",
     "background_schedule_pool_logs",
     "filtered_logs",
+    "query_pattern_queries",
+    "query_patterns_compare",
+    "query_leaderboard_queries",
+    "inserts_queries",
+    "table_queries",
+    "table_columns",
+    "table_indices",
+    "table_merge_tree_settings",
+    "table_partitions",
+    "query_threads",
+    "query_thread_profile_events",
 ];
 
+/// Prefixes of the widget names with the object in the name (e.g.
+/// "table_dependencies_db_t"), reserved the same way as RESERVED_VIEW_NAMES.
+pub const RESERVED_VIEW_NAME_PREFIXES: &[&str] = &["table_dependencies_"];
+
 /// Accepts both snake_case and the CLI kebab-case.
 impl FromStr for ChDigViews {
     type Err = anyhow::Error;
@@ -1097,6 +1135,72 @@ pub struct ChDigViewSettings {
     pub limit: Option<u64>,
     /// Maximum log level for log views (Error = Fatal, Critical and Error).
     pub level: Option<LogLevel>,
+    /// Extra columns of a queries view with the ProfileEvents values ("Name",
+    /// or "Name/s" for the per-second rate). A single value or a list.
+    #[serde(deserialize_with = "deserialize_profile_event_columns")]
+    pub profile_events: Vec<ProfileEventColumn>,
+    /// Tables averaging fewer rows per insert are flagged in the inserts
+    /// view.
+    pub min_rows_per_insert: Option<u64>,
+}
+
+/// A `ProfileEvents['name']` column of the queries views.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProfileEventColumn {
+    pub name: &'static str,
+    /// Column header, as in the config.
+    pub label: &'static str,
+    /// Per-second rate instead of the raw value.
+    pub rate: bool,
+}
+
+impl FromStr for ProfileEventColumn {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, rate) = match s.strip_suffix("/s") {
+            Some(name) => (name, true),
+            None => (s, false),
+        };
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!(
+                "invalid ProfileEvent column '{}' (expected Name or Name/s)",
+                s
+            ));
+        }
+        let label = intern_label(s);
+        Ok(Self {
+            name: &label[..name.len()],
+            label,
+            rate,
+        })
+    }
+}
+
+/// The queries view columns have to be Copy, so the labels are &'static str:
+/// each distinct one is leaked once, not on every config parsing.
+fn intern_label(label: &str) -> &'static str {
+    static LABELS: LazyLock<Mutex<HashSet<&'static str>>> =
+        LazyLock::new(|| Mutex::new(HashSet::new()));
+    let mut labels = LABELS.lock().unwrap();
+    if let Some(interned) = labels.get(label) {
+        return interned;
+    }
+    let interned: &'static str = Box::leak(label.to_string().into_boxed_str());
+    labels.insert(interned);
+    interned
+}
+
+fn deserialize_profile_event_columns<'de, D>(
+    deserializer: D,
+) -> Result<Vec<ProfileEventColumn>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    string_or_seq(deserializer)?
+        .iter()
+        .map(|s| s.parse().map_err(serde::de::Error::custom))
+        .collect()
 }
 
 /// A `views:` entry: settings for a builtin view (the key is the view name)
@@ -1199,7 +1303,11 @@ where
                         name
                     )));
                 }
-                if RESERVED_VIEW_NAMES.contains(&name.as_str()) {
+                if RESERVED_VIEW_NAMES.contains(&name.as_str())
+                    || RESERVED_VIEW_NAME_PREFIXES
+                        .iter()
+                        .any(|prefix| name.starts_with(prefix))
+                {
                     return Err(D::Error::custom(format!(
                         "views: instance name '{}' is reserved",
                         name
@@ -1208,6 +1316,19 @@ where
                 base
             }
         };
+        // Settings of the other views would be silently ignored
+        if !settings.profile_events.is_empty() && !view_type.is_queries_view() {
+            return Err(D::Error::custom(format!(
+                "views: '{}': profile_events is supported only by the queries views",
+                name
+            )));
+        }
+        if settings.min_rows_per_insert.is_some() && view_type != ChDigViews::Inserts {
+            return Err(D::Error::custom(format!(
+                "views: '{}': min_rows_per_insert is supported only by the inserts view",
+                name
+            )));
+        }
         views.insert(
             name,
             ViewInstance {
@@ -2422,6 +2543,16 @@ views:
         assert_eq!(last_queries.settings.limit, None);
     }
 
+    #[test]
+    fn test_chdig_config_view_min_rows_per_insert() {
+        let config: ChDigConfig =
+            serde_yaml::from_str("views:\n  inserts:\n    min_rows_per_insert: 10000\n").unwrap();
+        let inserts = &config.views["inserts"];
+        assert_eq!(inserts.view_type, ChDigViews::Inserts);
+        assert_eq!(inserts.settings.min_rows_per_insert, Some(10000));
+        assert_eq!(inserts.settings.limit, None);
+    }
+
     #[test]
     fn test_chdig_config_view_instances() {
         let config: ChDigConfig = serde_yaml::from_str(
@@ -2448,6 +2579,23 @@ views:
             vec!["Create", "Drop", "Alter"]
         );
 
+        // profile_events: raw values and rates
+        let config: ChDigConfig = serde_yaml::from_str(
+            "views:\n  queries:\n    profile_events: [SelectedMarks, S3ReadRequestsCount/s]\n",
+        )
+        .unwrap();
+        let columns = &config.views["queries"].settings.profile_events;
+        assert_eq!(
+            columns
+                .iter()
+                .map(|c| (c.name, c.label, c.rate))
+                .collect::<Vec<_>>(),
+            vec![
+                ("SelectedMarks", "SelectedMarks", false),
+                ("S3ReadRequestsCount", "S3ReadRequestsCount/s", true)
+            ]
+        );
+
         let views_error = |yaml: &str| {
             serde_yaml::from_str::<ChDigConfig>(yaml)
                 .err()
@@ -2462,7 +2610,23 @@ views:
             views_error("views:\n  my_merges:\n    view: merges\n")
                 .contains("does not support named instances")
         );
+        assert!(
+            views_error("views:\n  queries:\n    profile_events: \"SelectedMarks/m\"\n")
+                .contains("invalid ProfileEvent column")
+        );
         assert!(views_error("views:\n  processes:\n    view: last_queries\n").contains("reserved"));
+        assert!(
+            views_error("views:\n  table_dependencies_db_t:\n    view: last_queries\n")
+                .contains("reserved")
+        );
+        assert!(
+            views_error("views:\n  merges:\n    profile_events: SelectedMarks\n")
+                .contains("profile_events is supported only by the queries views")
+        );
+        assert!(
+            views_error("views:\n  queries:\n    min_rows_per_insert: 100\n")
+                .contains("min_rows_per_insert is supported only by the inserts view")
+        );
         assert!(
             views_error("views:\n  1st:\n    view: last_queries\n")
                 .contains("invalid instance name")
@@ -245,6 +245,29 @@ impl Query {
         ]);
     }
 
+    pub fn profile_event(&self, name: &str) -> u64 {
+        return *self.profile_events.get(name).unwrap_or(&0);
+    }
+
+    /// Per-second rate of the event (for the running queries - since the previous update).
+    pub fn profile_event_rate(&self, name: &str) -> f64 {
+        let value = self.profile_event(name);
+        if self.running
+            && let (Some(prev_profile_events), Some(prev_elapsed)) =
+                (&self.prev_profile_events, self.prev_elapsed)
+        {
+            let prev = *prev_profile_events.get(name).unwrap_or(&0);
+            let elapsed = self.elapsed - prev_elapsed;
+            if elapsed > 0. {
+                return value.saturating_sub(prev) as f64 / elapsed;
+            }
+        }
+        if self.elapsed > 0. {
+            return value as f64 / self.elapsed;
+        }
+        return 0.;
+    }
+
     fn get_profile_events_multi(&self, names: &[&'static str]) -> u64 {
         let mut result: u64 = 0;
         for &name in names {
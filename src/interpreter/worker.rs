@@ -1,5 +1,10 @@
 use crate::{
-    common::{RelativeDateTime, Stopwatch},
+    common::{
+        BENCHMARK_MAX_DURATION, BenchmarkRun, BenchmarkSpec, BenchmarkVariant, PipelineGraph,
+        PlanNode, RelativeDateTime, Stopwatch, ViewChainNode, ViewChainRow, ViewRun,
+        benchmark_query_text, dependencies_tree, diff_texts, merge_tree_table_settings,
+        schema_variants, table_dependencies, views_chain,
+    },
     interpreter::{
         ContextArc, Query,
         clickhouse::{
@@ -10,20 +15,26 @@ use crate::{
         perfetto::PerfettoTraceBuilder,
     },
     pastila,
-    tui::highlight_sql,
+    tui::{
+        StyledString, highlight_sql, highlight_sql_diff,
+        style::{Color, Modifier, Style as TextStyle},
+    },
     utils::share_graph,
 };
 use anyhow::{Result, anyhow};
-use chrono::{DateTime, Local};
+use chrono::{DateTime, Local, TimeDelta};
 // FIXME: "leaky abstractions"
 use clickhouse_rs::Block;
 use clickhouse_rs::errors::Error as ClickHouseError;
 use clickhouse_rs::types::Progress;
 
+use crate::tui::views::pipeline_graph_view::PipelineGraphView;
+use crate::tui::views::plan_tree_view::PlanTreeView;
 use crate::tui::views::queries_view::QueriesView;
 use crate::tui::views::sql_query_view::SQLQueryView;
 use crate::tui::views::summary_view::SummaryView;
 use crate::tui::views::text_log_view::TextLogView;
+use crate::tui::views::trace_spans_view::TraceSpansView;
 use crate::tui::{
     App, Dialog, DummyView, Event as UiEvent, LinearLayout, NamedView, Navigation, OnEventView,
     Resizable, Scrollable, TextView, UiSink,
@@ -147,16 +158,34 @@ pub enum Event {
     KillQuery(String),
     // (database, query)
     ExecuteQuery(String, String),
+    // (database, query, settings of the query, runs and settings variants)
+    BenchmarkQuery(String, String, Arc<HashMap<String, String>>, BenchmarkSpec),
     // (database, query, settings)
     ExplainSyntax(String, String, Arc<HashMap<String, String>>),
     // (database, query)
+    ShowFormattedQuery(String, String),
+    // ((query_id, query), (query_id, query))
+    DiffQueries((String, String), (String, String)),
+    // (database, query)
     ExplainPlan(String, String),
     // (database, query)
+    ExplainPlanTree(String, String),
+    // (database, query)
     ExplainPipeline(String, String),
     // (database, query)
     ExplainPipelineShareGraph(String, String),
+    // (database, query, (query_id, start, end) of the finished query for processors_profile_log)
+    ExplainPipelineGraph(
+        String,
+        String,
+        Option<(String, DateTime<Local>, DateTime<Local>)>,
+    ),
     // (database, query)
     ExplainPlanIndexes(String, String),
+    // (query_ids, start, end (None for running queries))
+    ShowTraceSpans(Vec<String>, DateTime<Local>, Option<DateTime<Local>>),
+    // (query_ids, start, end)
+    ShowQueryViewsChain(Vec<String>, DateTime<Local>, DateTime<Local>),
     // (database, table)
     ShowCreateTable(String, String),
     // (view_name, query); the name is Arc<str> since dialog views get
@@ -173,8 +202,23 @@ pub enum Event {
         RelativeDateTime,
     ),
     // (database, table)
+    TableOverview(String, String),
+    // (database, table)
     TableParts(String, String),
     // (database, table)
+    TablePartitions(String, String),
+    MergeTreeSettings,
+    // (database, table)
+    TableMergeTreeSettings(String, String),
+    // (database, table)
+    TableDependencies(String, String),
+    // (database, table)
+    SchemaDriftDiff(String, String),
+    // (database, table, table_uuid, part_name, time the part was created)
+    PartLineage(String, String, String, String, DateTime<Local>),
+    // (database, table, start, end, number of buckets)
+    TableGrowth(String, String, RelativeDateTime, RelativeDateTime, u32),
+    // (database, table)
     AsynchronousInserts(String, String),
     // (content to share via pastila)
     ShareLogs(OpaquePayload<String>),
@@ -223,15 +267,30 @@ impl Event {
             Event::KillQuery(..) => "KillQuery".to_string(),
             Event::ExecuteQuery(..) => "ExecuteQuery".to_string(),
             Event::ExplainSyntax(..) => "ExplainSyntax".to_string(),
+            Event::BenchmarkQuery(..) => "BenchmarkQuery".to_string(),
+            Event::ShowFormattedQuery(..) => "ShowFormattedQuery".to_string(),
+            Event::DiffQueries(..) => "DiffQueries".to_string(),
             Event::ExplainPlan(..) => "ExplainPlan".to_string(),
+            Event::ExplainPlanTree(..) => "ExplainPlanTree".to_string(),
             Event::ExplainPipeline(..) => "ExplainPipeline".to_string(),
             Event::ExplainPipelineShareGraph(..) => "ExplainPipelineShareGraph".to_string(),
+            Event::ExplainPipelineGraph(..) => "ExplainPipelineGraph".to_string(),
             Event::ExplainPlanIndexes(..) => "ExplainPlanIndexes".to_string(),
+            Event::ShowTraceSpans(..) => "ShowTraceSpans".to_string(),
+            Event::ShowQueryViewsChain(..) => "ShowQueryViewsChain".to_string(),
             Event::ShowCreateTable(..) => "ShowCreateTable".to_string(),
             Event::SQLQuery(view_name, _query) => format!("SQLQuery({})", view_name),
             Event::ShowChart(title, ..) => format!("ShowChart({})", title),
             Event::BackgroundSchedulePoolLogs(..) => "BackgroundSchedulePoolLogs".to_string(),
+            Event::TableOverview(..) => "TableOverview".to_string(),
             Event::TableParts(..) => "TableParts".to_string(),
+            Event::TablePartitions(..) => "TablePartitions".to_string(),
+            Event::MergeTreeSettings => "MergeTreeSettings".to_string(),
+            Event::TableMergeTreeSettings(..) => "TableMergeTreeSettings".to_string(),
+            Event::TableDependencies(..) => "TableDependencies".to_string(),
+            Event::SchemaDriftDiff(..) => "SchemaDriftDiff".to_string(),
+            Event::PartLineage(..) => "PartLineage".to_string(),
+            Event::TableGrowth(..) => "TableGrowth".to_string(),
             Event::AsynchronousInserts(..) => "AsynchronousInserts".to_string(),
             Event::ShareLogs(..) => "ShareLogs".to_string(),
             Event::PerfettoExport(..) => "PerfettoExport".to_string(),
@@ -1459,6 +1518,116 @@ async fn process_event(context: ContextArc, event: Event, need_clear: &mut bool)
                 }))
                 .map_err(|_| anyhow!("Cannot send message to UI"))?;
         }
+        Event::BenchmarkQuery(database, query, query_settings, spec) => {
+            // Marks the runs in the query_log
+            let start = Local::now();
+            let tag = format!("chdig-benchmark-{}", start.timestamp_micros());
+            // Settings of the query, with the variant on top of them
+            let query_settings: Vec<(String, String)> = query_settings
+                .iter()
+                .filter(|(name, _)| name.as_str() != "log_comment")
+                .map(|(name, value)| (name.clone(), value.clone()))
+                .collect();
+
+            let variants_settings: Vec<Vec<(String, String)>> = spec
+                .variants
+                .iter()
+                .map(|settings| query_settings.iter().chain(settings).cloned().collect())
+                .collect();
+            let query = benchmark_query_text(&query);
+
+            // The variants are interleaved, so that the time limit cuts all of
+            // them evenly (and a load change affects all of them)
+            let mut runs = vec![Vec::new(); spec.variants.len()];
+            let benchmark_stopwatch = Stopwatch::start_new();
+            let mut time_limit_reached = false;
+            let result: Result<()> = async {
+                for run in 0..spec.runs {
+                    for (variant, settings) in variants_settings.iter().enumerate() {
+                        if benchmark_stopwatch.elapsed() > BENCHMARK_MAX_DURATION {
+                            time_limit_reached = true;
+                            return Ok(());
+                        }
+                        update_statusbar(
+                            &cb_sink,
+                            &format!(
+                                "Benchmarking: run {}/{}, variant {}/{}",
+                                run + 1,
+                                spec.runs,
+                                variant + 1,
+                                spec.variants.len()
+                            ),
+                        );
+                        let log_comment = format!("{}-{}-{}", tag, variant, run);
+                        let stopwatch = Stopwatch::start_new();
+                        clickhouse
+                            .benchmark_query(&database, &query, settings, &log_comment)
+                            .await?;
+                        runs[variant].push((log_comment, stopwatch.elapsed().as_secs_f64() * 1e3));
+                    }
+                }
+                Ok(())
+            }
+            .await;
+            // The "Benchmarking..." dialog (see show_benchmark_prompt()) may
+            // have been dismissed already, so it is removed by name
+            cb_sink
+                .send(Box::new(|app: &mut App| {
+                    app.remove_layer_by_name("benchmark_progress");
+                }))
+                .unwrap_or_default();
+            result?;
+
+            // Without the query_log rows only the client side durations are shown
+            if let Err(err) = clickhouse.flush_logs().await {
+                log::warn!("Cannot flush logs: {}", err);
+            }
+            let mut logged = clickhouse
+                .get_benchmark_runs(&tag, start)
+                .await
+                .unwrap_or_else(|err| {
+                    log::warn!("Cannot get benchmark runs from query_log: {}", err);
+                    HashMap::new()
+                });
+            let variants: Vec<BenchmarkVariant> = spec
+                .variants
+                .into_iter()
+                .zip(runs)
+                .map(|(settings, runs)| BenchmarkVariant {
+                    settings,
+                    runs: runs
+                        .into_iter()
+                        .map(|(log_comment, duration_ms)| {
+                            logged.remove(&log_comment).unwrap_or(BenchmarkRun {
+                                duration_ms,
+                                ..Default::default()
+                            })
+                        })
+                        .collect(),
+                })
+                .collect();
+
+            let title = if time_limit_reached {
+                format!(
+                    "Benchmark (stopped after {}s, the runs done so far)",
+                    BENCHMARK_MAX_DURATION.as_secs()
+                )
+            } else {
+                format!("Benchmark ({} runs)", spec.runs)
+            };
+            cb_sink
+                .send(Box::new(move |app: &mut App| {
+                    let mut reports = LinearLayout::horizontal();
+                    for (i, variant) in variants.iter().enumerate() {
+                        if i > 0 {
+                            reports.add_child(DummyView.fixed_width(4));
+                        }
+                        reports.add_child(TextView::new(variant.report()));
+                    }
+                    app.add_layer(Dialog::around(reports.scrollable().scroll_x(true)).title(title));
+                }))
+                .map_err(|_| anyhow!("Cannot send message to UI"))?;
+        }
         Event::ExplainSyntax(database, query, settings) => {
             let query = clickhouse
                 .explain_syntax(database.as_str(), query.as_str(), &settings)
@@ -1479,6 +1648,48 @@ async fn process_event(context: ContextArc, event: Event, need_clear: &mut bool)
                 }))
                 .map_err(|_| anyhow!("Cannot send message to UI"))?;
         }
+        Event::ShowFormattedQuery(database, query) => {
+            let (query, title) = match clickhouse.format_query(&query).await {
+                Ok(formatted) => (formatted, "Formatted query"),
+                Err(err) => {
+                    // formatQuery() is 23.10+, and it cannot format everything
+                    log::warn!("Cannot format query: {}", err);
+                    (query, "Query (formatQuery() is not available)")
+                }
+            };
+            let query = format!("USE {};\n{}", database, query);
+            let query = highlight_sql(&query)?;
+            cb_sink
+                .send(Box::new(move |app: &mut App| {
+                    app.add_layer(Dialog::around(TextView::new(query).scrollable()).title(title));
+                }))
+                .map_err(|_| anyhow!("Cannot send message to UI"))?;
+        }
+        Event::DiffQueries((left_id, left), (right_id, right)) => {
+            // Formatting normalizes the whitespaces and the line breaks, so
+            // that only the meaningful differences are left
+            let format = async |query: String| match clickhouse.format_query(&query).await {
+                Ok(formatted) => formatted,
+                Err(err) => {
+                    log::warn!("Cannot format query: {}", err);
+                    query
+                }
+            };
+            let left = format(left).await;
+            let right = format(right).await;
+            let (left, right, changed) = highlight_sql_texts_diff(&left, &right)?;
+
+            let title = format!("{} vs {} ({} changed lines)", left_id, right_id, changed);
+            cb_sink
+                .send(Box::new(move |app: &mut App| {
+                    let diff = LinearLayout::horizontal()
+                        .child(TextView::new(left))
+                        .child(DummyView.fixed_width(4))
+                        .child(TextView::new(right));
+                    app.add_layer(Dialog::around(diff.scrollable().scroll_x(true)).title(title));
+                }))
+                .map_err(|_| anyhow!("Cannot send message to UI"))?;
+        }
         Event::ExplainPlan(database, query) => {
             let plan = clickhouse
                 .explain_plan(database.as_str(), query.as_str())
@@ -1498,6 +1709,20 @@ async fn process_event(context: ContextArc, event: Event, need_clear: &mut bool)
                 }))
                 .map_err(|_| anyhow!("Cannot send message to UI"))?;
         }
+        Event::ExplainPlanTree(database, query) => {
+            let json = clickhouse
+                .explain_plan_json(database.as_str(), query.as_str())
+                .await?;
+            let plans = PlanNode::parse_explain_json(&json)?;
+            cb_sink
+                .send(Box::new(move |app: &mut App| {
+                    app.add_layer(
+                        Dialog::around(PlanTreeView::new(plans).full_screen())
+                            .title("EXPLAIN PLAN"),
+                    );
+                }))
+                .map_err(|_| anyhow!("Cannot send message to UI"))?;
+        }
         Event::ExplainPipeline(database, query) => {
             let pipeline = clickhouse
                 .explain_pipeline(database.as_str(), query.as_str())
@@ -1519,7 +1744,7 @@ async fn process_event(context: ContextArc, event: Event, need_clear: &mut bool)
         }
         Event::ExplainPipelineShareGraph(database, query) => {
             let pipeline = clickhouse
-                .explain_pipeline_graph(database.as_str(), query.as_str())
+                .explain_pipeline_graph(database.as_str(), query.as_str(), true)
                 .await?
                 .join("\n");
 
@@ -1540,6 +1765,76 @@ async fn process_event(context: ContextArc, event: Event, need_clear: &mut bool)
                 }
             }
         }
+        Event::ExplainPipelineGraph(database, query, finished) => {
+            let dot = clickhouse
+                .explain_pipeline_graph(database.as_str(), query.as_str(), false)
+                .await?
+                .join("\n");
+            let graph = PipelineGraph::parse(&dot);
+
+            // The graph is useful on its own, do not fail if processors
+            // profiling is not available (log_processors_profiles=0, no table)
+            let profile = match finished {
+                Some((query_id, start, end)) => clickhouse
+                    .get_query_processors_profile(&query_id, start, end)
+                    .await
+                    .unwrap_or_else(|err| {
+                        log::warn!("Cannot get processors profile for {}: {}", query_id, err);
+                        HashMap::new()
+                    }),
+                None => HashMap::new(),
+            };
+
+            cb_sink
+                .send(Box::new(move |app: &mut App| {
+                    app.add_layer(
+                        Dialog::around(PipelineGraphView::new(graph, profile).full_screen())
+                            .title("EXPLAIN PIPELINE graph=1"),
+                    );
+                }))
+                .map_err(|_| anyhow!("Cannot send message to UI"))?;
+        }
+        Event::ShowTraceSpans(query_ids, start, end) => {
+            // Spans may be written with a small delay relative to the query_log
+            let drift = TimeDelta::seconds(1);
+            let end = end.unwrap_or_else(Local::now);
+            let spans = clickhouse
+                .get_query_trace_spans(&query_ids, start - drift, end + drift)
+                .await?;
+            if spans.is_empty() {
+                return Err(anyhow!(
+                    "No spans in opentelemetry_span_log (is opentelemetry_start_trace_probability set?)"
+                ));
+            }
+            cb_sink
+                .send(Box::new(move |app: &mut App| {
+                    app.add_layer(
+                        Dialog::around(TraceSpansView::new(spans).full_screen())
+                            .title("Trace spans"),
+                    );
+                }))
+                .map_err(|_| anyhow!("Cannot send message to UI"))?;
+        }
+        Event::ShowQueryViewsChain(query_ids, start, end) => {
+            let views = clickhouse
+                .get_query_views_chain(&query_ids, start, end)
+                .await?;
+            if views.is_empty() {
+                return Err(anyhow!(
+                    "No views in query_views_log (is log_query_views enabled?)"
+                ));
+            }
+            let rows = views_chain(&views);
+            let text = format_views_chain(&views, &rows);
+            cb_sink
+                .send(Box::new(move |app: &mut App| {
+                    app.add_layer(
+                        Dialog::around(TextView::new(text).scrollable().scroll_x(true))
+                            .title("Materialized views chain"),
+                    );
+                }))
+                .map_err(|_| anyhow!("Cannot send message to UI"))?;
+        }
         Event::ShowCreateTable(database, table) => {
             let create_statement = clickhouse
                 .show_create_table(database.as_str(), table.as_str())
@@ -1700,6 +1995,14 @@ async fn process_event(context: ContextArc, event: Event, need_clear: &mut bool)
                 }))
                 .map_err(|_| anyhow!("Cannot send message to UI"))?;
         }
+        Event::TableOverview(database, table) => {
+            let overview = clickhouse.get_table_overview(&database, &table).await?;
+            cb_sink
+                .send(Box::new(move |app: &mut App| {
+                    crate::tui::views::providers::tables::show_table_overview(app, overview);
+                }))
+                .map_err(|_| anyhow!("Cannot send message to UI"))?;
+        }
         Event::TableParts(database, table) => {
             cb_sink
                 .send(Box::new(move |app: &mut App| {
@@ -1714,6 +2017,201 @@ async fn process_event(context: ContextArc, event: Event, need_clear: &mut bool)
                 }))
                 .map_err(|_| anyhow!("Cannot send message to UI"))?;
         }
+        Event::TablePartitions(database, table) => {
+            let create_statement = clickhouse.show_create_table(&database, &table).await?;
+            let parts_to_delay_insert = clickhouse
+                .get_merge_tree_settings(&create_statement, &["parts_to_delay_insert"])
+                .await?
+                .get("parts_to_delay_insert")
+                .and_then(|value| value.parse::<u64>().ok())
+                .unwrap_or_default();
+            cb_sink
+                .send(Box::new(move |app: &mut App| {
+                    let context = app.user_data::<ContextArc>().unwrap().clone();
+                    crate::tui::views::providers::table_parts::show_table_partitions(
+                        app,
+                        context,
+                        database,
+                        table,
+                        parts_to_delay_insert,
+                    );
+                }))
+                .map_err(|_| anyhow!("Cannot send message to UI"))?;
+        }
+        Event::MergeTreeSettings => {
+            let overrides = clickhouse.get_merge_tree_settings_overrides().await?;
+            cb_sink
+                .send(Box::new(move |app: &mut App| {
+                    let context = app.user_data::<ContextArc>().unwrap().clone();
+                    crate::tui::views::providers::merge_tree_settings::show_merge_tree_settings(
+                        app, context, overrides,
+                    );
+                }))
+                .map_err(|_| anyhow!("Cannot send message to UI"))?;
+        }
+        Event::TableMergeTreeSettings(database, table) => {
+            let create_statement = clickhouse.show_create_table(&database, &table).await?;
+            let overrides = merge_tree_table_settings(&create_statement);
+            cb_sink
+                .send(Box::new(move |app: &mut App| {
+                    let context = app.user_data::<ContextArc>().unwrap().clone();
+                    crate::tui::views::providers::merge_tree_settings::show_table_merge_tree_settings(
+                        app, context, database, table, overrides,
+                    );
+                }))
+                .map_err(|_| anyhow!("Cannot send message to UI"))?;
+        }
+        Event::TableDependencies(database, table) => {
+            let tables = clickhouse.get_table_definitions().await?;
+            let edges = table_dependencies(&tables);
+            let nodes = dependencies_tree(&edges, &(database.clone(), table.clone()));
+            cb_sink
+                .send(Box::new(move |app: &mut App| {
+                    let context = app.user_data::<ContextArc>().unwrap().clone();
+                    crate::tui::views::providers::tables::show_table_dependencies(
+                        app, context, database, table, nodes,
+                    );
+                }))
+                .map_err(|_| anyhow!("Cannot send message to UI"))?;
+        }
+        Event::SchemaDriftDiff(database, table) => {
+            let schemas = clickhouse.get_table_schemas(&database, &table).await?;
+            let (variants, columns_differ) = schema_variants(schemas);
+            if variants.len() < 2 {
+                return Err(anyhow!(
+                    "{}.{} is the same on all the hosts it exists on",
+                    database,
+                    table
+                ));
+            }
+
+            // Formatting splits the statements into lines, that can be diffed
+            let mut texts = Vec::with_capacity(variants.len());
+            for variant in &variants {
+                let create_table_query = clickhouse
+                    .format_query(&variant.create_table_query)
+                    .await
+                    .unwrap_or_else(|err| {
+                        log::warn!("Cannot format query: {}", err);
+                        variant.create_table_query.clone()
+                    });
+                texts.push(variant.text(&create_table_query, columns_differ));
+            }
+            // Every other variant against the most common one
+            let mut diffs = Vec::with_capacity(variants.len() - 1);
+            for (variant, text) in variants.iter().zip(texts.iter()).skip(1) {
+                let (left, right, changed) = highlight_sql_texts_diff(&texts[0], text)?;
+                let title = format!(
+                    "{} vs {} ({} changed lines)",
+                    variants[0].hosts.join(", "),
+                    variant.hosts.join(", "),
+                    changed
+                );
+                diffs.push((title, left, right));
+            }
+
+            let title = format!(
+                "{}.{}: {} definitions on {} hosts",
+                database,
+                table,
+                variants.len(),
+                variants.iter().map(|v| v.hosts.len()).sum::<usize>()
+            );
+            cb_sink
+                .send(Box::new(move |app: &mut App| {
+                    let mut layout = LinearLayout::vertical();
+                    for (title, left, right) in diffs {
+                        layout.add_child(TextView::new(StyledString::styled(
+                            title,
+                            TextStyle::new()
+                                .fg(Color::Yellow)
+                                .add_modifier(Modifier::BOLD),
+                        )));
+                        layout.add_child(
+                            LinearLayout::horizontal()
+                                .child(TextView::new(left))
+                                .child(DummyView.fixed_width(4))
+                                .child(TextView::new(right)),
+                        );
+                        layout.add_child(DummyView.fixed_height(1));
+                    }
+                    app.add_layer(Dialog::around(layout.scrollable().scroll_x(true)).title(title));
+                }))
+                .map_err(|_| anyhow!("Cannot send message to UI"))?;
+        }
+        Event::PartLineage(database, table, table_uuid, part_name, created) => {
+            let lineage = clickhouse
+                .get_part_lineage(&database, &table, &table_uuid, &part_name, created)
+                .await?;
+            if lineage.is_empty() {
+                return Err(anyhow!(
+                    "No events for the part {} in part_log (is it enabled?)",
+                    part_name
+                ));
+            }
+            let text = lineage.report(&part_name);
+            let title = format!("Lineage of {}", part_name);
+            cb_sink
+                .send(Box::new(move |app: &mut App| {
+                    app.add_layer(
+                        Dialog::around(TextView::new(text).scrollable().scroll_x(true))
+                            .title(title),
+                    );
+                }))
+                .map_err(|_| anyhow!("Cannot send message to UI"))?;
+        }
+        Event::TableGrowth(database, table, start, end, buckets) => {
+            let range_label = format!(
+                "[{} .. {}]",
+                start.to_editable_string(),
+                end.to_editable_string(),
+            );
+            let growth = clickhouse
+                .get_table_growth(
+                    &database,
+                    &table,
+                    start,
+                    end,
+                    buckets,
+                    selected_host.as_ref(),
+                )
+                .await?;
+            let report = growth.report();
+            let charts: Vec<(&str, String)> = vec![
+                (
+                    "Bytes added",
+                    crate::common::render_column_chart(&growth.series(|b| b.bytes_added), 6),
+                ),
+                (
+                    "Bytes removed",
+                    crate::common::render_column_chart(&growth.series(|b| b.bytes_removed), 6),
+                ),
+                (
+                    "Rows added",
+                    crate::common::render_column_chart(&growth.series(|b| b.rows_added), 6),
+                ),
+                (
+                    "Rows removed",
+                    crate::common::render_column_chart(&growth.series(|b| b.rows_removed), 6),
+                ),
+            ];
+            let title = format!("Growth of {}.{}", database, table);
+            cb_sink
+                .send(Box::new(move |app: &mut App| {
+                    let mut layout = LinearLayout::vertical()
+                        .child(TextView::new(title).center())
+                        .child(DummyView.fixed_height(1))
+                        .child(TextView::new(report));
+                    for (name, chart) in charts {
+                        layout.add_child(DummyView.fixed_height(1));
+                        layout.add_child(TextView::new(name));
+                        layout.add_child(TextView::new(chart));
+                    }
+                    layout.add_child(TextView::new(range_label).center());
+                    app.add_layer(Dialog::around(layout).scrollable());
+                }))
+                .map_err(|_| anyhow!("Cannot send message to UI"))?;
+        }
         Event::AsynchronousInserts(database, table) => {
             cb_sink
                 .send(Box::new(move |app: &mut App| {
@@ -1856,6 +2354,105 @@ fn format_progress(progress: &Progress) -> String {
     );
 }
 
+/// Side by side diff of the SQL texts (removed - red, added - green) with the
+/// number of changed lines.
+fn highlight_sql_texts_diff(
+    left: &str,
+    right: &str,
+) -> Result<(StyledString, StyledString, usize)> {
+    let rows = diff_texts(left, right);
+    let changed = rows.iter().filter(|row| row.is_changed()).count();
+    let (left, right): (Vec<_>, Vec<_>) = rows.into_iter().map(|row| (row.left, row.right)).unzip();
+    let left = highlight_sql_diff(&left, Color::Red)?;
+    let right = highlight_sql_diff(&right, Color::Green)?;
+    Ok((left, right, changed))
+}
+
+// Source table -> MV -> (target) -> next MV, one row per node with the time of
+// the node and of everything below it, the slowest path is highlighted.
+fn format_views_chain(views: &[ViewRun], rows: &[ViewChainRow]) -> StyledString {
+    let fmt_bytes = SizeFormatter::new()
+        .with_base(Base::Base2)
+        .with_style(Style::Abbreviated);
+    let format_ms = |ms: u64| format!("{}ms", ms);
+    let labels: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            let indent = "  ".repeat(row.depth);
+            match &row.node {
+                ViewChainNode::Table(table) if table.is_empty() => {
+                    format!("{}<unknown source>", indent)
+                }
+                ViewChainNode::Table(table) => format!("{}{}", indent, table),
+                ViewChainNode::View(i) => {
+                    format!("{}{} → {}", indent, views[*i].view_name, views[*i].target)
+                }
+            }
+        })
+        .collect();
+    let width = labels.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+
+    let mut text = StyledString::styled(
+        format!(
+            "{:width$} {:>10} {:>10} {:>10} {:>10} {}",
+            "View",
+            "Duration",
+            "Total",
+            "Written",
+            "Bytes",
+            "Status",
+            width = width
+        ),
+        TextStyle::new().add_modifier(Modifier::BOLD),
+    );
+    for (row, label) in rows.iter().zip(labels) {
+        let view = match row.node {
+            ViewChainNode::View(i) => Some(&views[i]),
+            ViewChainNode::Table(_) => None,
+        };
+        let line = match view {
+            Some(view) => format!(
+                "{:width$} {:>10} {:>10} {:>10} {:>10} {}",
+                label,
+                format_ms(view.duration_ms),
+                format_ms(row.total_ms),
+                format_rows(view.written_rows),
+                fmt_bytes.format(view.written_bytes as i64),
+                view.status,
+                width = width
+            ),
+            None => format!(
+                "{:width$} {:>10} {:>10}",
+                label,
+                "",
+                format_ms(row.total_ms),
+                width = width
+            ),
+        };
+        let mut style = TextStyle::new();
+        if row.slowest {
+            style = style.fg(Color::Yellow).add_modifier(Modifier::BOLD);
+        }
+        if view.is_some_and(ViewRun::is_failed) {
+            style = style.fg(Color::Red);
+        }
+        text.append_plain("\n");
+        text.append_styled(line, style);
+
+        if let Some(view) = view
+            && !view.exception.is_empty()
+        {
+            let exception = view.exception.lines().next().unwrap_or_default();
+            text.append_plain("\n");
+            text.append_styled(
+                format!("{}  {}", "  ".repeat(row.depth), exception),
+                TextStyle::new().fg(Color::Red),
+            );
+        }
+    }
+    text
+}
+
 fn format_rows(rows: u64) -> String {
     const SCALE: &[(f64, &str)] = &[(1e12, "T"), (1e9, "B"), (1e6, "M"), (1e3, "K")];
     for &(factor, suffix) in SCALE {
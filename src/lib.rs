@@ -6,6 +6,7 @@ pub mod tui;
 mod utils;
 // pub for integration tests (tests/)
 pub use tui::views::providers::query_patterns::query_patterns_sql;
+pub use tui::views::providers::query_patterns_compare::query_patterns_compare_sql;
 
 mod bin;
 pub use bin::chdig_build_app;
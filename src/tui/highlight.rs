@@ -4,6 +4,7 @@ use syntect::highlighting::{FontStyle, ThemeSet};
 use syntect::parsing::SyntaxSet;
 
 use super::style::{Color, Modifier, Style, StyledString};
+use crate::common::DiffSegment;
 
 fn convert_style(style: syntect::highlighting::Style) -> Style {
     let fg = style.foreground;
@@ -44,3 +45,61 @@ pub fn highlight_sql(text: &str) -> Result<StyledString> {
     }
     Ok(result)
 }
+
+/// SQL syntax highlighting of one side of a diff (see common::diff_texts()):
+/// the changed parts get the `changed` background, the missing lines are
+/// empty (to keep both sides aligned).
+pub fn highlight_sql_diff(
+    lines: &[Option<Vec<DiffSegment>>],
+    changed: Color,
+) -> Result<StyledString> {
+    let syntax_set = SyntaxSet::load_defaults_newlines();
+    let ts = ThemeSet::load_defaults();
+    let mut highlighter = HighlightLines::new(
+        syntax_set
+            .find_syntax_by_token("sql")
+            .context("Cannot load SQL syntax")?,
+        &ts.themes["base16-ocean.dark"],
+    );
+
+    let mut result = StyledString::new();
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            result.append_plain("\n");
+        }
+        let Some(segments) = line else {
+            continue;
+        };
+        let text = segments.iter().map(|(s, _)| s.as_str()).collect::<String>() + "\n";
+        // (end offset, changed) of the segments
+        let mut bounds = Vec::with_capacity(segments.len());
+        let mut end = 0;
+        for (s, is_changed) in segments {
+            end += s.len();
+            bounds.push((end, *is_changed));
+        }
+
+        let regions = highlighter
+            .highlight_line(&text, &syntax_set)
+            .context("Cannot highlight query")?;
+        let (mut offset, mut segment) = (0, 0);
+        for (style, mut part) in regions {
+            let style = convert_style(style);
+            while !part.is_empty() {
+                // Trailing newline is out of the segments
+                let Some(&(segment_end, is_changed)) = bounds.get(segment) else {
+                    break;
+                };
+                let (head, tail) = part.split_at(part.len().min(segment_end - offset));
+                let style = if is_changed { style.bg(changed) } else { style };
+                result.append_styled(head, style);
+                offset += head.len();
+                part = tail;
+                if offset == segment_end {
+                    segment += 1;
+                }
+            }
+        }
+    }
+    Ok(result)
+}
@@ -38,7 +38,7 @@ pub use dialog::Dialog;
 pub use edit::EditView;
 pub use event::{Callback, Event, EventResult, Key, MouseButton, MouseEvent};
 pub use fuzzy::{fuzzy_actions, fuzzy_select_strings};
-pub use highlight::highlight_sql;
+pub use highlight::{highlight_sql, highlight_sql_diff};
 pub use linear::LinearLayout;
 pub use mux::Mux;
 pub use navigation::Navigation;
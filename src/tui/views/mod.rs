@@ -1,6 +1,8 @@
 pub mod flamelens_view;
 pub mod log_store;
 pub mod log_view;
+pub mod pipeline_graph_view;
+pub mod plan_tree_view;
 pub mod providers;
 pub mod queries_view;
 pub mod query_view;
@@ -10,6 +12,7 @@ pub mod sql_query_view;
 pub mod summary_view;
 pub mod table_view;
 pub mod text_log_view;
+pub mod trace_spans_view;
 
 pub use log_store::{LogEntry, LogStore};
 pub use search_history::SearchHistory;
@@ -0,0 +1,710 @@
+use std::collections::{HashMap, HashSet};
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::{Position, Rect};
+
+use crate::common::{PipelineGraph, ProcessorProfile};
+use crate::tui::component::{Canvas, Component};
+use crate::tui::event::{Event, EventResult, Key, MouseButton, MouseEvent};
+use crate::tui::scroll::keep_row_visible;
+use crate::tui::style::{Color, Modifier, Style, highlight, highlight_inactive, print_str};
+
+// Columns between two layers, edges are routed in the middle of them
+const LAYER_GAP: u16 = 10;
+
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+fn junction(mask: u8) -> &'static str {
+    match mask {
+        0 => " ",
+        1..=3 => "│",
+        4 | 8 | 12 => "─",
+        10 => "┌",
+        6 => "┐",
+        9 => "└",
+        5 => "┘",
+        11 => "├",
+        7 => "┤",
+        14 => "┬",
+        13 => "┴",
+        _ => "┼",
+    }
+}
+
+fn format_us(us: u64) -> String {
+    let s = us as f64 / 1e6;
+    if s < 1. {
+        format!("{:.0}ms", s * 1e3)
+    } else if s < 60. {
+        format!("{:.2}s", s)
+    } else {
+        format!("{:.1}m", s / 60.)
+    }
+}
+
+/// Edges of the scene as a grid of line directions, so that crossing and
+/// forking edges are drawn with the proper box-drawing characters.
+struct Lines {
+    width: usize,
+    masks: Vec<u8>,
+}
+
+impl Lines {
+    fn new(width: u16, height: u16) -> Self {
+        Self {
+            width: width as usize,
+            masks: vec![0; width as usize * height as usize],
+        }
+    }
+
+    fn set(&mut self, x: u16, y: u16, direction: u8) {
+        if let Some(mask) = self.masks.get_mut(y as usize * self.width + x as usize) {
+            *mask |= direction;
+        }
+    }
+
+    /// Straight (horizontal or vertical) line between two cells.
+    fn connect(&mut self, from: (u16, u16), to: (u16, u16)) {
+        let (x0, x1) = (from.0.min(to.0), from.0.max(to.0));
+        let (y0, y1) = (from.1.min(to.1), from.1.max(to.1));
+        if from.1 == to.1 {
+            for x in x0..x1 {
+                self.set(x, y0, RIGHT);
+                self.set(x + 1, y0, LEFT);
+            }
+        } else {
+            for y in y0..y1 {
+                self.set(x0, y, DOWN);
+                self.set(x0, y + 1, UP);
+            }
+        }
+    }
+}
+
+/// Box with the text lines at (x, y) (can be partially outside of `clip`).
+fn draw_box(
+    buf: &mut Buffer,
+    clip: Rect,
+    (x, y): (i32, i32),
+    width: u16,
+    lines: &[(String, Style)],
+    border: Style,
+) {
+    let mut put = |cx: i32, cy: i32, symbol: &str, style: Style| {
+        if cx < clip.left() as i32
+            || cy < clip.top() as i32
+            || cx >= clip.right() as i32
+            || cy >= clip.bottom() as i32
+        {
+            return;
+        }
+        if let Some(cell) = buf.cell_mut((cx as u16, cy as u16)) {
+            cell.set_symbol(symbol);
+            cell.set_style(style);
+        }
+    };
+
+    let (right, bottom) = (x + width as i32 - 1, y + lines.len() as i32 + 1);
+    for cx in x + 1..right {
+        put(cx, y, "─", border);
+        put(cx, bottom, "─", border);
+    }
+    put(x, y, "┌", border);
+    put(right, y, "┐", border);
+    put(x, bottom, "└", border);
+    put(right, bottom, "┘", border);
+    for (i, (text, style)) in lines.iter().enumerate() {
+        let cy = y + 1 + i as i32;
+        put(x, cy, "│", border);
+        put(right, cy, "│", border);
+        let mut chars = text.chars();
+        for cx in x + 1..right {
+            // One column of padding on both sides
+            let c = if cx == x + 1 || cx == right - 1 {
+                None
+            } else {
+                chars.next()
+            };
+            let mut tmp = [0; 4];
+            let symbol = match c {
+                Some(c) => &*c.encode_utf8(&mut tmp),
+                None => " ",
+            };
+            put(cx, cy, symbol, *style);
+        }
+    }
+}
+
+/// Layered (left to right, like rankdir=LR) rendering of the graph.
+struct Scene {
+    buf: Buffer,
+    /// Box of every node.
+    boxes: Vec<Rect>,
+    /// Nodes of every layer, from top to bottom.
+    layers: Vec<Vec<usize>>,
+}
+
+impl Scene {
+    fn new(graph: &PipelineGraph, texts: &[Vec<(String, Style)>]) -> Self {
+        let n = graph.nodes.len();
+        let box_height = texts.first().map_or(0, |t| t.len() as u16) + 2;
+        let box_width = |i: usize| {
+            texts[i]
+                .iter()
+                .map(|(t, _)| t.chars().count())
+                .max()
+                .unwrap_or(0) as u16
+                + 4
+        };
+
+        // Layer is the longest path from the sources
+        let mut predecessors = vec![Vec::new(); n];
+        for edge in &graph.edges {
+            predecessors[edge.to].push(edge.from);
+        }
+        let order = graph.topological_order();
+        let mut layer_of = vec![usize::MAX; n];
+        for &i in &order {
+            layer_of[i] = predecessors[i]
+                .iter()
+                .filter(|&&p| layer_of[p] != usize::MAX)
+                .map(|&p| layer_of[p] + 1)
+                .max()
+                .unwrap_or(0);
+        }
+        let layers_count = layer_of.iter().max().map_or(0, |l| l + 1);
+
+        // Items of the layers: Some(node), or None for an edge that spans
+        // several layers and passes through this one
+        let mut items: Vec<Vec<Option<usize>>> = vec![Vec::new(); layers_count];
+        let mut item_of = vec![0; n];
+        for &i in &order {
+            item_of[i] = items[layer_of[i]].len();
+            items[layer_of[i]].push(Some(i));
+        }
+        // (layer, item, item in the next layer, "× N" label)
+        let mut segments: Vec<(usize, usize, usize, Option<usize>)> = Vec::new();
+        for edge in &graph.edges {
+            let (from_layer, to_layer) = (layer_of[edge.from], layer_of[edge.to]);
+            // Back edges are not possible in a pipeline
+            if to_layer <= from_layer {
+                continue;
+            }
+            let mut item = item_of[edge.from];
+            let mut label = Some(edge.count).filter(|&c| c > 1);
+            let passing_layers = items.iter_mut().enumerate().take(to_layer);
+            for (layer, layer_items) in passing_layers.skip(from_layer + 1) {
+                let passing = layer_items.len();
+                layer_items.push(None);
+                segments.push((layer - 1, item, passing, label.take()));
+                item = passing;
+            }
+            segments.push((to_layer - 1, item, item_of[edge.to], label));
+        }
+
+        // Order the items by the mean position of their inputs (one
+        // barycenter sweep), this removes most of the crossings
+        let mut positions: Vec<Vec<usize>> = items.iter().map(|l| (0..l.len()).collect()).collect();
+        for layer in 1..layers_count {
+            let mut inputs = vec![(0.0, 0); items[layer].len()];
+            for &(l, from, to, _) in &segments {
+                if l == layer - 1 {
+                    inputs[to].0 += positions[layer - 1][from] as f64;
+                    inputs[to].1 += 1;
+                }
+            }
+            let mut sorted: Vec<(f64, usize)> = inputs
+                .iter()
+                .enumerate()
+                .map(|(i, &(sum, count))| {
+                    if count == 0 {
+                        (i as f64, i)
+                    } else {
+                        (sum / count as f64, i)
+                    }
+                })
+                .collect();
+            sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+            for (position, &(_, i)) in sorted.iter().enumerate() {
+                positions[layer][i] = position;
+            }
+        }
+
+        // Coordinates
+        let mut column_x = Vec::with_capacity(layers_count);
+        let mut column_width = Vec::with_capacity(layers_count);
+        let mut x = 0;
+        for layer in &items {
+            let width = layer
+                .iter()
+                .flatten()
+                .map(|&i| box_width(i))
+                .max()
+                .unwrap_or(1);
+            column_x.push(x);
+            column_width.push(width);
+            x += width + LAYER_GAP;
+        }
+        let width = x.saturating_sub(LAYER_GAP).max(1);
+
+        let mut item_y: Vec<Vec<u16>> = items.iter().map(|l| vec![0; l.len()]).collect();
+        let mut height = 1;
+        for (layer, layer_items) in items.iter().enumerate() {
+            let mut by_position: Vec<usize> = (0..layer_items.len()).collect();
+            by_position.sort_by_key(|&i| positions[layer][i]);
+            let mut y = 0;
+            for i in by_position {
+                item_y[layer][i] = y;
+                y += if layer_items[i].is_some() {
+                    box_height
+                } else {
+                    1
+                } + 1;
+            }
+            height = height.max(y);
+        }
+
+        let mut boxes = vec![Rect::default(); n];
+        let mut layers = vec![Vec::new(); layers_count];
+        for (layer, layer_items) in items.iter().enumerate() {
+            for (i, item) in layer_items.iter().enumerate() {
+                if let Some(node) = item {
+                    boxes[*node] = Rect::new(
+                        column_x[layer],
+                        item_y[layer][i],
+                        box_width(*node),
+                        box_height,
+                    );
+                    layers[layer].push(*node);
+                }
+            }
+            layers[layer].sort_by_key(|&node| boxes[node].y);
+        }
+
+        // Edges
+        let mut lines = Lines::new(width, height);
+        let mut arrows = Vec::new();
+        let mut labels = Vec::new();
+        for (layer, layer_items) in items.iter().enumerate() {
+            for (i, item) in layer_items.iter().enumerate() {
+                if item.is_none() {
+                    let y = item_y[layer][i];
+                    let end = column_x[layer] + column_width[layer] - 1;
+                    lines.connect((column_x[layer], y), (end, y));
+                }
+            }
+        }
+        for &(layer, from, to, label) in &segments {
+            let (out_x, from_y) = match items[layer][from] {
+                Some(node) => (boxes[node].right(), boxes[node].y + 1),
+                None => (
+                    column_x[layer] + column_width[layer] - 1,
+                    item_y[layer][from],
+                ),
+            };
+            let (in_x, to_y) = match items[layer + 1][to] {
+                Some(node) => {
+                    arrows.push((boxes[node].x - 1, boxes[node].y + 1));
+                    (boxes[node].x - 1, boxes[node].y + 1)
+                }
+                None => (column_x[layer + 1], item_y[layer + 1][to]),
+            };
+            let channel_x = column_x[layer] + column_width[layer] + LAYER_GAP / 2;
+            lines.connect((out_x, from_y), (channel_x, from_y));
+            lines.connect((channel_x, from_y), (channel_x, to_y));
+            lines.connect((channel_x, to_y), (in_x, to_y));
+            if let Some(count) = label {
+                labels.push((out_x + 1, from_y, format!("×{}", count)));
+            }
+        }
+
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        for y in 0..height {
+            for x in 0..width {
+                let mask = lines.masks[y as usize * width as usize + x as usize];
+                if mask != 0
+                    && let Some(cell) = buf.cell_mut((x, y))
+                {
+                    cell.set_symbol(junction(mask));
+                }
+            }
+        }
+        for (x, y) in arrows {
+            if let Some(cell) = buf.cell_mut((x, y)) {
+                cell.set_symbol("▶");
+            }
+        }
+        for (x, y, label) in labels {
+            print_str(&mut buf, x, y, area, &label, Style::new().fg(Color::Yellow));
+        }
+        for (node, rect) in boxes.iter().enumerate() {
+            draw_box(
+                &mut buf,
+                area,
+                (rect.x as i32, rect.y as i32),
+                rect.width,
+                &texts[node],
+                Style::default(),
+            );
+        }
+
+        Self { buf, boxes, layers }
+    }
+
+    fn layer_of(&self, node: usize) -> Option<(usize, usize)> {
+        self.layers.iter().enumerate().find_map(|(layer, nodes)| {
+            nodes
+                .iter()
+                .position(|&n| n == node)
+                .map(|position| (layer, position))
+        })
+    }
+}
+
+/// `EXPLAIN PIPELINE graph=1` rendered in the terminal: processors as boxes,
+/// parallel streams collapsed into one "× N" box (Enter expands/collapses
+/// the selected one, `a` - all of them), optionally annotated with the
+/// processors_profile_log of the finished query.
+pub struct PipelineGraphView {
+    graph: PipelineGraph,
+    groups: Vec<usize>,
+    profile: HashMap<String, ProcessorProfile>,
+    expanded: HashSet<usize>,
+
+    display: PipelineGraph,
+    // Group of every node of the displayed graph
+    display_groups: Vec<usize>,
+    scene: Scene,
+    selected: usize,
+    // Scroll to the selected node on the next draw
+    follow: bool,
+    offset: (u16, u16),
+    area: Rect,
+}
+
+impl PipelineGraphView {
+    pub fn new(graph: PipelineGraph, profile: HashMap<String, ProcessorProfile>) -> Self {
+        let groups = graph.parallel_groups();
+        let mut view = Self {
+            graph,
+            groups,
+            profile,
+            expanded: HashSet::new(),
+            display: PipelineGraph::default(),
+            display_groups: Vec::new(),
+            scene: Scene::new(&PipelineGraph::default(), &[]),
+            selected: 0,
+            follow: true,
+            offset: (0, 0),
+            area: Rect::default(),
+        };
+        view.rebuild(None);
+        view
+    }
+
+    fn node_text(&self, node: usize) -> Vec<(String, Style)> {
+        let node = &self.display.nodes[node];
+        let mut lines = Vec::new();
+        let name = if node.count > 1 {
+            format!("{} × {}", node.name, node.count)
+        } else {
+            node.name.clone()
+        };
+        lines.push((name, Style::new().add_modifier(Modifier::BOLD)));
+        lines.push((node.step.clone(), Style::new().fg(Color::DarkGray)));
+        if !self.profile.is_empty() {
+            // Processors are matched by name, so the totals are spread
+            // evenly between all processors with that name.
+            let stats = match self.profile.get(&node.name) {
+                Some(p) if p.processors > 0 => {
+                    let share = |us: u64| us * node.count as u64 / p.processors;
+                    format!(
+                        "{} in:{} out:{}",
+                        format_us(share(p.elapsed_us)),
+                        format_us(share(p.input_wait_us)),
+                        format_us(share(p.output_wait_us)),
+                    )
+                }
+                _ => "-".to_string(),
+            };
+            lines.push((stats, Style::new().fg(Color::Cyan)));
+        }
+        lines
+    }
+
+    /// Rebuilds the displayed graph after the expanded groups had been
+    /// changed, `select_group` - the group to select.
+    fn rebuild(&mut self, select_group: Option<usize>) {
+        let groups_count = self.groups.iter().max().map_or(0, |g| g + 1);
+        let keys: Vec<usize> = self
+            .groups
+            .iter()
+            .enumerate()
+            .map(|(i, g)| {
+                if self.expanded.contains(g) {
+                    groups_count + i
+                } else {
+                    *g
+                }
+            })
+            .collect();
+        let (display, display_keys) = self.graph.merge_by(&keys);
+        self.display = display;
+        self.display_groups = display_keys
+            .iter()
+            .map(|&key| {
+                if key >= groups_count {
+                    self.groups[key - groups_count]
+                } else {
+                    key
+                }
+            })
+            .collect();
+
+        let texts: Vec<_> = (0..self.display.nodes.len())
+            .map(|i| self.node_text(i))
+            .collect();
+        self.scene = Scene::new(&self.display, &texts);
+
+        self.selected = select_group
+            .and_then(|g| self.display_groups.iter().position(|&dg| dg == g))
+            .unwrap_or(0);
+        self.follow = true;
+    }
+
+    fn toggle_selected(&mut self) -> EventResult {
+        let Some(&group) = self.display_groups.get(self.selected) else {
+            return EventResult::Ignored;
+        };
+        if !self.expanded.remove(&group) {
+            // Nothing to expand
+            if self.groups.iter().filter(|&&g| g == group).count() < 2 {
+                return EventResult::consumed();
+            }
+            self.expanded.insert(group);
+        }
+        self.rebuild(Some(group));
+        EventResult::consumed()
+    }
+
+    fn toggle_all(&mut self) -> EventResult {
+        let group = self.display_groups.get(self.selected).copied();
+        if self.expanded.is_empty() {
+            self.expanded = self.groups.iter().copied().collect();
+        } else {
+            self.expanded.clear();
+        }
+        self.rebuild(group);
+        EventResult::consumed()
+    }
+
+    fn select(&mut self, node: usize) -> EventResult {
+        self.selected = node;
+        self.follow = true;
+        EventResult::consumed()
+    }
+
+    fn select_in_layer(&mut self, delta: isize) -> EventResult {
+        let Some((layer, position)) = self.scene.layer_of(self.selected) else {
+            return EventResult::Ignored;
+        };
+        let node = position
+            .checked_add_signed(delta)
+            .and_then(|position| self.scene.layers[layer].get(position))
+            .copied();
+        match node {
+            Some(node) => self.select(node),
+            None => EventResult::Ignored,
+        }
+    }
+
+    fn select_layer(&mut self, delta: isize) -> EventResult {
+        let Some((layer, _)) = self.scene.layer_of(self.selected) else {
+            return EventResult::Ignored;
+        };
+        let Some(layer) = layer
+            .checked_add_signed(delta)
+            .filter(|&l| l < self.scene.layers.len())
+        else {
+            return EventResult::Ignored;
+        };
+        let y = self.scene.boxes[self.selected].y;
+        let nearest = self.scene.layers[layer]
+            .iter()
+            .min_by_key(|&&node| self.scene.boxes[node].y.abs_diff(y))
+            .copied();
+        match nearest {
+            Some(node) => self.select(node),
+            None => EventResult::Ignored,
+        }
+    }
+
+    /// First node of the first (sources) or the last layer.
+    fn select_edge_layer(&mut self, last: bool) -> EventResult {
+        let layer = if last {
+            self.scene.layers.last()
+        } else {
+            self.scene.layers.first()
+        };
+        match layer.and_then(|nodes| nodes.first()).copied() {
+            Some(node) => self.select(node),
+            None => EventResult::Ignored,
+        }
+    }
+
+    fn scroll(&mut self, dy: i32) -> EventResult {
+        let max = self
+            .scene
+            .buf
+            .area
+            .height
+            .saturating_sub(self.area.height.saturating_sub(1));
+        self.offset.1 = (self.offset.1 as i32 + dy).clamp(0, max as i32) as u16;
+        self.follow = false;
+        EventResult::consumed()
+    }
+
+    fn status(&self) -> String {
+        let Some(node) = self.display.nodes.get(self.selected) else {
+            return "Empty pipeline".to_string();
+        };
+        let mut status = node.name.clone();
+        if node.count > 1 {
+            status.push_str(&format!(" × {}", node.count));
+        }
+        if !node.step.is_empty() {
+            status.push_str(&format!(" ({})", node.step));
+        }
+        if let Some(p) = self.profile.get(&node.name) {
+            status.push_str(&format!(
+                " | {} processors: elapsed {}, input wait {}, output wait {}",
+                p.processors,
+                format_us(p.elapsed_us),
+                format_us(p.input_wait_us),
+                format_us(p.output_wait_us),
+            ));
+        }
+        status.push_str(" | <Enter> - expand/collapse, a - all");
+        status
+    }
+}
+
+impl Component for PipelineGraphView {
+    fn draw(&mut self, canvas: &mut Canvas<'_>, area: Rect, focused: bool) {
+        self.area = area;
+        // The last line is for the status
+        let view = Rect {
+            height: area.height.saturating_sub(1),
+            ..area
+        };
+        let scene = self.scene.buf.area;
+
+        if self.follow
+            && let Some(rect) = self.scene.boxes.get(self.selected)
+        {
+            let keep_visible = |offset: u16, start: u16, len: u16, viewport: u16| {
+                let end = (start + len).saturating_sub(1) as usize;
+                let offset = keep_row_visible(offset as usize, end, viewport as usize);
+                keep_row_visible(offset, start as usize, viewport as usize) as u16
+            };
+            self.offset = (
+                keep_visible(self.offset.0, rect.x, rect.width, view.width),
+                keep_visible(self.offset.1, rect.y, rect.height, view.height),
+            );
+            self.follow = false;
+        }
+        self.offset = (
+            self.offset.0.min(scene.width.saturating_sub(view.width)),
+            self.offset.1.min(scene.height.saturating_sub(view.height)),
+        );
+
+        for y in 0..view.height {
+            for x in 0..view.width {
+                let src = (x + self.offset.0, y + self.offset.1);
+                if src.0 < scene.width
+                    && src.1 < scene.height
+                    && let Some(dst) = canvas.buf.cell_mut((view.x + x, view.y + y))
+                {
+                    *dst = self.scene.buf[src].clone();
+                }
+            }
+        }
+
+        if let Some(rect) = self.scene.boxes.get(self.selected) {
+            let border = if focused {
+                highlight()
+            } else {
+                highlight_inactive()
+            };
+            let text = self.node_text(self.selected);
+            draw_box(
+                canvas.buf,
+                view,
+                (
+                    view.x as i32 + rect.x as i32 - self.offset.0 as i32,
+                    view.y as i32 + rect.y as i32 - self.offset.1 as i32,
+                ),
+                rect.width,
+                &text,
+                border,
+            );
+        }
+
+        if area.height > 1 {
+            print_str(
+                canvas.buf,
+                area.x,
+                area.bottom() - 1,
+                area,
+                &self.status(),
+                Style::new().add_modifier(Modifier::REVERSED),
+            );
+        }
+    }
+
+    fn on_event(&mut self, event: &Event) -> EventResult {
+        let page = self.area.height.saturating_sub(1).max(1) as i32;
+        match event {
+            Event::Key(Key::Up) => self.select_in_layer(-1),
+            Event::Key(Key::Down) => self.select_in_layer(1),
+            Event::Key(Key::Left) => self.select_layer(-1),
+            Event::Key(Key::Right) => self.select_layer(1),
+            Event::Key(Key::PageUp) => self.scroll(-page),
+            Event::Key(Key::PageDown) => self.scroll(page),
+            Event::Key(Key::Home) => self.select_edge_layer(false),
+            Event::Key(Key::End) => self.select_edge_layer(true),
+            Event::Key(Key::Enter) | Event::Char(' ') => self.toggle_selected(),
+            Event::Char('a') => self.toggle_all(),
+            Event::Mouse {
+                event: MouseEvent::WheelUp,
+                ..
+            } => self.scroll(-3),
+            Event::Mouse {
+                event: MouseEvent::WheelDown,
+                ..
+            } => self.scroll(3),
+            Event::Mouse {
+                position,
+                event: MouseEvent::Press(MouseButton::Left),
+            } if self.area.contains(*position) => {
+                let position = Position::new(
+                    position.x - self.area.x + self.offset.0,
+                    position.y - self.area.y + self.offset.1,
+                );
+                match self.scene.boxes.iter().position(|b| b.contains(position)) {
+                    Some(node) => self.select(node),
+                    None => EventResult::Ignored,
+                }
+            }
+            _ => EventResult::Ignored,
+        }
+    }
+
+    fn take_focus(&mut self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,433 @@
+use ratatui::layout::Rect;
+
+use crate::common::{PlanIndex, PlanNode};
+use crate::tui::component::{Canvas, Component};
+use crate::tui::event::{Event, EventResult, Key, MouseButton, MouseEvent};
+use crate::tui::scroll::{draw_scrollbar_v, keep_row_visible};
+use crate::tui::style::{Color, Modifier, Style, highlight, highlight_inactive, print_str};
+
+/// Step of the plan, `PlanNode` without children.
+struct Item {
+    node: PlanNode,
+    depth: usize,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    expanded: bool,
+}
+
+fn granules_ratio(selected: u64, initial: u64) -> String {
+    if initial == 0 {
+        return format!("{}/{}", selected, initial);
+    }
+    format!(
+        "{}/{} ({:.0}%)",
+        selected,
+        initial,
+        selected as f64 / initial as f64 * 100.
+    )
+}
+
+fn index_lines(index: &PlanIndex, lines: &mut Vec<(String, Style)>) {
+    let mut title = index.index_type.clone();
+    if !index.name.is_empty() {
+        title.push_str(&format!(" {}", index.name));
+    }
+    lines.push((
+        format!("  {}", title),
+        Style::new().add_modifier(Modifier::BOLD),
+    ));
+    if !index.keys.is_empty() {
+        lines.push((format!("    Keys: {}", index.keys.join(", ")), Style::new()));
+    }
+    if !index.condition.is_empty() {
+        lines.push((format!("    Condition: {}", index.condition), Style::new()));
+    }
+    if let (Some(selected), Some(initial)) = (index.selected_parts, index.initial_parts) {
+        lines.push((
+            format!("    Parts: {}", granules_ratio(selected, initial)),
+            Style::new(),
+        ));
+    }
+    if let (Some(selected), Some(initial)) = (index.selected_granules, index.initial_granules) {
+        // Index that filters nothing
+        let style = if initial > 0 && selected == initial {
+            Style::new().fg(Color::Yellow)
+        } else {
+            Style::new()
+        };
+        lines.push((
+            format!("    Granules: {}", granules_ratio(selected, initial)),
+            style,
+        ));
+    }
+}
+
+/// Wraps the lines to the `width` (by chars, plans do not have wide ones).
+fn wrap(lines: Vec<(String, Style)>, width: usize) -> Vec<(String, Style)> {
+    let width = width.max(1);
+    let mut wrapped = Vec::new();
+    for (line, style) in lines {
+        let chars: Vec<char> = line.chars().collect();
+        if chars.is_empty() {
+            wrapped.push((line, style));
+            continue;
+        }
+        for chunk in chars.chunks(width) {
+            wrapped.push((chunk.iter().collect(), style));
+        }
+    }
+    wrapped
+}
+
+/// Tree of the `EXPLAIN PLAN json=1` steps with the details of the
+/// selected one on the right.
+pub struct PlanTreeView {
+    items: Vec<Item>,
+    /// Visible items (children of the collapsed ones are hidden).
+    rows: Vec<usize>,
+    selected: usize,
+    offset: usize,
+    details_offset: usize,
+    area: Rect,
+    tree_area: Rect,
+    details_area: Rect,
+}
+
+impl PlanTreeView {
+    pub fn new(plans: Vec<PlanNode>) -> Self {
+        let mut view = Self {
+            items: Vec::new(),
+            rows: Vec::new(),
+            selected: 0,
+            offset: 0,
+            details_offset: 0,
+            area: Rect::default(),
+            tree_area: Rect::default(),
+            details_area: Rect::default(),
+        };
+        for plan in plans {
+            view.add_item(plan, 0, None);
+        }
+        view.rebuild_rows();
+        view
+    }
+
+    fn add_item(&mut self, mut node: PlanNode, depth: usize, parent: Option<usize>) -> usize {
+        let children = std::mem::take(&mut node.children);
+        let id = self.items.len();
+        self.items.push(Item {
+            node,
+            depth,
+            parent,
+            children: Vec::new(),
+            expanded: true,
+        });
+        for child in children {
+            let child = self.add_item(child, depth + 1, Some(id));
+            self.items[id].children.push(child);
+        }
+        id
+    }
+
+    fn rebuild_rows(&mut self) {
+        let selected = self.rows.get(self.selected).copied();
+        self.rows.clear();
+        let mut stack: Vec<usize> = (0..self.items.len())
+            .rev()
+            .filter(|&i| self.items[i].parent.is_none())
+            .collect();
+        while let Some(i) = stack.pop() {
+            self.rows.push(i);
+            if self.items[i].expanded {
+                stack.extend(self.items[i].children.iter().rev());
+            }
+        }
+        self.selected = selected
+            .and_then(|item| self.rows.iter().position(|&i| i == item))
+            .unwrap_or(0);
+    }
+
+    fn row_text(&self, item: usize) -> (String, Style) {
+        let item = &self.items[item];
+        let marker = match (item.children.is_empty(), item.expanded) {
+            (true, _) => "  ",
+            (false, true) => "▾ ",
+            (false, false) => "▸ ",
+        };
+        let mut text = format!(
+            "{}{}{}",
+            "  ".repeat(item.depth),
+            marker,
+            item.node.node_type
+        );
+        if !item.node.description.is_empty() {
+            text.push_str(&format!(" {}", item.node.description));
+        }
+        if let Some((selected, initial)) = item.node.granules_selectivity() {
+            text.push_str(&format!(" [{}]", granules_ratio(selected, initial)));
+        }
+        let style = if item.node.has_poor_selectivity() {
+            Style::new().fg(Color::Red)
+        } else {
+            Style::new()
+        };
+        (text, style)
+    }
+
+    fn details(&self) -> Vec<(String, Style)> {
+        let Some(&item) = self.rows.get(self.selected) else {
+            return vec![("Empty plan".to_string(), Style::new())];
+        };
+        let node = &self.items[item].node;
+        let header = Style::new().fg(Color::Cyan).add_modifier(Modifier::BOLD);
+
+        let mut lines = vec![(
+            node.node_type.clone(),
+            Style::new().add_modifier(Modifier::BOLD),
+        )];
+        if !node.description.is_empty() {
+            lines.push((node.description.clone(), Style::new().fg(Color::DarkGray)));
+        }
+        if node.has_poor_selectivity() {
+            lines.push((
+                "Indexes filter out less than half of the granules".to_string(),
+                Style::new().fg(Color::Red),
+            ));
+        }
+        for (name, value) in &node.properties {
+            lines.push((format!("{}: {}", name, value), Style::new()));
+        }
+        if !node.indexes.is_empty() {
+            lines.push((String::new(), Style::new()));
+            lines.push(("Indexes".to_string(), header));
+            for index in &node.indexes {
+                index_lines(index, &mut lines);
+            }
+        }
+        if !node.actions.is_empty() {
+            lines.push((String::new(), Style::new()));
+            lines.push(("Actions".to_string(), header));
+            lines.extend(
+                node.actions
+                    .iter()
+                    .map(|action| (format!("  {}", action), Style::new())),
+            );
+        }
+        lines
+    }
+
+    fn select(&mut self, row: usize) -> EventResult {
+        let row = row.min(self.rows.len().saturating_sub(1));
+        if row != self.selected {
+            self.selected = row;
+            self.details_offset = 0;
+        }
+        EventResult::consumed()
+    }
+
+    fn select_relative(&mut self, delta: isize) -> EventResult {
+        self.select(self.selected.saturating_add_signed(delta))
+    }
+
+    fn set_expanded(&mut self, expanded: bool) -> EventResult {
+        let Some(&item) = self.rows.get(self.selected) else {
+            return EventResult::Ignored;
+        };
+        if self.items[item].children.is_empty() || self.items[item].expanded == expanded {
+            // Left on a leaf or a collapsed node goes to the parent, Right on
+            // an expanded one - to the first child
+            let next = if expanded {
+                self.items[item].children.first().copied()
+            } else {
+                self.items[item].parent
+            };
+            return match next.and_then(|next| self.rows.iter().position(|&i| i == next)) {
+                Some(row) => self.select(row),
+                None => EventResult::consumed(),
+            };
+        }
+        self.items[item].expanded = expanded;
+        self.rebuild_rows();
+        EventResult::consumed()
+    }
+
+    fn toggle_selected(&mut self) -> EventResult {
+        let Some(&item) = self.rows.get(self.selected) else {
+            return EventResult::Ignored;
+        };
+        let expanded = self.items[item].expanded;
+        self.set_expanded(!expanded)
+    }
+
+    fn toggle_all(&mut self) -> EventResult {
+        let expanded = !self
+            .items
+            .iter()
+            .all(|item| item.expanded || item.children.is_empty());
+        self.items
+            .iter_mut()
+            .for_each(|item| item.expanded = expanded);
+        self.rebuild_rows();
+        EventResult::consumed()
+    }
+
+    fn scroll_details(&mut self, delta: isize) -> EventResult {
+        self.details_offset = self.details_offset.saturating_add_signed(delta);
+        EventResult::consumed()
+    }
+}
+
+impl Component for PlanTreeView {
+    fn draw(&mut self, canvas: &mut Canvas<'_>, area: Rect, focused: bool) {
+        self.area = area;
+        // The last line is for the status
+        let height = area.height.saturating_sub(1);
+        let tree_width = (area.width / 2).max(area.width.min(30));
+        self.tree_area = Rect {
+            width: tree_width,
+            height,
+            ..area
+        };
+        self.details_area = Rect {
+            x: area.x + tree_width + 1,
+            width: area.width.saturating_sub(tree_width + 1),
+            height,
+            ..area
+        };
+
+        let viewport = height as usize;
+        self.offset = keep_row_visible(self.offset, self.selected, viewport);
+        for (y, &item) in self
+            .rows
+            .iter()
+            .skip(self.offset)
+            .take(viewport)
+            .enumerate()
+        {
+            let (text, mut style) = self.row_text(item);
+            if self.offset + y == self.selected {
+                style = if focused {
+                    highlight()
+                } else {
+                    highlight_inactive()
+                };
+            }
+            let text = format!("{:width$}", text, width = tree_width as usize);
+            print_str(
+                canvas.buf,
+                area.x,
+                area.y + y as u16,
+                self.tree_area,
+                &text,
+                style,
+            );
+        }
+        if self.rows.len() > viewport && tree_width > 0 {
+            draw_scrollbar_v(
+                canvas.buf,
+                area.x + tree_width - 1,
+                area.y,
+                self.rows.len(),
+                viewport,
+                self.offset,
+            );
+        }
+
+        for y in 0..height {
+            print_str(
+                canvas.buf,
+                area.x + tree_width,
+                area.y + y,
+                area,
+                "│",
+                Style::new().fg(Color::DarkGray),
+            );
+        }
+
+        let details = wrap(self.details(), self.details_area.width as usize);
+        self.details_offset = self
+            .details_offset
+            .min(details.len().saturating_sub(viewport));
+        for (y, (text, style)) in details
+            .iter()
+            .skip(self.details_offset)
+            .take(viewport)
+            .enumerate()
+        {
+            print_str(
+                canvas.buf,
+                self.details_area.x,
+                area.y + y as u16,
+                self.details_area,
+                text,
+                *style,
+            );
+        }
+
+        if area.height > 1 {
+            let status = format!(
+                "{:width$}",
+                "<Enter> - expand/collapse, a - all, [/] - scroll details",
+                width = area.width as usize
+            );
+            print_str(
+                canvas.buf,
+                area.x,
+                area.bottom() - 1,
+                area,
+                &status,
+                Style::new().add_modifier(Modifier::REVERSED),
+            );
+        }
+    }
+
+    fn on_event(&mut self, event: &Event) -> EventResult {
+        let page = self.tree_area.height.max(1) as isize;
+        match event {
+            Event::Key(Key::Up) => self.select_relative(-1),
+            Event::Key(Key::Down) => self.select_relative(1),
+            Event::Key(Key::PageUp) => self.select_relative(-page),
+            Event::Key(Key::PageDown) => self.select_relative(page),
+            Event::Key(Key::Home) => self.select(0),
+            Event::Key(Key::End) => self.select(usize::MAX),
+            Event::Key(Key::Left) => self.set_expanded(false),
+            Event::Key(Key::Right) => self.set_expanded(true),
+            Event::Key(Key::Enter) | Event::Char(' ') => self.toggle_selected(),
+            Event::Char('a') => self.toggle_all(),
+            Event::Char('[') => self.scroll_details(-page),
+            Event::Char(']') => self.scroll_details(page),
+            Event::Mouse {
+                position,
+                event: MouseEvent::WheelUp,
+            } if self.details_area.contains(*position) => self.scroll_details(-3),
+            Event::Mouse {
+                position,
+                event: MouseEvent::WheelDown,
+            } if self.details_area.contains(*position) => self.scroll_details(3),
+            Event::Mouse {
+                event: MouseEvent::WheelUp,
+                ..
+            } => self.select_relative(-3),
+            Event::Mouse {
+                event: MouseEvent::WheelDown,
+                ..
+            } => self.select_relative(3),
+            Event::Mouse {
+                position,
+                event: MouseEvent::Press(MouseButton::Left),
+            } if self.tree_area.contains(*position) => {
+                let row = self.offset + (position.y - self.tree_area.y) as usize;
+                if row < self.rows.len() {
+                    self.select(row)
+                } else {
+                    EventResult::Ignored
+                }
+            }
+            _ => EventResult::Ignored,
+        }
+    }
+
+    fn take_focus(&mut self) -> bool {
+        true
+    }
+}
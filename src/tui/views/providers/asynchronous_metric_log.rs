@@ -121,6 +121,8 @@ fn show_asynchronous_metric_log(app: &mut App, context: ContextArc) {
     )
     .unwrap_or_else(|_| panic!("Cannot create {}", view_name));
 
+    let descriptions = context.lock().unwrap().clickhouse.metric_descriptions();
+    view.get_inner_mut().set_descriptions("name", descriptions);
     view.get_inner_mut().set_on_submit(show_chart);
     view.get_inner_mut().set_title("Asynchronous metric log");
 
@@ -0,0 +1,242 @@
+use crate::{
+    interpreter::{ContextArc, options::ChDigViews},
+    tui::{
+        App, Dialog, Event, Nameable, Navigation, Resizable, Scrollable, TextView, ViewProvider,
+        actions::ActionDescription,
+        fuzzy_actions,
+        views::sql_query_view::{Row as QueryResultRow, SQLQueryView},
+    },
+};
+use std::collections::HashMap;
+
+const VIEW_NAME: &str = "failed_queries";
+
+// Both failure kinds: ExceptionBeforeStart (parsing, access, ...) does not
+// have a QueryFinish counterpart, ExceptionWhileProcessing replaces it.
+const FAILED_TYPES: &str = "type IN ('ExceptionBeforeStart', 'ExceptionWhileProcessing')";
+
+pub struct FailedQueriesViewProvider;
+
+impl ViewProvider for FailedQueriesViewProvider {
+    fn name(&self) -> &'static str {
+        "Failed queries"
+    }
+
+    fn view_type(&self) -> ChDigViews {
+        ChDigViews::FailedQueries
+    }
+
+    fn show(&self, app: &mut App, context: ContextArc, _instance: Option<&str>) {
+        show_failed_queries(app, context);
+    }
+}
+
+fn show_failed_queries(app: &mut App, context: ContextArc) {
+    if app.focus_name(VIEW_NAME) {
+        return;
+    }
+
+    let columns = vec![
+        "exception_code code",
+        "errorCodeToName(exception_code) name",
+        "toString(normalized_query_hash) hash",
+        "count() total",
+        "total bar",
+        "arrayStringConcat(groupUniqArray(10)(user), ', ') users",
+        "min(event_time) first_seen",
+        "max(event_time) last_seen",
+        "any(exception) exception",
+        "any(normalizeQuery(query)) normalized_query",
+    ];
+    let columns_to_compare = vec!["code", "hash"];
+
+    let ((start_sql, end_sql), limit, dbtable, clickhouse, selected_host) = {
+        let ctx = context.lock().unwrap();
+        (
+            ctx.view_interval_sql(VIEW_NAME),
+            ctx.view_limit(VIEW_NAME, ctx.options.clickhouse.limit),
+            ctx.clickhouse.get_log_table_name("query_log"),
+            ctx.clickhouse.clone(),
+            ctx.selected_host.clone(),
+        )
+    };
+
+    let query = format!(
+        r#"
+        WITH {start} AS start_, {end} AS end_
+        SELECT {columns}
+        FROM {dbtable}
+        WHERE
+            event_date BETWEEN toDate(start_) AND toDate(end_) AND
+            event_time BETWEEN toDateTime(start_) AND toDateTime(end_) AND
+            {failed_types}
+            {internal}
+            {host_filter}
+        GROUP BY exception_code, normalized_query_hash
+        ORDER BY total DESC
+        LIMIT {limit}
+        "#,
+        start = start_sql,
+        end = end_sql,
+        columns = columns.join(", "),
+        dbtable = dbtable,
+        failed_types = FAILED_TYPES,
+        internal = clickhouse.get_internal_filter_clause(),
+        host_filter = clickhouse.get_log_host_filter_clause(selected_host.as_ref()),
+        limit = limit,
+    );
+
+    let mut view = SQLQueryView::new(
+        context.clone(),
+        VIEW_NAME,
+        "total",
+        columns,
+        columns_to_compare,
+        vec!["exception", "normalized_query"],
+        query,
+    )
+    .unwrap_or_else(|_| panic!("Cannot create {}", VIEW_NAME));
+    view.get_inner_mut()
+        .set_on_submit(show_failed_query_actions);
+    view.get_inner_mut().set_title("Failed queries");
+    view.get_inner_mut().set_bar_columns(vec![("bar", "total")]);
+
+    app.present_view(VIEW_NAME, view.with_name(VIEW_NAME).full_screen());
+}
+
+/// (exception_code, normalized_query_hash) of the row, validated, since both
+/// are interpolated into the drill-down queries.
+fn row_group(columns: &[&'static str], row: &QueryResultRow) -> Option<(i32, u64, String)> {
+    let mut map = HashMap::new();
+    columns.iter().zip(row.0.iter()).for_each(|(c, r)| {
+        map.insert(*c, r.to_string());
+    });
+    let code = map.get("code")?.parse::<i32>().ok()?;
+    let hash = map.get("hash")?.parse::<u64>().ok()?;
+    let name = map.get("name").cloned().unwrap_or_default();
+    Some((code, hash, name))
+}
+
+fn show_failed_query_actions(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
+    let Some((code, hash, name)) = row_group(&columns, &row) else {
+        return;
+    };
+
+    let actions = vec![
+        ActionDescription {
+            text: "Show failed queries",
+            event: Event::Unknown(vec![]),
+        },
+        ActionDescription {
+            text: "Show failures chart",
+            event: Event::Unknown(vec![]),
+        },
+    ];
+
+    fuzzy_actions(app, actions, move |app, selected| match selected.as_str() {
+        "Show failed queries" => show_failed_queries_for_group(app, code, hash, &name),
+        "Show failures chart" => {
+            super::show_log_chart(
+                app,
+                VIEW_NAME,
+                "query_log",
+                "count()".to_string(),
+                Some(format!(
+                    "{} AND exception_code = {} AND normalized_query_hash = {}",
+                    FAILED_TYPES, code, hash
+                )),
+                format!("Failures: {} ({})", name, hash),
+            );
+        }
+        _ => {}
+    });
+}
+
+fn show_failed_queries_for_group(app: &mut App, code: i32, hash: u64, name: &str) {
+    let context = app.user_data::<ContextArc>().unwrap().clone();
+
+    let columns = vec![
+        "event_time",
+        "query_id",
+        "user",
+        "query_duration_ms/1e3 elapsed",
+        "exception",
+        "stack_trace _stack_trace",
+        "query",
+    ];
+
+    let (limit, dbtable, clickhouse, selected_host) = {
+        let ctx = context.lock().unwrap();
+        (
+            ctx.view_limit(VIEW_NAME, ctx.options.clickhouse.limit),
+            ctx.clickhouse.get_log_table_name("query_log"),
+            ctx.clickhouse.clone(),
+            ctx.selected_host.clone(),
+        )
+    };
+
+    let (with_prelude, mut where_clauses) = super::log_time_window(&context, VIEW_NAME);
+    where_clauses.push(FAILED_TYPES.to_string());
+    where_clauses.push(format!("exception_code = {}", code));
+    where_clauses.push(format!("normalized_query_hash = {}", hash));
+    super::push_host_filter(
+        &mut where_clauses,
+        &clickhouse,
+        selected_host.as_ref(),
+        true,
+    );
+
+    let query = format!(
+        r#"
+        {with_prelude}
+        SELECT {columns}
+        FROM {dbtable}
+        WHERE {where_clause} {internal}
+        ORDER BY event_time DESC
+        LIMIT {limit}
+        "#,
+        with_prelude = with_prelude,
+        columns = columns.join(", "),
+        dbtable = dbtable,
+        where_clause = where_clauses.join(" AND "),
+        internal = clickhouse.get_internal_filter_clause(),
+        limit = limit,
+    );
+
+    let title = format!("Failed queries: {} ({})", name, hash);
+    super::present_query_table(
+        app,
+        context,
+        super::QueryTableSpec {
+            view_name: format!("{}_{}_{}", VIEW_NAME, code, hash),
+            title: title.clone(),
+            dialog_title: title,
+            sort_by: "event_time",
+            columns,
+            columns_to_compare: vec!["query_id"],
+            wide_columns: vec!["exception", "query"],
+            query,
+        },
+        show_failed_query,
+        super::Presentation::Dialog,
+    );
+}
+
+fn show_failed_query(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
+    let mut map = HashMap::new();
+    columns.iter().zip(row.0.iter()).for_each(|(c, r)| {
+        map.insert(*c, r.to_string());
+    });
+    let get = |name: &str| map.get(name).cloned().unwrap_or_default();
+
+    let text = format!(
+        "{}\n\nStack trace:\n{}\n\nQuery:\n{}",
+        get("exception"),
+        get("_stack_trace"),
+        get("query"),
+    );
+    app.add_layer(
+        Dialog::around(TextView::new(text).scrollable())
+            .title(format!("Failed query: {}", get("query_id"))),
+    );
+}
@@ -0,0 +1,252 @@
+use crate::{
+    interpreter::{ContextArc, options::ChDigViews},
+    tui::{
+        App, Event, Nameable, Navigation, Resizable, ViewProvider,
+        actions::ActionDescription,
+        fuzzy_actions,
+        style::Color,
+        views::queries_view::{QueriesView, Type as QueriesType},
+        views::sql_query_view::{Row as QueryResultRow, SQLQueryView, Unit},
+    },
+};
+use std::collections::HashMap;
+
+const VIEW_NAME: &str = "inserts";
+// Widget name of the drill-down queries view (see RESERVED_VIEW_NAMES).
+const INSERT_QUERIES_VIEW_NAME: &str = "inserts_queries";
+// Tables with fewer rows per insert are flagged (can be overridden with
+// min_rows_per_insert in the view config), 1000 is the minimum recommended
+// batch size.
+const DEFAULT_MIN_ROWS_PER_INSERT: u64 = 1000;
+
+pub struct InsertsViewProvider;
+
+impl ViewProvider for InsertsViewProvider {
+    fn name(&self) -> &'static str {
+        "Inserts"
+    }
+
+    fn view_type(&self) -> ChDigViews {
+        ChDigViews::Inserts
+    }
+
+    fn show(&self, app: &mut App, context: ContextArc, _instance: Option<&str>) {
+        show_inserts(app, context);
+    }
+}
+
+fn min_rows_per_insert(context: &ContextArc) -> u64 {
+    context
+        .lock()
+        .unwrap()
+        .view_min_rows_per_insert(VIEW_NAME)
+        .unwrap_or(DEFAULT_MIN_ROWS_PER_INSERT)
+}
+
+fn show_inserts(app: &mut App, context: ContextArc) {
+    if app.focus_name(VIEW_NAME) {
+        return;
+    }
+
+    let (limit, query_log, part_log, clickhouse, selected_host) = {
+        let ctx = context.lock().unwrap();
+        (
+            ctx.view_limit(VIEW_NAME, ctx.options.clickhouse.limit),
+            ctx.clickhouse.get_log_table_name("query_log"),
+            ctx.clickhouse.get_log_table_name("part_log"),
+            ctx.clickhouse.clone(),
+            ctx.selected_host.clone(),
+        )
+    };
+    let min_rows = min_rows_per_insert(&context);
+
+    let (with_prelude, time_clauses) = super::log_time_window(&context, VIEW_NAME);
+
+    // Every INSERT (including the ones on the shards of a Distributed table)
+    // is counted for all the tables it wrote to. The rows per insert are of
+    // the synchronous inserts only, the asynchronous ones are small by design
+    // (the server batches them into parts)
+    let mut query_log_clauses = time_clauses.clone();
+    query_log_clauses.push("type = 'QueryFinish'".to_string());
+    query_log_clauses.push("query_kind = 'Insert'".to_string());
+    super::push_host_filter(
+        &mut query_log_clauses,
+        &clickhouse,
+        selected_host.as_ref(),
+        true,
+    );
+
+    let mut part_log_clauses = time_clauses;
+    part_log_clauses.push("event_type = 'NewPart'".to_string());
+    part_log_clauses.push("error = 0".to_string());
+    part_log_clauses.push("database != 'system'".to_string());
+    super::push_host_filter(
+        &mut part_log_clauses,
+        &clickhouse,
+        selected_host.as_ref(),
+        true,
+    );
+
+    let columns = vec![
+        "database",
+        "table",
+        "inserts",
+        "inserts_per_second",
+        "avg_rows",
+        "avg_bytes",
+        "async_pct",
+        "new_parts",
+        "rows_per_part",
+        "clients",
+        "_small",
+    ];
+    // FULL JOIN: inserts of the materialized views do not always have the
+    // destination table in query_log, while part_log has the parts of them
+    // (so the name of the table is in one of the sides only)
+    let query = format!(
+        r#"
+        {with_prelude}, {min_rows} AS min_rows_
+        SELECT
+            if(queries.name != '', queries.name, parts.name) AS table_name,
+            extract(table_name, '^([^.]*)') AS database,
+            substring(table_name, length(database) + 2) AS table,
+            toUInt64(queries.inserts) AS inserts,
+            round(inserts / greatest(dateDiff('second', toDateTime(start_), toDateTime(end_)), 1), 2) AS inserts_per_second,
+            round(queries.avg_rows) AS avg_rows,
+            round(queries.avg_bytes) AS avg_bytes,
+            round(queries.async_pct, 1) AS async_pct,
+            toUInt64(parts.new_parts) AS new_parts,
+            round(parts.rows / greatest(new_parts, 1)) AS rows_per_part,
+            queries.clients AS clients,
+            toUInt8(queries.sync_inserts > 0 AND avg_rows < min_rows_) AS _small
+        FROM (
+            SELECT
+                arrayJoin(tables) AS name,
+                count() AS inserts,
+                countIf(Settings['async_insert'] != '1') AS sync_inserts,
+                avgIfOrDefault(written_rows, Settings['async_insert'] != '1') AS avg_rows,
+                avgIfOrDefault(written_bytes, Settings['async_insert'] != '1') AS avg_bytes,
+                (inserts - sync_inserts) / inserts * 100 AS async_pct,
+                arrayStringConcat(topK(3)(concat(initial_user, '@', toString(initial_address), if(client_name != '', concat(' ', client_name), ''))), ', ') AS clients
+            FROM {query_log}
+            WHERE {query_log_where} {internal}
+            GROUP BY name
+            HAVING name NOT LIKE 'system.%'
+        ) AS queries
+        FULL JOIN (
+            SELECT
+                concat(database, '.', table) AS name,
+                count() AS new_parts,
+                sum(rows) AS rows
+            FROM {part_log}
+            WHERE {part_log_where}
+            GROUP BY name
+        ) AS parts ON parts.name = queries.name
+        ORDER BY inserts DESC
+        LIMIT {limit}
+        "#,
+        with_prelude = with_prelude,
+        min_rows = min_rows,
+        query_log = query_log,
+        query_log_where = query_log_clauses.join(" AND "),
+        internal = clickhouse.get_internal_filter_clause(),
+        part_log = part_log,
+        part_log_where = part_log_clauses.join(" AND "),
+        limit = limit,
+    );
+
+    let mut view = SQLQueryView::new(
+        context.clone(),
+        VIEW_NAME,
+        "inserts",
+        columns,
+        vec!["database", "table"],
+        vec!["clients"],
+        query,
+    )
+    .unwrap_or_else(|_| panic!("Cannot create {}", VIEW_NAME));
+    let inner = view.get_inner_mut();
+    inner.set_on_submit(show_insert_actions);
+    inner.set_title(format!(
+        "Inserts (fewer than {} rows per insert are red)",
+        min_rows
+    ));
+    inner.set_value_unit("avg_bytes", Unit::Bytes);
+    inner.set_color_thresholds("_small", vec![(1., Color::Red)]);
+
+    app.present_view(VIEW_NAME, view.with_name(VIEW_NAME).full_screen());
+}
+
+fn show_insert_actions(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
+    let mut map = HashMap::new();
+    columns.iter().zip(row.0.iter()).for_each(|(c, r)| {
+        map.insert(*c, r.to_string());
+    });
+    let database = map.get("database").cloned().unwrap_or_default();
+    let table = map.get("table").cloned().unwrap_or_default();
+
+    let actions = vec![
+        ActionDescription {
+            text: "Show small inserts",
+            event: Event::Unknown(vec![]),
+        },
+        ActionDescription {
+            text: "Show all inserts",
+            event: Event::Unknown(vec![]),
+        },
+    ];
+
+    fuzzy_actions(app, actions, move |app, selected| match selected.as_str() {
+        "Show small inserts" => show_insert_queries(app, &database, &table, true),
+        "Show all inserts" => show_insert_queries(app, &database, &table, false),
+        _ => {}
+    });
+}
+
+/// INSERT queries into the table (only the synchronous ones with fewer rows
+/// than the threshold for `small`), over the interval of the inserts view.
+fn show_insert_queries(app: &mut App, database: &str, table: &str, small: bool) {
+    let context = app.user_data::<ContextArc>().unwrap().clone();
+    let min_rows = min_rows_per_insert(&context);
+
+    let name = format!("{}.{}", database, table);
+    let mut condition = format!(
+        "query_kind = 'Insert' AND has(tables, '{}')",
+        name.replace('\\', "\\\\").replace('\'', "\\'")
+    );
+    if small {
+        condition.push_str(&format!(
+            " AND written_rows < {} AND Settings['async_insert'] != '1'",
+            min_rows
+        ));
+    }
+    {
+        let mut ctx = context.lock().unwrap();
+        ctx.set_queries_condition(INSERT_QUERIES_VIEW_NAME, condition);
+        ctx.set_current_view(ChDigViews::LastQueries);
+    }
+
+    // Replace the view of a previously selected table in place
+    app.focus_name(INSERT_QUERIES_VIEW_NAME);
+    let title = if small {
+        format!(
+            "Synchronous inserts into {} with fewer than {} rows",
+            name, min_rows
+        )
+    } else {
+        format!("Inserts into {}", name)
+    };
+    app.present_view(
+        INSERT_QUERIES_VIEW_NAME,
+        QueriesView::new_with_interval_view(
+            context.clone(),
+            QueriesType::LastQueryLog,
+            INSERT_QUERIES_VIEW_NAME,
+            &title,
+            VIEW_NAME,
+        )
+        .with_name(INSERT_QUERIES_VIEW_NAME)
+        .full_screen(),
+    );
+    context.lock().unwrap().trigger_view_refresh();
+}
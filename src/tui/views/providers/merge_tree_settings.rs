@@ -0,0 +1,263 @@
+use crate::{
+    interpreter::{ClickHouseAvailableQuirks, ContextArc, WorkerEvent, options::ChDigViews},
+    tui::{
+        App, Dialog, Nameable, Navigation, Resizable, SizeConstraint, ViewProvider, style::Color,
+        views::sql_query_view::SQLQueryView,
+    },
+};
+
+// Widget name of the settings of a table (see RESERVED_VIEW_NAMES).
+const TABLE_MERGE_TREE_SETTINGS_VIEW_NAME: &str = "table_merge_tree_settings";
+
+/// String literal for the query (the overrides come from the CREATE
+/// statements, so anything can be there).
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Array(String) literal.
+fn string_array(values: impl Iterator<Item = String>) -> String {
+    format!(
+        "CAST([{}], 'Array(String)')",
+        values
+            .map(|value| quote(&value))
+            .collect::<Vec<_>>()
+            .join(", ")
+    )
+}
+
+// Rows with the table overrides are yellow, changed on the server level - cyan
+fn color_levels() -> Vec<(f64, Color)> {
+    vec![(1., Color::Cyan), (2., Color::Yellow)]
+}
+
+/// Server settings by name (any host, they are expected to be the same).
+fn server_settings_query(dbtable: &str, filter: &str) -> String {
+    format!(
+        "SELECT name, any(value) AS value, any(changed) AS changed, any(description) AS description FROM {} WHERE {} GROUP BY name",
+        dbtable, filter,
+    )
+}
+
+/// Server settings of the table engine (the replicated ones for the
+/// Replicated*MergeTree, if the server has them).
+fn table_engine_settings_query(context: &ContextArc, is_replicated: &str) -> String {
+    let ctx = context.lock().unwrap();
+    let merge_tree_settings = ctx.clickhouse.get_live_table_name("merge_tree_settings");
+    if !ctx
+        .clickhouse
+        .quirks
+        .has(ClickHouseAvailableQuirks::SystemReplicatedMergeTreeSettings)
+    {
+        return server_settings_query(&merge_tree_settings, "1");
+    }
+    format!(
+        "{} UNION ALL {}",
+        server_settings_query(&merge_tree_settings, &format!("NOT {}", is_replicated)),
+        server_settings_query(
+            &ctx.clickhouse
+                .get_live_table_name("replicated_merge_tree_settings"),
+            is_replicated,
+        ),
+    )
+}
+
+pub struct MergeTreeSettingsViewProvider;
+
+impl ViewProvider for MergeTreeSettingsViewProvider {
+    fn name(&self) -> &'static str {
+        "MergeTree settings"
+    }
+
+    fn view_type(&self) -> ChDigViews {
+        ChDigViews::MergeTreeSettings
+    }
+
+    fn show(&self, app: &mut App, context: ContextArc, _instance: Option<&str>) {
+        if app.focus_name("merge_tree_settings") {
+            return;
+        }
+        // The overrides are parsed from the CREATE statements by the worker
+        // (see show_merge_tree_settings())
+        context
+            .lock()
+            .unwrap()
+            .worker
+            .send(true, WorkerEvent::MergeTreeSettings);
+    }
+}
+
+/// Server MergeTree settings with the tables that override them, `overrides`
+/// are (setting, "db.table", value) of the tables (as of opening the view).
+pub fn show_merge_tree_settings(
+    app: &mut App,
+    context: ContextArc,
+    overrides: Vec<(String, String, String)>,
+) {
+    if app.focus_name("merge_tree_settings") {
+        return;
+    }
+
+    let (merge_tree_settings, replicated_merge_tree_settings) = {
+        let ctx = context.lock().unwrap();
+        (
+            ctx.clickhouse.get_live_table_name("merge_tree_settings"),
+            ctx.clickhouse
+                .quirks
+                .has(ClickHouseAvailableQuirks::SystemReplicatedMergeTreeSettings)
+                .then(|| {
+                    ctx.clickhouse
+                        .get_live_table_name("replicated_merge_tree_settings")
+                }),
+        )
+    };
+    let mut columns = vec![
+        "name",
+        "value",
+        "source",
+        "tables",
+        "overrides",
+        "description",
+        "_level",
+    ];
+    // Value for the Replicated*MergeTree, if it differs
+    let replicated_join = match replicated_merge_tree_settings {
+        Some(dbtable) => {
+            columns.insert(2, "replicated_value");
+            format!(
+                "LEFT JOIN ({}) AS replicated ON replicated.name = settings.name",
+                server_settings_query(&dbtable, "1")
+            )
+        }
+        None => String::new(),
+    };
+    let replicated_value = if replicated_join.is_empty() {
+        ""
+    } else {
+        "if(replicated.value != settings.value, replicated.value, '') AS replicated_value,"
+    };
+    let query = format!(
+        r#"
+        SELECT
+            settings.name AS name,
+            settings.value AS value,
+            {replicated_value}
+            if(settings.changed, 'server', 'default') AS source,
+            toUInt32(length(overrides.table_values)) AS tables,
+            arrayStringConcat(arraySort(overrides.table_values), ', ') AS overrides,
+            settings.description AS description,
+            toUInt8(multiIf(tables > 0, 2, settings.changed, 1, 0)) AS _level
+        FROM ({settings}) AS settings
+        {replicated_join}
+        LEFT JOIN (
+            SELECT
+                setting.1 AS name,
+                groupUniqArray(concat(setting.2, ' = ', setting.3)) AS table_values
+            FROM (SELECT arrayJoin(arrayZip({names}, {tables}, {values})) AS setting)
+            GROUP BY name
+        ) AS overrides ON overrides.name = settings.name
+        "#,
+        replicated_value = replicated_value,
+        settings = server_settings_query(&merge_tree_settings, "1"),
+        replicated_join = replicated_join,
+        names = string_array(overrides.iter().map(|o| o.0.clone())),
+        tables = string_array(overrides.iter().map(|o| o.1.clone())),
+        values = string_array(overrides.iter().map(|o| o.2.clone())),
+    );
+
+    let mut view = SQLQueryView::new(
+        context.clone(),
+        "merge_tree_settings",
+        "tables",
+        columns,
+        vec!["name"],
+        vec!["overrides", "description"],
+        query,
+    )
+    .unwrap_or_else(|_| panic!("Cannot get merge_tree_settings"));
+    let inner = view.get_inner_mut();
+    inner.set_on_submit(super::query_result_show_row);
+    inner.set_title("MergeTree settings");
+    inner.set_color_thresholds("_level", color_levels());
+
+    app.present_view(
+        "merge_tree_settings",
+        view.with_name("merge_tree_settings").full_screen(),
+    );
+}
+
+/// Effective MergeTree settings of the table: the override from the CREATE
+/// statement (`overrides`, see merge_tree_table_settings()), or the server
+/// value.
+pub fn show_table_merge_tree_settings(
+    app: &mut App,
+    context: ContextArc,
+    database: String,
+    table: String,
+    overrides: Vec<(String, String)>,
+) {
+    let tables = context
+        .lock()
+        .unwrap()
+        .clickhouse
+        .get_table_name_no_history("tables");
+    let table_filter = format!(
+        "database = '{}' AND name = '{}'",
+        database.replace('\'', "''"),
+        table.replace('\'', "''")
+    );
+
+    let columns = vec![
+        "name",
+        "value",
+        "server_value",
+        "source",
+        "description",
+        "_level",
+    ];
+    let query = format!(
+        r#"
+        WITH
+            (SELECT any(engine) FROM {tables} WHERE {table_filter}) LIKE 'Replicated%' AS is_replicated,
+            {names} AS names_,
+            {values} AS values_
+        SELECT
+            settings.name AS name,
+            if(has(names_, settings.name), values_[indexOf(names_, settings.name)], settings.value) AS value,
+            settings.value AS server_value,
+            multiIf(has(names_, settings.name), 'table', settings.changed, 'server', 'default') AS source,
+            settings.description AS description,
+            toUInt8(multiIf(source = 'table', 2, source = 'server', 1, 0)) AS _level
+        FROM ({settings}) AS settings
+        "#,
+        tables = tables,
+        table_filter = table_filter,
+        names = string_array(overrides.iter().map(|o| o.0.clone())),
+        values = string_array(overrides.iter().map(|o| o.1.clone())),
+        settings = table_engine_settings_query(&context, "is_replicated"),
+    );
+
+    // "table" > "server" > "default", i.e. the overrides go first
+    let mut view = SQLQueryView::new(
+        context,
+        TABLE_MERGE_TREE_SETTINGS_VIEW_NAME,
+        "source",
+        columns,
+        vec!["name"],
+        vec!["description"],
+        query,
+    )
+    .unwrap_or_else(|_| panic!("Cannot get {}", TABLE_MERGE_TREE_SETTINGS_VIEW_NAME));
+    let inner = view.get_inner_mut();
+    inner.set_title(format!("MergeTree settings of {}.{}", database, table));
+    inner.set_on_submit(super::query_result_show_row);
+    inner.set_color_thresholds("_level", color_levels());
+
+    app.add_layer(
+        Dialog::around(
+            view.with_name(TABLE_MERGE_TREE_SETTINGS_VIEW_NAME)
+                .resized(SizeConstraint::AtLeast(140), SizeConstraint::AtLeast(30)),
+        )
+        .title("MergeTree settings"),
+    );
+}
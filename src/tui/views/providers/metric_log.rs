@@ -132,6 +132,8 @@ fn show_metric_log(app: &mut App, context: ContextArc) {
     )
     .unwrap_or_else(|_| panic!("Cannot create {}", view_name));
 
+    let descriptions = context.lock().unwrap().clickhouse.metric_descriptions();
+    view.get_inner_mut().set_descriptions("name", descriptions);
     view.get_inner_mut().set_on_submit(show_chart);
     view.get_inner_mut().set_title("Metric log");
 
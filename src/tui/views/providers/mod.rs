@@ -7,19 +7,25 @@ pub mod client;
 pub mod dictionaries;
 pub mod error_log;
 pub mod errors;
+pub mod failed_queries;
 pub mod flamegraph;
+pub mod inserts;
 pub mod logger_names;
+pub mod merge_tree_settings;
 pub mod merges;
 pub mod metric_log;
 pub mod mutations;
 pub mod object_storage_queue;
 pub mod part_log;
 pub mod queries;
+pub mod query_leaderboard;
 pub mod query_patterns;
+pub mod query_patterns_compare;
 pub mod query_patterns_metrics;
 pub mod replicas;
 pub mod replicated_fetches;
 pub mod replication_queue;
+pub mod schema_drift;
 pub mod server_logs;
 pub mod table_parts;
 pub mod tables;
@@ -40,6 +46,8 @@ pub fn all() -> Vec<std::sync::Arc<dyn crate::tui::ViewProvider>> {
         Arc::new(queries::SlowQueryLogViewProvider),
         Arc::new(queries::LastQueryLogViewProvider),
         Arc::new(query_patterns::QueryPatternsViewProvider),
+        Arc::new(failed_queries::FailedQueriesViewProvider),
+        Arc::new(query_leaderboard::QueryLeaderboardViewProvider),
         Arc::new(merges::MergesViewProvider),
         Arc::new(object_storage_queue::S3QueueViewProvider),
         Arc::new(object_storage_queue::AzureQueueViewProvider),
@@ -48,10 +56,13 @@ pub fn all() -> Vec<std::sync::Arc<dyn crate::tui::ViewProvider>> {
         Arc::new(replication_queue::ReplicationQueueViewProvider),
         Arc::new(replicas::ReplicasViewProvider),
         Arc::new(tables::TablesViewProvider),
+        Arc::new(schema_drift::SchemaDriftViewProvider),
+        Arc::new(merge_tree_settings::MergeTreeSettingsViewProvider),
         Arc::new(background_schedule_pool::BackgroundSchedulePoolViewProvider),
         Arc::new(background_schedule_pool_log::BackgroundSchedulePoolLogViewProvider),
         Arc::new(table_parts::TablePartsViewProvider),
         Arc::new(asynchronous_inserts::AsynchronousInsertsViewProvider),
+        Arc::new(inserts::InsertsViewProvider),
         Arc::new(part_log::PartLogViewProvider),
         Arc::new(metric_log::MetricLogViewProvider),
         Arc::new(asynchronous_metric_log::AsynchronousMetricLogViewProvider),
@@ -357,6 +368,24 @@ fn escape_for_like(s: &str) -> String {
         .replace('%', "\\%")
 }
 
+/// system.query_log condition for the queries that accessed `spec`
+/// (`db`, `db.table` or `db.table.column`, matched against the databases,
+/// tables/views and columns/projections arrays). None for an empty spec.
+pub fn accessed_objects_condition(spec: &str) -> Option<String> {
+    let spec = spec.trim().replace('`', "");
+    if spec.is_empty() {
+        return None;
+    }
+    let quoted = format!("'{}'", spec.replace('\\', "\\\\").replace('\'', "\\'"));
+    // Column names may contain dots (Nested), database and table names may not
+    let condition = match spec.splitn(3, '.').count() {
+        1 => format!("has(databases, {})", quoted),
+        2 => format!("(has(tables, {0}) OR has(views, {0}))", quoted),
+        _ => format!("(has(columns, {0}) OR has(projections, {0}))", quoted),
+    };
+    Some(condition)
+}
+
 pub fn query_result_show_logs_for_row(
     app: &mut App,
     columns: Vec<&'static str>,
@@ -560,17 +589,30 @@ pub fn show_metric_chart(
     value_expr: String,
     filter: Option<String>,
     title: String,
+) {
+    // The callers are the *_log views themselves, whose view names match
+    // their table names, so `table` also picks up the per-view interval.
+    show_log_chart(app, table, table, value_expr, filter, title);
+}
+
+/// show_metric_chart() for views whose name differs from the `*_log` table
+/// they chart (`view_name` picks the time interval).
+pub fn show_log_chart(
+    app: &mut App,
+    view_name: &str,
+    table: &'static str,
+    value_expr: String,
+    filter: Option<String>,
+    title: String,
 ) {
     let context = app.user_data::<ContextArc>().unwrap().clone();
     // Dialog borders + y-axis label
     let buckets = app.screen_size().width.saturating_sub(24).clamp(16, 240) as u32;
 
-    // The callers are the *_log views themselves, whose view names match
-    // their table names, so `table` also picks up the per-view interval.
     let ((start, end), dbtable, clickhouse, selected_host) = {
         let ctx = context.lock().unwrap();
         (
-            ctx.view_interval(table),
+            ctx.view_interval(view_name),
             ctx.clickhouse.get_log_table_name(table),
             ctx.clickhouse.clone(),
             ctx.selected_host.clone(),
@@ -659,6 +701,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_accessed_objects_condition() {
+        assert_eq!(accessed_objects_condition(""), None);
+        assert_eq!(accessed_objects_condition("  "), None);
+        assert_eq!(
+            accessed_objects_condition("db").unwrap(),
+            "has(databases, 'db')"
+        );
+        assert_eq!(
+            accessed_objects_condition("`db`.`t`").unwrap(),
+            "(has(tables, 'db.t') OR has(views, 'db.t'))"
+        );
+        assert_eq!(
+            accessed_objects_condition("db.t.n.x").unwrap(),
+            "(has(columns, 'db.t.n.x') OR has(projections, 'db.t.n.x'))"
+        );
+        assert_eq!(
+            accessed_objects_condition("db.it's").unwrap(),
+            "(has(tables, 'db.it\\'s') OR has(views, 'db.it\\'s'))"
+        );
+    }
+
     #[test]
     fn test_backquote_if_needed_valid_identifiers() {
         // Valid simple identifiers should not be quoted
@@ -1,6 +1,6 @@
 use super::{Presentation, QueryTableSpec, TableFilterParams};
 use crate::{
-    interpreter::{ContextArc, TextLogArguments, options::ChDigViews},
+    interpreter::{ContextArc, TextLogArguments, WorkerEvent, options::ChDigViews},
     tui::{
         App, Dialog, DummyView, Event, LinearLayout, NamedView, Resizable, TextView, ViewProvider,
         actions::ActionDescription, fuzzy_actions, views::sql_query_view::Row as QueryResultRow,
@@ -39,6 +39,9 @@ const COLUMNS: &[&str] = &[
     "peak_memory_usage",
     "exception",
     "table_uuid _table_uuid",
+    // For the part lineage (database/table are not shown in the dialogs)
+    "database _database",
+    "table _table",
 ];
 
 fn build_query(
@@ -143,6 +146,25 @@ fn show_part_details(app: &mut App, columns: Vec<&'static str>, row: QueryResult
     app.add_layer(Dialog::info(info).title("Part Log Details"));
 }
 
+fn show_part_lineage(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
+    let mut map = HashMap::new();
+    columns.iter().zip(row.0.iter()).for_each(|(c, r)| {
+        map.insert(c.to_string(), r);
+    });
+
+    let context = app.user_data::<ContextArc>().unwrap().clone();
+    context.lock().unwrap().worker.send(
+        true,
+        WorkerEvent::PartLineage(
+            map["_database"].to_string(),
+            map["_table"].to_string(),
+            map["_table_uuid"].to_string(),
+            map["part_name"].to_string(),
+            map["event_time"].as_datetime().unwrap(),
+        ),
+    );
+}
+
 fn part_log_action_callback(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
     let actions = vec![
         ActionDescription {
@@ -153,6 +175,10 @@ fn part_log_action_callback(app: &mut App, columns: Vec<&'static str>, row: Quer
             text: "Show part details",
             event: Event::Unknown(vec![]),
         },
+        ActionDescription {
+            text: "Show part lineage",
+            event: Event::Unknown(vec![]),
+        },
     ];
 
     let columns_clone = columns.clone();
@@ -165,6 +191,9 @@ fn part_log_action_callback(app: &mut App, columns: Vec<&'static str>, row: Quer
         "Show part details" => {
             show_part_details(app, columns_clone.clone(), row_clone.clone());
         }
+        "Show part lineage" => {
+            show_part_lineage(app, columns_clone.clone(), row_clone.clone());
+        }
         _ => {}
     });
 }
@@ -0,0 +1,243 @@
+use crate::{
+    interpreter::{ContextArc, options::ChDigViews},
+    tui::{
+        App, Event, Nameable, Navigation, OnEventView, Resizable, ViewProvider,
+        fuzzy_select_strings,
+        views::queries_view::{QueriesView, Type as QueriesType},
+        views::sql_query_view::{Row as QueryResultRow, SQLQueryView, Unit},
+    },
+};
+use std::collections::HashMap;
+
+const VIEW_NAME: &str = "query_leaderboard";
+// Widget name of the drill-down queries view (see RESERVED_VIEW_NAMES).
+const GROUP_QUERIES_VIEW_NAME: &str = "query_leaderboard_queries";
+
+/// One grouping dimension of the leaderboard.
+pub struct Dimension {
+    pub key: &'static str,
+    pub label: &'static str,
+    /// Grouping expression over system.query_log (the `name` column).
+    pub expr: &'static str,
+    /// Drill-down condition, `{}` is replaced with the quoted group value.
+    pub condition: &'static str,
+}
+
+pub const DIMENSIONS: &[Dimension] = &[
+    Dimension {
+        key: "user",
+        label: "user",
+        expr: "user",
+        condition: "user = {}",
+    },
+    Dimension {
+        key: "client",
+        label: "client",
+        expr: "concat(client_name, ' ', toString(client_version_major), '.', toString(client_version_minor), '.', toString(client_version_patch))",
+        condition: "concat(client_name, ' ', toString(client_version_major), '.', toString(client_version_minor), '.', toString(client_version_patch)) = {}",
+    },
+    Dimension {
+        key: "http_user_agent",
+        label: "HTTP user agent",
+        expr: "http_user_agent",
+        condition: "http_user_agent = {}",
+    },
+    Dimension {
+        key: "table",
+        label: "table",
+        expr: "arrayJoin(tables)",
+        condition: "has(tables, {})",
+    },
+    Dimension {
+        key: "interface",
+        label: "interface",
+        expr: "toString(interface)",
+        condition: "toString(interface) = {}",
+    },
+];
+
+pub fn default_dimension() -> &'static Dimension {
+    &DIMENSIONS[0]
+}
+
+pub struct QueryLeaderboardViewProvider;
+
+impl ViewProvider for QueryLeaderboardViewProvider {
+    fn name(&self) -> &'static str {
+        "Query leaderboard"
+    }
+
+    fn view_type(&self) -> ChDigViews {
+        ChDigViews::QueryLeaderboard
+    }
+
+    fn show(&self, app: &mut App, context: ContextArc, _instance: Option<&str>) {
+        if app.focus_name(VIEW_NAME) {
+            return;
+        }
+        build_and_install(app, context);
+    }
+}
+
+fn build_query(context: &ContextArc, dimension: &Dimension) -> String {
+    let ((start_sql, end_sql), limit, dbtable, clickhouse, selected_host) = {
+        let ctx = context.lock().unwrap();
+        (
+            ctx.view_interval_sql(VIEW_NAME),
+            ctx.view_limit(VIEW_NAME, ctx.options.clickhouse.limit),
+            ctx.clickhouse.get_log_table_name("query_log"),
+            ctx.clickhouse.clone(),
+            ctx.selected_host.clone(),
+        )
+    };
+
+    // One row per finished query (QueryFinish or one of the Exception* types),
+    // initial queries only, so that distributed queries are not counted twice.
+    format!(
+        r#"
+        WITH {start} AS start_, {end} AS end_
+        SELECT
+            {expr} AS name,
+            count() AS queries,
+            queries AS bar,
+            sum(ProfileEvents['UserTimeMicroseconds'] + ProfileEvents['SystemTimeMicroseconds']) AS cpu,
+            sum(read_bytes) AS read,
+            max(memory_usage) AS memory,
+            countIf(type != 'QueryFinish') / queries * 100 AS errors_pct
+        FROM {dbtable}
+        WHERE
+            event_date BETWEEN toDate(start_) AND toDate(end_) AND
+            event_time BETWEEN toDateTime(start_) AND toDateTime(end_) AND
+            is_initial_query AND
+            type != 'QueryStart'
+            {internal}
+            {host_filter}
+        GROUP BY name
+        ORDER BY queries DESC
+        LIMIT {limit}
+        "#,
+        start = start_sql,
+        end = end_sql,
+        expr = dimension.expr,
+        dbtable = dbtable,
+        internal = clickhouse.get_internal_filter_clause(),
+        host_filter = clickhouse.get_log_host_filter_clause(selected_host.as_ref()),
+        limit = limit,
+    )
+}
+
+fn build_and_install(app: &mut App, context: ContextArc) {
+    let dimension = context.lock().unwrap().query_leaderboard_dimension;
+    let query = build_query(&context, dimension);
+    let columns = vec![
+        "name",
+        "queries",
+        "bar",
+        "cpu",
+        "read",
+        "memory",
+        "errors_pct",
+    ];
+
+    let mut view = SQLQueryView::new(
+        context.clone(),
+        VIEW_NAME,
+        "queries",
+        columns,
+        vec!["name"],
+        vec!["name"],
+        query,
+    )
+    .unwrap_or_else(|_| panic!("Cannot create {}", VIEW_NAME));
+
+    let v = view.get_inner_mut();
+    v.set_on_submit(open_group_queries);
+    v.set_title(format!("Query leaderboard by {}", dimension.label));
+    v.set_bar_columns(vec![("bar", "queries")]);
+    v.set_column_title("name", dimension.label);
+    v.set_column_title("errors_pct", "errors %");
+    v.set_value_unit("cpu", Unit::Microseconds);
+    v.set_value_unit("read", Unit::Bytes);
+    v.set_value_unit("memory", Unit::Bytes);
+
+    let wrapped = OnEventView::new(view.with_name(VIEW_NAME).full_screen())
+        .on_event(Event::Char('g'), show_dimension_picker)
+        .on_event(Event::Char(' '), cycle_dimension);
+
+    app.present_view(VIEW_NAME, wrapped);
+}
+
+fn set_dimension(app: &mut App, dimension: &'static Dimension) {
+    let context = app.user_data::<ContextArc>().unwrap().clone();
+    context.lock().unwrap().query_leaderboard_dimension = dimension;
+    log::trace!("Query leaderboard dimension switched to {}", dimension.key);
+    // Unlike the query patterns metrics, the grouping is done by the server,
+    // so the view is recreated (in place) with the new query.
+    app.focus_name(VIEW_NAME);
+    build_and_install(app, context);
+}
+
+fn cycle_dimension(app: &mut App) {
+    let context = app.user_data::<ContextArc>().unwrap().clone();
+    let current = context.lock().unwrap().query_leaderboard_dimension.key;
+    let idx = DIMENSIONS
+        .iter()
+        .position(|d| d.key == current)
+        .unwrap_or(0);
+    set_dimension(app, &DIMENSIONS[(idx + 1) % DIMENSIONS.len()]);
+}
+
+fn show_dimension_picker(app: &mut App) {
+    let items: Vec<(String, String)> = DIMENSIONS
+        .iter()
+        .map(|d| (d.label.to_string(), d.key.to_string()))
+        .collect();
+    fuzzy_select_strings(app, "Group by", items, |app, key| {
+        let Some(dimension) = DIMENSIONS.iter().find(|d| d.key == key) else {
+            return;
+        };
+        set_dimension(app, dimension);
+    });
+}
+
+/// Queries of the selected group, as a LastQueries-like view over the
+/// leaderboard interval.
+fn open_group_queries(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
+    let mut map = HashMap::new();
+    columns.iter().zip(row.0.iter()).for_each(|(c, r)| {
+        map.insert(*c, r.to_string());
+    });
+    let Some(name) = map.get("name") else {
+        return;
+    };
+
+    let context = app.user_data::<ContextArc>().unwrap().clone();
+    let dimension = context.lock().unwrap().query_leaderboard_dimension;
+    let condition = dimension.condition.replace(
+        "{}",
+        &format!("'{}'", name.replace('\\', "\\\\").replace('\'', "\\'")),
+    );
+    {
+        let mut ctx = context.lock().unwrap();
+        ctx.set_queries_condition(GROUP_QUERIES_VIEW_NAME, condition);
+        ctx.set_current_view(ChDigViews::LastQueries);
+    }
+
+    // Two views with one name would both receive the worker updates: replace
+    // the one of a previously opened group in place.
+    app.focus_name(GROUP_QUERIES_VIEW_NAME);
+    let title = format!("Queries of {} {}", dimension.label, name);
+    app.present_view(
+        GROUP_QUERIES_VIEW_NAME,
+        QueriesView::new_with_interval_view(
+            context.clone(),
+            QueriesType::LastQueryLog,
+            GROUP_QUERIES_VIEW_NAME,
+            &title,
+            VIEW_NAME,
+        )
+        .with_name(GROUP_QUERIES_VIEW_NAME)
+        .full_screen(),
+    );
+    context.lock().unwrap().trigger_view_refresh();
+}
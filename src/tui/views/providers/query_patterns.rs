@@ -5,6 +5,7 @@ use crate::{
         App, Event, Nameable, Navigation, OnEventView, Resizable, ViewProvider,
         fuzzy_select_strings,
         style::Color,
+        views::queries_view::{QueriesView, Type as QueriesType},
         views::sql_query_view::{Row as QueryResultRow, SQLQueryView},
     },
 };
@@ -14,7 +15,9 @@ use std::collections::HashMap;
 // the bucket count and the rendered column width line up exactly.
 const HEATMAP_BUCKETS: usize = 40;
 
-const VIEW_NAME: &str = "query_patterns";
+pub(super) const VIEW_NAME: &str = "query_patterns";
+// Widget name of the per-pattern queries view (see RESERVED_VIEW_NAMES).
+const PATTERN_QUERIES_VIEW_NAME: &str = "query_pattern_queries";
 
 pub struct QueryPatternsViewProvider;
 
@@ -163,7 +166,9 @@ pub fn query_patterns_sql(
     )
 }
 
-fn open_last_queries_for_hash(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
+/// Executions of the selected pattern, as a LastQueries-like view (so that
+/// all per-query actions are available) over the patterns view's interval.
+fn open_pattern_queries(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
     let mut map = HashMap::new();
     columns.iter().zip(row.0.iter()).for_each(|(c, r)| {
         map.insert(*c, r.to_string());
@@ -174,13 +179,30 @@ fn open_last_queries_for_hash(app: &mut App, columns: Vec<&'static str>, row: Qu
     }
 
     let context = app.user_data::<ContextArc>().unwrap().clone();
-    let provider = {
+    {
         let mut ctx = context.lock().unwrap();
-        *ctx.queries_filter("last_query_log").lock().unwrap() = hash;
+        *ctx.queries_filter(PATTERN_QUERIES_VIEW_NAME)
+            .lock()
+            .unwrap() = hash.clone();
         ctx.set_current_view(ChDigViews::LastQueries);
-        ctx.view_registry.get_by_view_type(ChDigViews::LastQueries)
-    };
-    provider.show(app, context.clone(), None);
+    }
+
+    // Two views with one name would both receive the worker updates: replace
+    // the one of a previously opened pattern in place.
+    app.focus_name(PATTERN_QUERIES_VIEW_NAME);
+    let title = format!("Queries of pattern {}", hash);
+    app.present_view(
+        PATTERN_QUERIES_VIEW_NAME,
+        QueriesView::new_with_interval_view(
+            context.clone(),
+            QueriesType::LastQueryLog,
+            PATTERN_QUERIES_VIEW_NAME,
+            &title,
+            VIEW_NAME,
+        )
+        .with_name(PATTERN_QUERIES_VIEW_NAME)
+        .full_screen(),
+    );
     context.lock().unwrap().trigger_view_refresh();
 }
 
@@ -278,8 +300,7 @@ fn build_and_install(app: &mut App, context: ContextArc) {
     )
     .unwrap_or_else(|_| panic!("Cannot create {}", VIEW_NAME));
 
-    view.get_inner_mut()
-        .set_on_submit(open_last_queries_for_hash);
+    view.get_inner_mut().set_on_submit(open_pattern_queries);
     view.get_inner_mut()
         .set_column_width("heatmap", HEATMAP_BUCKETS);
     configure_metric(view.get_inner_mut(), metric);
@@ -293,7 +314,11 @@ fn build_and_install(app: &mut App, context: ContextArc) {
 
     let wrapped = OnEventView::new(view.with_name(VIEW_NAME).full_screen())
         .on_event(Event::Char('m'), show_metric_picker)
-        .on_event(Event::Char(' '), cycle_metric);
+        .on_event(Event::Char(' '), cycle_metric)
+        .on_event(
+            Event::Char('c'),
+            super::query_patterns_compare::show_compare_prompt,
+        );
 
     app.present_view(VIEW_NAME, wrapped);
 }
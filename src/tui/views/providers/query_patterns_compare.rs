@@ -0,0 +1,255 @@
+use crate::{
+    common::RelativeDateTime,
+    interpreter::ContextArc,
+    tui::{
+        App, Dialog, Nameable, Resizable, SizeConstraint, show_bottom_prompt,
+        style::Color,
+        views::sql_query_view::{SQLQueryView, Unit},
+    },
+};
+use chrono::{DateTime, Local, TimeDelta};
+
+// Dialog widget name (see RESERVED_VIEW_NAMES).
+const VIEW_NAME: &str = "query_patterns_compare";
+
+/// Builds the "Query patterns" comparison SQL: per normalized_query_hash
+/// durations quantiles, count, read bytes and memory in the baseline and in
+/// the current window, with absolute/relative deltas. Deltas are 0 for the
+/// patterns seen in only one window (`flag` is "new"/"gone" for them), so
+/// that sorting by a delta shows the actual regressions first.
+/// Pure (no Context) so integration tests can run it against a real server.
+/// `current` / `baseline` are (start, end) SQL expressions, `internal_filter` /
+/// `host_filter` are extra `AND ...` WHERE clauses (or "").
+pub fn query_patterns_compare_sql(
+    current: (&str, &str),
+    baseline: (&str, &str),
+    dbtable: &str,
+    internal_filter: &str,
+    host_filter: &str,
+    limit: u64,
+) -> String {
+    let cur = "event_time BETWEEN toDateTime(start_) AND toDateTime(end_)";
+    format!(
+        r#"
+        WITH
+            {start} AS start_,
+            {end} AS end_,
+            {base_start} AS base_start_,
+            {base_end} AS base_end_
+        SELECT
+            hash,
+            multiIf(cnt_base = 0, 'new', cnt_cur = 0, 'gone', '') AS flag,
+            cnt_base > 0 AND cnt_cur > 0 AS _both,
+            cnt_base,
+            cnt_cur,
+            if(_both, toInt64(cnt_cur) - toInt64(cnt_base), 0) AS cnt_delta,
+            if(_both, (toInt64(cnt_cur) - toInt64(cnt_base)) / cnt_base * 100, 0) AS cnt_rel,
+            q_base[1] / 1e3 AS p50_base,
+            q_cur[1] / 1e3 AS p50_cur,
+            if(_both, p50_cur - p50_base, 0) AS p50_delta,
+            if(_both AND p50_base > 0, (p50_cur - p50_base) / p50_base * 100, 0) AS p50_rel,
+            q_base[2] / 1e3 AS p95_base,
+            q_cur[2] / 1e3 AS p95_cur,
+            if(_both, p95_cur - p95_base, 0) AS p95_delta,
+            if(_both AND p95_base > 0, (p95_cur - p95_base) / p95_base * 100, 0) AS p95_rel,
+            q_base[3] / 1e3 AS p99_base,
+            q_cur[3] / 1e3 AS p99_cur,
+            if(_both, p99_cur - p99_base, 0) AS p99_delta,
+            if(_both AND p99_base > 0, (p99_cur - p99_base) / p99_base * 100, 0) AS p99_rel,
+            read_base,
+            read_cur,
+            if(_both, read_cur - read_base, 0) AS read_delta,
+            if(_both AND read_base > 0, (read_cur - read_base) / read_base * 100, 0) AS read_rel,
+            mem_base,
+            mem_cur,
+            if(_both, mem_cur - mem_base, 0) AS mem_delta,
+            if(_both AND mem_base > 0, (mem_cur - mem_base) / mem_base * 100, 0) AS mem_rel,
+            normalized_query
+        FROM
+        (
+            SELECT
+                toString(normalized_query_hash) AS hash,
+                countIf(NOT ({cur})) AS cnt_base,
+                countIf({cur}) AS cnt_cur,
+                arrayMap(x -> ifNotFinite(x, 0), quantilesIf(0.5, 0.95, 0.99)(query_duration_ms, NOT ({cur}))) AS q_base,
+                arrayMap(x -> ifNotFinite(x, 0), quantilesIf(0.5, 0.95, 0.99)(query_duration_ms, {cur})) AS q_cur,
+                ifNotFinite(avgIf(read_bytes, NOT ({cur})), 0) AS read_base,
+                ifNotFinite(avgIf(read_bytes, {cur}), 0) AS read_cur,
+                ifNotFinite(avgIf(memory_usage, NOT ({cur})), 0) AS mem_base,
+                ifNotFinite(avgIf(memory_usage, {cur}), 0) AS mem_cur,
+                any(normalizeQuery(query)) AS normalized_query
+            FROM {dbtable}
+            WHERE
+                (
+                    (event_date BETWEEN toDate(start_) AND toDate(end_) AND {cur}) OR
+                    (event_date BETWEEN toDate(base_start_) AND toDate(base_end_) AND
+                     event_time BETWEEN toDateTime(base_start_) AND toDateTime(base_end_))
+                ) AND
+                is_initial_query AND
+                type NOT IN ('QueryStart', 'ExceptionBeforeStart')
+                {internal}
+                {host_filter}
+            GROUP BY normalized_query_hash
+            ORDER BY greatest(cnt_base, cnt_cur) DESC
+            LIMIT {limit}
+        )
+        "#,
+        start = current.0,
+        end = current.1,
+        base_start = baseline.0,
+        base_end = baseline.1,
+        cur = cur,
+        dbtable = dbtable,
+        internal = internal_filter,
+        host_filter = host_filter,
+        limit = limit,
+    )
+}
+
+/// Asks for the baseline: the patterns view interval shifted back by the
+/// entered duration (empty - by its own length, i.e. the preceding interval).
+pub fn show_compare_prompt(app: &mut App) {
+    show_bottom_prompt(app, "baseline shift (e.g. 1d): ", |app, text| {
+        app.pop_layer();
+
+        let shift = if text.trim().is_empty() {
+            None
+        } else {
+            match text.trim().parse::<humantime::Duration>() {
+                Ok(shift) => TimeDelta::from_std(shift.into()).ok(),
+                Err(err) => {
+                    app.add_layer(Dialog::info(format!(
+                        "Invalid baseline shift '{}': {}",
+                        text, err
+                    )));
+                    return;
+                }
+            }
+        };
+        show_compare(app, shift);
+    });
+}
+
+fn show_compare(app: &mut App, shift: Option<TimeDelta>) {
+    let context = app.user_data::<ContextArc>().unwrap().clone();
+
+    let ((start, end), (start_sql, end_sql), limit, dbtable, clickhouse, selected_host) = {
+        let ctx = context.lock().unwrap();
+        (
+            ctx.view_interval(super::query_patterns::VIEW_NAME),
+            ctx.view_interval_sql(super::query_patterns::VIEW_NAME),
+            ctx.view_limit(
+                super::query_patterns::VIEW_NAME,
+                ctx.options.clickhouse.limit,
+            ),
+            ctx.clickhouse.get_log_table_name("query_log"),
+            ctx.clickhouse.clone(),
+            ctx.selected_host.clone(),
+        )
+    };
+
+    let start = DateTime::<Local>::from(start);
+    let end = DateTime::<Local>::from(end);
+    let shift = shift.unwrap_or(end - start);
+    let base_start = RelativeDateTime::from(start - shift);
+    let base_end = RelativeDateTime::from(end - shift);
+    let (Some(base_start_sql), Some(base_end_sql)) = (
+        base_start.to_sql_datetime_64(),
+        base_end.to_sql_datetime_64(),
+    ) else {
+        return;
+    };
+
+    let query = query_patterns_compare_sql(
+        (&start_sql, &end_sql),
+        (&base_start_sql, &base_end_sql),
+        &dbtable,
+        &clickhouse.get_internal_filter_clause(),
+        &clickhouse.get_log_host_filter_clause(selected_host.as_ref()),
+        limit,
+    );
+
+    // Every metric is base, cur, Δ and Δ%, so that the view can be sorted by
+    // any of the deltas
+    let columns = vec![
+        "hash",
+        "flag",
+        "cnt_base",
+        "cnt_cur",
+        "cnt_delta",
+        "cnt_rel",
+        "p50_base",
+        "p50_cur",
+        "p50_delta",
+        "p50_rel",
+        "p95_base",
+        "p95_cur",
+        "p95_delta",
+        "p95_rel",
+        "p99_base",
+        "p99_cur",
+        "p99_delta",
+        "p99_rel",
+        "read_base",
+        "read_cur",
+        "read_delta",
+        "read_rel",
+        "mem_base",
+        "mem_cur",
+        "mem_delta",
+        "mem_rel",
+        "normalized_query",
+    ];
+
+    let mut view = SQLQueryView::new(
+        context.clone(),
+        VIEW_NAME,
+        "p95_delta",
+        columns.clone(),
+        vec!["hash"],
+        vec!["normalized_query"],
+        query,
+    )
+    .unwrap_or_else(|_| panic!("Cannot create {}", VIEW_NAME));
+
+    let v = view.get_inner_mut();
+    v.set_on_submit(super::query_result_show_row);
+    for &column in &columns {
+        let Some((metric, kind)) = column.split_once('_') else {
+            continue;
+        };
+        match (metric, kind) {
+            (_, "rel") => v.set_column_title(column, &format!("{} Δ%", metric)),
+            (_, "delta") => v.set_column_title(column, &format!("{} Δ", metric)),
+            _ => {}
+        }
+        match (metric, kind) {
+            ("p50" | "p95" | "p99", "base" | "cur" | "delta") => {
+                v.set_value_unit(column, Unit::Seconds)
+            }
+            ("read" | "mem", "base" | "cur" | "delta") => v.set_value_unit(column, Unit::Bytes),
+            _ => {}
+        }
+    }
+    v.set_color_log_scale(
+        "p95_rel",
+        vec![Color::Green, Color::Yellow, Color::Magenta, Color::Red],
+    );
+
+    let title = format!(
+        "Query patterns: [{} .. {}] vs [{} .. {}]",
+        base_start.to_editable_string(),
+        base_end.to_editable_string(),
+        RelativeDateTime::from(start).to_editable_string(),
+        RelativeDateTime::from(end).to_editable_string(),
+    );
+    v.set_title(title.clone());
+
+    app.add_layer(
+        Dialog::around(
+            view.with_name(VIEW_NAME)
+                .resized(SizeConstraint::AtLeast(140), SizeConstraint::AtLeast(30)),
+        )
+        .title(title),
+    );
+}
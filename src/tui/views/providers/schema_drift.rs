@@ -0,0 +1,125 @@
+use crate::{
+    interpreter::{ContextArc, WorkerEvent, options::ChDigViews},
+    tui::{
+        App, Dialog, Nameable, Navigation, Resizable, ViewProvider,
+        views::sql_query_view::{Row as QueryResultRow, SQLQueryView},
+    },
+};
+use std::collections::HashMap;
+
+pub struct SchemaDriftViewProvider;
+
+impl ViewProvider for SchemaDriftViewProvider {
+    fn name(&self) -> &'static str {
+        "Schema drift"
+    }
+
+    fn view_type(&self) -> ChDigViews {
+        ChDigViews::SchemaDrift
+    }
+
+    fn show(&self, app: &mut App, context: ContextArc, _instance: Option<&str>) {
+        if app.focus_name("schema_drift") {
+            return;
+        }
+
+        let (cluster, tables_dbtable, columns_dbtable) = {
+            let ctx = context.lock().unwrap();
+            (
+                ctx.options.clickhouse.cluster.is_some(),
+                ctx.clickhouse.get_table_name_no_history("tables"),
+                ctx.clickhouse.get_table_name_no_history("columns"),
+            )
+        };
+        // Without the cluster there is nothing to compare with
+        if !cluster {
+            app.add_layer(Dialog::info("Schema drift requires --cluster"));
+            return;
+        }
+
+        let columns = vec![
+            "database",
+            "table",
+            "hosts",
+            "definitions",
+            "column_lists",
+            "missing_on",
+        ];
+        // Column lists are hashed in the order of the columns, the positions
+        // are sorted since groupArray() over the replicas has no order
+        let query = format!(
+            r#"
+            WITH
+                (SELECT groupUniqArray(hostName()) FROM {tables}) AS all_hosts
+            SELECT
+                tables.database AS database,
+                tables.table AS table,
+                toUInt32(count()) AS hosts,
+                toUInt32(uniqExact(tables.create_hash)) AS definitions,
+                toUInt32(uniqExact(columns.columns_hash)) AS column_lists,
+                arrayStringConcat(arraySort(arrayFilter(host -> NOT has(groupArray(tables.host), host), all_hosts)), ', ') AS missing_on
+            FROM (
+                SELECT
+                    hostName() AS host,
+                    database,
+                    name AS table,
+                    cityHash64(create_table_query) AS create_hash
+                FROM {tables}
+                WHERE
+                    database NOT IN ('system', 'INFORMATION_SCHEMA', 'information_schema')
+                    AND NOT is_temporary
+            ) AS tables
+            LEFT JOIN (
+                SELECT
+                    hostName() AS host,
+                    database,
+                    table,
+                    cityHash64(arraySort(groupArray((position, name, type)))) AS columns_hash
+                FROM {columns}
+                WHERE database NOT IN ('system', 'INFORMATION_SCHEMA', 'information_schema')
+                GROUP BY host, database, table
+            ) AS columns
+                ON columns.host = tables.host AND columns.database = tables.database AND columns.table = tables.table
+            GROUP BY database, table
+            HAVING definitions > 1 OR column_lists > 1 OR missing_on != ''
+            "#,
+            tables = tables_dbtable,
+            columns = columns_dbtable,
+        );
+
+        let mut view = SQLQueryView::new(
+            context.clone(),
+            "schema_drift",
+            "definitions",
+            columns,
+            vec!["database", "table"],
+            vec!["missing_on"],
+            query,
+        )
+        .unwrap_or_else(|_| panic!("Cannot get schema_drift"));
+        view.get_inner_mut().set_on_submit(show_schema_drift_diff);
+        view.get_inner_mut()
+            .set_title("Tables that differ between the hosts");
+
+        app.present_view("schema_drift", view.with_name("schema_drift").full_screen());
+    }
+}
+
+fn show_schema_drift_diff(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
+    let row_data = row.0;
+    let mut map = HashMap::<String, String>::new();
+    columns.iter().zip(row_data.iter()).for_each(|(c, r)| {
+        let value = r.to_string();
+        map.insert(c.to_string(), value);
+    });
+
+    let database = map.get("database").cloned().unwrap_or_default();
+    let table = map.get("table").cloned().unwrap_or_default();
+
+    let context = app.user_data::<ContextArc>().unwrap().clone();
+    context
+        .lock()
+        .unwrap()
+        .worker
+        .send(true, WorkerEvent::SchemaDriftDiff(database, table));
+}
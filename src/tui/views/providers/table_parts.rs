@@ -1,9 +1,13 @@
 use super::{Presentation, QueryTableSpec, TableFilterParams};
 use crate::{
-    interpreter::{ContextArc, TextLogArguments, options::ChDigViews},
+    interpreter::{ContextArc, TextLogArguments, WorkerEvent, options::ChDigViews},
     tui::{
-        App, Dialog, DummyView, Event, LinearLayout, NamedView, Resizable, TextView, ViewProvider,
-        actions::ActionDescription, fuzzy_actions, views::sql_query_view::Row as QueryResultRow,
+        App, Dialog, DummyView, Event, LinearLayout, Nameable, NamedView, Resizable,
+        SizeConstraint, TextView, ViewProvider,
+        actions::ActionDescription,
+        fuzzy_actions,
+        style::Color,
+        views::sql_query_view::{Row as QueryResultRow, SQLQueryView, Unit},
         views::text_log_view::TextLogView,
     },
 };
@@ -25,6 +29,12 @@ impl ViewProvider for TablePartsViewProvider {
     }
 }
 
+// Widget name of the partitions of a table (see RESERVED_VIEW_NAMES).
+const TABLE_PARTITIONS_VIEW_NAME: &str = "table_partitions";
+// Shares of parts_to_delay_insert from which the partitions are colored
+const PARTS_TO_DELAY_INSERT_WARNING: f64 = 0.5;
+const PARTS_TO_DELAY_INSERT_CRITICAL: f64 = 0.8;
+
 // database/name are qualified (and re-aliased) since the tables subquery
 // exposes columns with the same names.
 const COLUMNS: &[&str] = &[
@@ -39,6 +49,9 @@ const COLUMNS: &[&str] = &[
     "modification_time",
     "active",
     "tables.uuid _table_uuid",
+    // For the part lineage (database/table are not shown in the dialogs)
+    "parts.database _database",
+    "parts.table _table",
 ];
 
 fn build_query(
@@ -147,6 +160,25 @@ fn show_part_details(app: &mut App, columns: Vec<&'static str>, row: QueryResult
     app.add_layer(Dialog::info(info).title("Part Details"));
 }
 
+fn show_part_lineage(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
+    let mut map = HashMap::new();
+    columns.iter().zip(row.0.iter()).for_each(|(c, r)| {
+        map.insert(c.to_string(), r);
+    });
+
+    let context = app.user_data::<ContextArc>().unwrap().clone();
+    context.lock().unwrap().worker.send(
+        true,
+        WorkerEvent::PartLineage(
+            map["_database"].to_string(),
+            map["_table"].to_string(),
+            map["_table_uuid"].to_string(),
+            map["name"].to_string(),
+            map["modification_time"].as_datetime().unwrap(),
+        ),
+    );
+}
+
 fn table_parts_action_callback(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
     let actions = vec![
         ActionDescription {
@@ -157,6 +189,10 @@ fn table_parts_action_callback(app: &mut App, columns: Vec<&'static str>, row: Q
             text: "Show part details",
             event: Event::Unknown(vec![]),
         },
+        ActionDescription {
+            text: "Show part lineage",
+            event: Event::Unknown(vec![]),
+        },
     ];
 
     let columns_clone = columns.clone();
@@ -169,6 +205,9 @@ fn table_parts_action_callback(app: &mut App, columns: Vec<&'static str>, row: Q
         "Show part details" => {
             show_part_details(app, columns_clone.clone(), row_clone.clone());
         }
+        "Show part lineage" => {
+            show_part_lineage(app, columns_clone.clone(), row_clone.clone());
+        }
         _ => {}
     });
 }
@@ -181,7 +220,29 @@ pub fn show_table_parts(
     presentation: Presentation,
 ) {
     let filters = TableFilterParams::new(database, table, "table_parts", "Table Parts");
+    show_filtered_table_parts(app, context, filters, presentation);
+}
+
+/// Active parts of one partition (drill-down from the partitions).
+pub fn show_partition_parts(
+    app: &mut App,
+    context: ContextArc,
+    database: String,
+    table: String,
+    partition_id: String,
+) {
+    let filters = TableFilterParams::new(Some(database), Some(table), "table_parts", "Table Parts")
+        .with_eq("partition_id", Some(partition_id))
+        .with_eq("active", Some("1".to_string()));
+    show_filtered_table_parts(app, context, filters, Presentation::Dialog);
+}
 
+fn show_filtered_table_parts(
+    app: &mut App,
+    context: ContextArc,
+    filters: TableFilterParams,
+    presentation: Presentation,
+) {
     let columns = if presentation.is_dialog() {
         super::dialog_columns(COLUMNS)
     } else {
@@ -207,3 +268,136 @@ pub fn show_table_parts(
         presentation,
     );
 }
+
+/// Active parts grouped by partition, the partitions whose number of parts
+/// approaches parts_to_delay_insert are colored ("Too many parts" is per
+/// partition).
+pub fn show_table_partitions(
+    app: &mut App,
+    context: ContextArc,
+    database: String,
+    table: String,
+    parts_to_delay_insert: u64,
+) {
+    let (cluster, parts_dbtable, clickhouse, selected_host) = {
+        let ctx = context.lock().unwrap();
+        (
+            ctx.options.clickhouse.cluster.is_some(),
+            ctx.clickhouse.get_table_name("parts"),
+            ctx.clickhouse.clone(),
+            ctx.selected_host.clone(),
+        )
+    };
+
+    let filters = TableFilterParams::new(
+        Some(database.clone()),
+        Some(table.clone()),
+        "table_partitions",
+        "Partitions",
+    );
+    let mut where_clauses = filters.build_where_clauses();
+    where_clauses.push("active".to_string());
+    super::push_host_filter(
+        &mut where_clauses,
+        &clickhouse,
+        selected_host.as_ref(),
+        false,
+    );
+
+    // Parts are counted per host (every replica has its own parts, and "Too
+    // many parts" is per host as well), and the partition shows the worst
+    // host: max over the hosts for parts, rows and bytes (i.e. one replica),
+    // with the host column in --cluster mode
+    let mut columns = vec![
+        "partition",
+        "toUInt32(max(host_parts)) parts",
+        "max(host_rows) rows",
+        "max(host_bytes) bytes",
+        "toString(min(host_min_block)) min_block",
+        "toString(max(host_max_block)) max_block",
+        "max(host_oldest_part_age) oldest_part_age",
+        "partition_id _partition_id",
+    ];
+    let columns_to_compare = if cluster && selected_host.is_none() {
+        columns.insert(1, "argMax(host, host_parts) host");
+        vec!["partition", "host"]
+    } else {
+        vec!["partition"]
+    };
+    let query = format!(
+        r#"
+        SELECT {columns}
+        FROM (
+            SELECT
+                hostName() AS host,
+                partition,
+                partition_id,
+                count() AS host_parts,
+                sum(rows) AS host_rows,
+                sum(bytes_on_disk) AS host_bytes,
+                min(min_block_number) AS host_min_block,
+                max(max_block_number) AS host_max_block,
+                toUInt64(dateDiff('second', min(modification_time), now())) AS host_oldest_part_age
+            FROM {parts}
+            WHERE {where_clause}
+            GROUP BY hostName(), partition, partition_id
+        )
+        GROUP BY partition, partition_id
+        ORDER BY parts DESC
+        "#,
+        columns = columns.join(", "),
+        parts = parts_dbtable,
+        where_clause = where_clauses.join(" AND "),
+    );
+
+    let mut view = SQLQueryView::new(
+        context,
+        TABLE_PARTITIONS_VIEW_NAME,
+        "parts",
+        columns,
+        columns_to_compare,
+        vec!["partition"],
+        query,
+    )
+    .unwrap_or_else(|_| panic!("Cannot get {}", TABLE_PARTITIONS_VIEW_NAME));
+    let inner = view.get_inner_mut();
+    inner.set_title(format!(
+        "{} (parts_to_delay_insert: {})",
+        filters.build_title(true),
+        parts_to_delay_insert
+    ));
+    inner.set_value_unit("rows", Unit::Count);
+    inner.set_value_unit("bytes", Unit::Bytes);
+    inner.set_value_unit("oldest_part_age", Unit::Seconds);
+    if parts_to_delay_insert > 0 {
+        let threshold = parts_to_delay_insert as f64;
+        inner.set_color_thresholds(
+            "parts",
+            vec![
+                (threshold * PARTS_TO_DELAY_INSERT_WARNING, Color::Yellow),
+                (threshold * PARTS_TO_DELAY_INSERT_CRITICAL, Color::Red),
+            ],
+        );
+    }
+    inner.set_on_submit(
+        move |app: &mut App, columns: Vec<&'static str>, row: QueryResultRow| {
+            let Some(partition_id) = columns
+                .iter()
+                .position(|c| *c == "_partition_id")
+                .map(|i| row.0[i].to_string())
+            else {
+                return;
+            };
+            let context = app.user_data::<ContextArc>().unwrap().clone();
+            show_partition_parts(app, context, database.clone(), table.clone(), partition_id);
+        },
+    );
+
+    app.add_layer(
+        Dialog::around(
+            view.with_name(TABLE_PARTITIONS_VIEW_NAME)
+                .resized(SizeConstraint::AtLeast(140), SizeConstraint::AtLeast(30)),
+        )
+        .title("Partitions"),
+    );
+}
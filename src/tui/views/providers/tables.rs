@@ -1,14 +1,32 @@
+use super::Presentation;
 use crate::{
+    common::{DependencyDirection, DependencyNode, TableOverview},
     interpreter::{ClickHouseAvailableQuirks, ContextArc, WorkerEvent, options::ChDigViews},
     tui::{
-        App, Event, Nameable, Navigation, Resizable, ViewProvider,
+        App, Dialog, Event, Nameable, Navigation, OnEventView, Resizable, Scrollable,
+        SizeConstraint, StyledString, TextView, ViewProvider,
         actions::ActionDescription,
-        fuzzy_actions,
-        views::sql_query_view::{Row as QueryResultRow, SQLQueryView},
+        fuzzy_actions, highlight_sql,
+        style::{Color, Style},
+        views::queries_view::{QueriesView, Type as QueriesType},
+        views::sql_query_view::{Row as QueryResultRow, SQLQueryView, Unit},
     },
 };
 use std::collections::HashMap;
 
+// Widget name of the "queries that used this table" view (see RESERVED_VIEW_NAMES).
+const TABLE_QUERIES_VIEW_NAME: &str = "table_queries";
+// Widget name of the columns of a table (see RESERVED_VIEW_NAMES).
+const TABLE_COLUMNS_VIEW_NAME: &str = "table_columns";
+// Widget name of the skip indices and projections of a table (see RESERVED_VIEW_NAMES).
+const TABLE_INDICES_VIEW_NAME: &str = "table_indices";
+// Prefix of the widget names of the dependencies graphs (one per table, so that
+// the graph of an object opened from another graph gets its own name, see
+// RESERVED_VIEW_NAME_PREFIXES).
+const TABLE_DEPENDENCIES_VIEW_NAME: &str = "table_dependencies";
+// Loggers of the table (by name and by UUID) for the table logs
+const TABLE_LOGGER_NAMES_PATTERNS: &[&str] = &["%{database}.{table}%", "%{_uuid_raw}%"];
+
 pub struct TablesViewProvider;
 
 impl ViewProvider for TablesViewProvider {
@@ -111,10 +129,9 @@ impl ViewProvider for TablesViewProvider {
         )
         .unwrap_or_else(|_| panic!("Cannot get tables"));
 
-        let logger_names_patterns = vec!["%{database}.{table}%", "%{_uuid_raw}%"];
         let tables_action_callback =
             move |app: &mut App, columns: Vec<&'static str>, row: QueryResultRow| {
-                show_table_actions(app, columns, row, &logger_names_patterns);
+                show_table_actions(app, columns, row, TABLE_LOGGER_NAMES_PATTERNS);
             };
         view.get_inner_mut().set_on_submit(tables_action_callback);
         view.get_inner_mut().set_title("Tables");
@@ -130,6 +147,10 @@ fn show_table_actions(
     logger_names_patterns: &[&'static str],
 ) {
     let actions = vec![
+        ActionDescription {
+            text: "Show table overview",
+            event: Event::Unknown(vec![]),
+        },
         ActionDescription {
             text: "Show table logs",
             event: Event::Unknown(vec![]),
@@ -146,6 +167,26 @@ fn show_table_actions(
             text: "Show table parts",
             event: Event::Unknown(vec![]),
         },
+        ActionDescription {
+            text: "Show table partitions",
+            event: Event::Unknown(vec![]),
+        },
+        ActionDescription {
+            text: "Show table columns",
+            event: Event::Unknown(vec![]),
+        },
+        ActionDescription {
+            text: "Show table indices and projections",
+            event: Event::Unknown(vec![]),
+        },
+        ActionDescription {
+            text: "Show table dependencies",
+            event: Event::Unknown(vec![]),
+        },
+        ActionDescription {
+            text: "Show table MergeTree settings",
+            event: Event::Unknown(vec![]),
+        },
         ActionDescription {
             text: "Show asynchronous inserts",
             event: Event::Unknown(vec![]),
@@ -162,6 +203,14 @@ fn show_table_actions(
             text: "Show table part log",
             event: Event::Unknown(vec![]),
         },
+        ActionDescription {
+            text: "Show table growth",
+            event: Event::Unknown(vec![]),
+        },
+        ActionDescription {
+            text: "Show queries that used this table",
+            event: Event::Unknown(vec![]),
+        },
         ActionDescription {
             text: "SHOW CREATE TABLE",
             event: Event::Unknown(vec![]),
@@ -177,6 +226,9 @@ fn show_table_actions(
     //   select table from system.columns where name = 'table' and database = 'system'
     //
     fuzzy_actions(app, actions, move |app, selected| match selected.as_str() {
+        "Show table overview" => {
+            show_table_overview_for_row(app, columns_clone.clone(), row_clone.clone());
+        }
         "Show table logs" => {
             show_table_logs(
                 app,
@@ -188,6 +240,21 @@ fn show_table_actions(
         "Show table parts" => {
             show_table_parts(app, columns_clone.clone(), row_clone.clone());
         }
+        "Show table partitions" => {
+            show_table_partitions(app, columns_clone.clone(), row_clone.clone());
+        }
+        "Show table columns" => {
+            show_table_columns(app, columns_clone.clone(), row_clone.clone());
+        }
+        "Show table indices and projections" => {
+            show_table_indices(app, columns_clone.clone(), row_clone.clone());
+        }
+        "Show table dependencies" => {
+            show_table_dependencies_for_row(app, columns_clone.clone(), row_clone.clone());
+        }
+        "Show table MergeTree settings" => {
+            show_table_merge_tree_settings(app, columns_clone.clone(), row_clone.clone());
+        }
         "Show asynchronous inserts" => {
             show_table_asynchronous_inserts(app, columns_clone.clone(), row_clone.clone());
         }
@@ -206,6 +273,12 @@ fn show_table_actions(
         "Show table part log" => {
             show_table_part_log(app, columns_clone.clone(), row_clone.clone());
         }
+        "Show table growth" => {
+            show_table_growth(app, columns_clone.clone(), row_clone.clone());
+        }
+        "Show queries that used this table" => {
+            show_table_queries(app, columns_clone.clone(), row_clone.clone());
+        }
         "SHOW CREATE TABLE" => {
             show_create_table(app, columns_clone.clone(), row_clone.clone());
         }
@@ -213,6 +286,125 @@ fn show_table_actions(
     });
 }
 
+fn show_table_overview_for_row(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
+    let row_data = row.0;
+    let mut map = HashMap::<String, String>::new();
+    columns.iter().zip(row_data.iter()).for_each(|(c, r)| {
+        let value = r.to_string();
+        map.insert(c.to_string(), value);
+    });
+
+    let database = map.get("database").cloned().unwrap_or_default();
+    let table = map.get("table").cloned().unwrap_or_default();
+
+    let context = app.user_data::<ContextArc>().unwrap().clone();
+    context
+        .lock()
+        .unwrap()
+        .worker
+        .send(true, WorkerEvent::TableOverview(database, table));
+}
+
+/// Everything about the table on one page, the keys open the detailed
+/// dialogs of the sections.
+pub fn show_table_overview(app: &mut App, overview: TableOverview) {
+    let mut text = StyledString::new();
+    for (i, line) in overview.report().lines().enumerate() {
+        if i > 0 {
+            text.append_plain("\n");
+        }
+        // Warnings
+        if line.ends_with('!') {
+            text.append_styled(line, Style::new().fg(Color::Red));
+        } else {
+            text.append_plain(line);
+        }
+    }
+    text.append_plain("\n");
+    text.append(
+        highlight_sql(&overview.create_statement)
+            .unwrap_or_else(|_| StyledString::plain(overview.create_statement.clone())),
+    );
+
+    let title = format!("Table overview: {}.{}", overview.database, overview.table);
+    let database = overview.database;
+    let table = overview.table;
+    let context = app.user_data::<ContextArc>().unwrap().clone();
+
+    // Every section opens its detailed dialog
+    type SectionDialog = fn(&mut App, ContextArc, String, String);
+    let sections: [(char, SectionDialog); 7] = [
+        ('p', |app, context, database, table| {
+            super::table_parts::show_table_parts(
+                app,
+                context,
+                Some(database),
+                Some(table),
+                Presentation::Dialog,
+            )
+        }),
+        ('P', |_app, context, database, table| {
+            context
+                .lock()
+                .unwrap()
+                .worker
+                .send(true, WorkerEvent::TablePartitions(database, table));
+        }),
+        ('m', |app, context, database, table| {
+            super::merges::show_merges(
+                app,
+                context,
+                Some(database),
+                Some(table),
+                Presentation::Dialog,
+            )
+        }),
+        ('u', |app, context, database, table| {
+            super::mutations::show_mutations(
+                app,
+                context,
+                Some(database),
+                Some(table),
+                Presentation::Dialog,
+            )
+        }),
+        ('l', |app, context, database, table| {
+            super::part_log::show_part_log(
+                app,
+                context,
+                Some(database),
+                Some(table),
+                None,
+                Presentation::Dialog,
+            )
+        }),
+        ('i', |app, context, database, table| {
+            super::asynchronous_inserts::show_asynchronous_inserts(
+                app,
+                context,
+                Some(database),
+                Some(table),
+                Presentation::Dialog,
+            )
+        }),
+        ('c', |_app, context, database, table| {
+            context
+                .lock()
+                .unwrap()
+                .worker
+                .send(true, WorkerEvent::ShowCreateTable(database, table));
+        }),
+    ];
+    let mut view = OnEventView::new(TextView::new(text).scrollable());
+    for (key, show) in sections {
+        let (context, database, table) = (context.clone(), database.clone(), table.clone());
+        view = view.on_event(key, move |app: &mut App| {
+            show(app, context.clone(), database.clone(), table.clone());
+        });
+    }
+    app.add_layer(Dialog::around(view).title(title));
+}
+
 fn show_create_table(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
     let row_data = row.0;
     let mut map = HashMap::<String, String>::new();
@@ -235,6 +427,47 @@ fn show_create_table(app: &mut App, columns: Vec<&'static str>, row: QueryResult
         .send(true, WorkerEvent::ShowCreateTable(database, table));
 }
 
+/// Queries from system.query_log that accessed the table (as a table or a
+/// view), over the global interval.
+fn show_table_queries(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
+    let row_data = row.0;
+    let mut map = HashMap::<String, String>::new();
+    columns.iter().zip(row_data.iter()).for_each(|(c, r)| {
+        let value = r.to_string();
+        map.insert(c.to_string(), value);
+    });
+
+    let database = map.get("database").cloned().unwrap_or_default();
+    let table = map.get("table").cloned().unwrap_or_default();
+    let Some(condition) = super::accessed_objects_condition(&format!("{}.{}", database, table))
+    else {
+        return;
+    };
+
+    let context = app.user_data::<ContextArc>().unwrap().clone();
+    {
+        let mut ctx = context.lock().unwrap();
+        ctx.set_queries_condition(TABLE_QUERIES_VIEW_NAME, condition);
+        ctx.set_current_view(ChDigViews::LastQueries);
+    }
+
+    // Replace the view of a previously selected table in place
+    app.focus_name(TABLE_QUERIES_VIEW_NAME);
+    let title = format!("Queries that used {}.{}", database, table);
+    app.present_view(
+        TABLE_QUERIES_VIEW_NAME,
+        QueriesView::new(
+            context.clone(),
+            QueriesType::LastQueryLog,
+            TABLE_QUERIES_VIEW_NAME,
+            &title,
+        )
+        .with_name(TABLE_QUERIES_VIEW_NAME)
+        .full_screen(),
+    );
+    context.lock().unwrap().trigger_view_refresh();
+}
+
 fn show_table_logs(
     app: &mut App,
     columns: Vec<&'static str>,
@@ -315,6 +548,499 @@ fn show_table_parts(app: &mut App, columns: Vec<&'static str>, row: QueryResultR
         .send(true, WorkerEvent::TableParts(database, table));
 }
 
+/// Storage of the columns over the active parts (system.parts_columns), the
+/// columns that are not there (e.g. the table has no parts on this host) fall
+/// back to system.columns. In --cluster mode the sizes are of the largest
+/// replica (per column), not the sum over the replicas.
+fn show_table_columns(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
+    let row_data = row.0;
+    let mut map = HashMap::<String, String>::new();
+    columns.iter().zip(row_data.iter()).for_each(|(c, r)| {
+        let value = r.to_string();
+        map.insert(c.to_string(), value);
+    });
+
+    let database = map.get("database").cloned().unwrap_or_default();
+    let table = map.get("table").cloned().unwrap_or_default();
+
+    let context = app.user_data::<ContextArc>().unwrap().clone();
+    let (columns_dbtable, parts_columns_dbtable) = {
+        let ctx = context.lock().unwrap();
+        (
+            ctx.clickhouse.get_table_name_no_history("columns"),
+            ctx.clickhouse.get_table_name_no_history("parts_columns"),
+        )
+    };
+    let table_filter = format!(
+        "database = '{}' AND table = '{}'",
+        database.replace('\'', "''"),
+        table.replace('\'', "''")
+    );
+
+    let columns = vec![
+        "column",
+        "type",
+        "codec",
+        "compressed",
+        "uncompressed",
+        "ratio",
+        "share",
+        "bar",
+    ];
+    let query = format!(
+        r#"
+        SELECT
+            columns.name AS column,
+            columns.type AS type,
+            columns.codec AS codec,
+            if(parts_columns.column != '', parts_columns.compressed, columns.compressed) AS compressed,
+            if(parts_columns.column != '', parts_columns.uncompressed, columns.uncompressed) AS uncompressed,
+            round(uncompressed / greatest(compressed, 1), 2) AS ratio,
+            round(100 * compressed / greatest(sum(compressed) OVER (), 1), 2) AS share,
+            compressed AS bar
+        FROM (
+            SELECT
+                name,
+                any(type) AS type,
+                any(codec) AS codec,
+                max(host_compressed) AS compressed,
+                max(host_uncompressed) AS uncompressed
+            FROM (
+                SELECT
+                    name,
+                    any(type) AS type,
+                    any(compression_codec) AS codec,
+                    sum(data_compressed_bytes) AS host_compressed,
+                    sum(data_uncompressed_bytes) AS host_uncompressed
+                FROM {columns_dbtable}
+                WHERE {table_filter}
+                GROUP BY hostName(), name
+            )
+            GROUP BY name
+        ) AS columns
+        LEFT JOIN (
+            SELECT
+                column,
+                max(host_compressed) AS compressed,
+                max(host_uncompressed) AS uncompressed
+            FROM (
+                SELECT
+                    column,
+                    sum(column_data_compressed_bytes) AS host_compressed,
+                    sum(column_data_uncompressed_bytes) AS host_uncompressed
+                FROM {parts_columns_dbtable}
+                WHERE active AND {table_filter}
+                GROUP BY hostName(), column
+            )
+            GROUP BY column
+        ) AS parts_columns ON parts_columns.column = columns.name
+        ORDER BY compressed DESC
+        "#,
+        columns_dbtable = columns_dbtable,
+        parts_columns_dbtable = parts_columns_dbtable,
+        table_filter = table_filter,
+    );
+
+    let mut view = SQLQueryView::new(
+        context,
+        TABLE_COLUMNS_VIEW_NAME,
+        "compressed",
+        columns,
+        vec!["column"],
+        vec!["type", "codec"],
+        query,
+    )
+    .unwrap_or_else(|_| panic!("Cannot get {}", TABLE_COLUMNS_VIEW_NAME));
+    let inner = view.get_inner_mut();
+    inner.set_title(format!("Columns of {}.{}", database, table));
+    inner.set_value_unit("compressed", Unit::Bytes);
+    inner.set_value_unit("uncompressed", Unit::Bytes);
+    inner.set_bar_columns(vec![("bar", "compressed")]);
+
+    app.add_layer(
+        Dialog::around(
+            view.with_name(TABLE_COLUMNS_VIEW_NAME)
+                .resized(SizeConstraint::AtLeast(140), SizeConstraint::AtLeast(30)),
+        )
+        .title("Columns"),
+    );
+}
+
+/// Data skipping indices and projections of the table with their sizes (of
+/// the largest replica in --cluster mode) and the number of queries that used
+/// them over the view interval (so that the unused ones, which are red, can be
+/// dropped). Parts and rows are of the projections only, granularity - of the
+/// indices only.
+fn show_table_indices(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
+    let row_data = row.0;
+    let mut map = HashMap::<String, String>::new();
+    columns.iter().zip(row_data.iter()).for_each(|(c, r)| {
+        let value = r.to_string();
+        map.insert(c.to_string(), value);
+    });
+
+    let database = map.get("database").cloned().unwrap_or_default();
+    let table = map.get("table").cloned().unwrap_or_default();
+
+    let context = app.user_data::<ContextArc>().unwrap().clone();
+    let (indices_dbtable, projection_parts_dbtable, query_log_dbtable, has_skip_indices) = {
+        let ctx = context.lock().unwrap();
+        (
+            ctx.clickhouse
+                .get_table_name_no_history("data_skipping_indices"),
+            ctx.clickhouse.get_table_name_no_history("projection_parts"),
+            ctx.clickhouse.get_log_table_name("query_log"),
+            ctx.clickhouse.has_query_log_skip_indices(),
+        )
+    };
+    let table_filter = format!(
+        "database = '{}' AND table = '{}'",
+        database.replace('\'', "''"),
+        table.replace('\'', "''")
+    );
+    // query_log has them as "database.table.name", each part back quoted if
+    // needed (like the tables)
+    let table_name = format!(
+        "concat(backQuoteIfNeed('{}'), '.', backQuoteIfNeed('{}'))",
+        database.replace('\'', "''"),
+        table.replace('\'', "''")
+    );
+    let used_objects = if has_skip_indices {
+        "arrayConcat(projections, skip_indices)"
+    } else {
+        "projections"
+    };
+
+    let (with_prelude, mut where_clauses) =
+        super::log_time_window(&context, TABLE_INDICES_VIEW_NAME);
+    where_clauses.push("type = 'QueryFinish'".to_string());
+    where_clauses.push("has(tables, table_name_)".to_string());
+
+    let columns = vec![
+        "kind",
+        "name",
+        "type",
+        "expression",
+        "granularity",
+        "parts",
+        "rows",
+        "compressed",
+        "uncompressed",
+        "queries",
+        "last_used",
+        "_unused",
+    ];
+    let query = format!(
+        r#"
+        {with_prelude}, {table_name} AS table_name_, concat(table_name_, '.') AS prefix_
+        SELECT
+            objects.kind AS kind,
+            objects.name AS name,
+            objects.type AS type,
+            objects.expression AS expression,
+            objects.granularity AS granularity,
+            objects.parts AS parts,
+            objects.rows AS rows,
+            objects.compressed AS compressed,
+            objects.uncompressed AS uncompressed,
+            toUInt64(usage.queries) AS queries,
+            usage.last_used AS last_used,
+            toUInt8(queries = 0) AS _unused
+        FROM (
+            SELECT
+                'index' AS kind,
+                name,
+                any(type) AS type,
+                any(expression) AS expression,
+                toString(any(granularity)) AS granularity,
+                '' AS parts,
+                '' AS rows,
+                toUInt64(max(host_compressed)) AS compressed,
+                toUInt64(max(host_uncompressed)) AS uncompressed
+            FROM (
+                SELECT
+                    name,
+                    any(type_full) AS type,
+                    any(expr) AS expression,
+                    any(granularity) AS granularity,
+                    sum(data_compressed_bytes) AS host_compressed,
+                    sum(data_uncompressed_bytes) AS host_uncompressed
+                FROM {indices_dbtable}
+                WHERE {table_filter}
+                GROUP BY hostName(), name
+            )
+            GROUP BY name
+            UNION ALL
+            SELECT
+                'projection' AS kind,
+                name,
+                '' AS type,
+                '' AS expression,
+                '' AS granularity,
+                toString(max(host_parts)) AS parts,
+                toString(max(host_rows)) AS rows,
+                toUInt64(max(host_compressed)) AS compressed,
+                toUInt64(max(host_uncompressed)) AS uncompressed
+            FROM (
+                SELECT
+                    name,
+                    count() AS host_parts,
+                    sum(rows) AS host_rows,
+                    sum(data_compressed_bytes) AS host_compressed,
+                    sum(data_uncompressed_bytes) AS host_uncompressed
+                FROM {projection_parts_dbtable}
+                WHERE active AND {table_filter}
+                GROUP BY hostName(), name
+            )
+            GROUP BY name
+        ) AS objects
+        LEFT JOIN (
+            SELECT
+                substring(object, length(prefix_) + 1) AS name,
+                count() AS queries,
+                max(event_time) AS last_used
+            FROM (
+                SELECT
+                    arrayJoin(arrayFilter(x -> startsWith(x, prefix_), {used_objects})) AS object,
+                    event_time
+                FROM {query_log_dbtable}
+                WHERE {where_clause}
+            )
+            GROUP BY name
+        ) AS usage ON usage.name = backQuoteIfNeed(objects.name)
+        "#,
+        with_prelude = with_prelude,
+        table_name = table_name,
+        indices_dbtable = indices_dbtable,
+        projection_parts_dbtable = projection_parts_dbtable,
+        table_filter = table_filter,
+        used_objects = used_objects,
+        query_log_dbtable = query_log_dbtable,
+        where_clause = where_clauses.join(" AND "),
+    );
+
+    let mut view = SQLQueryView::new(
+        context,
+        TABLE_INDICES_VIEW_NAME,
+        "compressed",
+        columns,
+        vec!["kind", "name"],
+        vec!["expression"],
+        query,
+    )
+    .unwrap_or_else(|_| panic!("Cannot get {}", TABLE_INDICES_VIEW_NAME));
+    let inner = view.get_inner_mut();
+    inner.set_title(format!("Indices and projections of {}.{}", database, table));
+    inner.set_on_submit(super::query_result_show_row);
+    inner.set_value_unit("compressed", Unit::Bytes);
+    inner.set_value_unit("uncompressed", Unit::Bytes);
+    inner.set_color_thresholds("_unused", vec![(1., Color::Red)]);
+
+    app.add_layer(
+        Dialog::around(
+            view.with_name(TABLE_INDICES_VIEW_NAME)
+                .resized(SizeConstraint::AtLeast(140), SizeConstraint::AtLeast(30)),
+        )
+        .title("Indices and projections"),
+    );
+}
+
+fn show_table_merge_tree_settings(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
+    let row_data = row.0;
+    let mut map = HashMap::<String, String>::new();
+    columns.iter().zip(row_data.iter()).for_each(|(c, r)| {
+        let value = r.to_string();
+        map.insert(c.to_string(), value);
+    });
+
+    let database = map.get("database").cloned().unwrap_or_default();
+    let table = map.get("table").cloned().unwrap_or_default();
+
+    // The overrides are parsed from the CREATE statement by the worker
+    let context = app.user_data::<ContextArc>().unwrap().clone();
+    context
+        .lock()
+        .unwrap()
+        .worker
+        .send(true, WorkerEvent::TableMergeTreeSettings(database, table));
+}
+
+/// Bytes and rows added/removed over the interval (see get_table_growth())
+/// with the projected time to full.
+fn show_table_growth(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
+    let row_data = row.0;
+    let mut map = HashMap::<String, String>::new();
+    columns.iter().zip(row_data.iter()).for_each(|(c, r)| {
+        let value = r.to_string();
+        map.insert(c.to_string(), value);
+    });
+
+    let database = map.get("database").cloned().unwrap_or_default();
+    let table = map.get("table").cloned().unwrap_or_default();
+
+    // Dialog borders + y-axis label (as for show_log_chart())
+    let buckets = app.screen_size().width.saturating_sub(24).clamp(16, 240) as u32;
+    let context = app.user_data::<ContextArc>().unwrap().clone();
+    let mut ctx = context.lock().unwrap();
+    let (start, end) = ctx.view_interval("tables");
+    ctx.worker.send(
+        true,
+        WorkerEvent::TableGrowth(database, table, start, end, buckets),
+    );
+}
+
+fn show_table_dependencies_for_row(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
+    let row_data = row.0;
+    let mut map = HashMap::<String, String>::new();
+    columns.iter().zip(row_data.iter()).for_each(|(c, r)| {
+        let value = r.to_string();
+        map.insert(c.to_string(), value);
+    });
+
+    let database = map.get("database").cloned().unwrap_or_default();
+    let table = map.get("table").cloned().unwrap_or_default();
+
+    // The graph is built from all the tables, so it goes through the worker
+    let context = app.user_data::<ContextArc>().unwrap().clone();
+    context
+        .lock()
+        .unwrap()
+        .worker
+        .send(true, WorkerEvent::TableDependencies(database, table));
+}
+
+/// Upstream and downstream objects of the table (see dependencies_tree()),
+/// Enter opens the actions of the selected one.
+pub fn show_table_dependencies(
+    app: &mut App,
+    context: ContextArc,
+    database: String,
+    table: String,
+    nodes: Vec<DependencyNode>,
+) {
+    let dbtable = context
+        .lock()
+        .unwrap()
+        .clickhouse
+        .get_table_name_no_history("tables");
+
+    let values = nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| {
+            let (direction, arrow) = match node.direction {
+                DependencyDirection::Selected => ("", ""),
+                DependencyDirection::Upstream => ("upstream", "<- "),
+                DependencyDirection::Downstream => ("downstream", "-> "),
+            };
+            let label = format!(
+                "{}{}{}.{}",
+                "  ".repeat(node.depth.saturating_sub(1)),
+                arrow,
+                node.table.0,
+                node.table.1
+            );
+            format!(
+                "({}, '{}', '{}', '{}', '{}', '{}')",
+                i,
+                direction,
+                label.replace('\'', "''"),
+                node.relation,
+                node.table.0.replace('\'', "''"),
+                node.table.1.replace('\'', "''"),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",\n            ");
+
+    let columns = vec![
+        "direction",
+        "node",
+        "relation",
+        "engine",
+        "database",
+        "table",
+        "total_bytes",
+        "_uuid",
+    ];
+    // Objects that are not in system.tables (e.g. the tables of the remote
+    // Distributed) have an empty engine
+    let query = format!(
+        r#"
+        SELECT
+            nodes.direction AS direction,
+            nodes.node AS node,
+            nodes.relation AS relation,
+            tables.engine AS engine,
+            nodes.database AS database,
+            nodes.table AS table,
+            assumeNotNull(tables.total_bytes) AS total_bytes,
+            tables.uuid AS _uuid
+        FROM values(
+            'position UInt32, direction String, node String, relation String, database String, table String',
+            {values}
+        ) AS nodes
+        LEFT JOIN (
+            SELECT DISTINCT ON (database, name) database, name, engine, total_bytes, uuid
+            FROM {dbtable}
+        ) AS tables ON tables.database = nodes.database AND tables.name = nodes.table
+        ORDER BY nodes.position
+        "#,
+        values = values,
+        dbtable = dbtable,
+    );
+
+    let view_name = format!("{}_{}_{}", TABLE_DEPENDENCIES_VIEW_NAME, database, table);
+    let mut view = SQLQueryView::new(
+        context,
+        &view_name,
+        "node",
+        columns,
+        vec!["database", "table"],
+        vec!["node"],
+        query,
+    )
+    .unwrap_or_else(|_| panic!("Cannot get {}", view_name));
+    let inner = view.get_inner_mut();
+    inner.set_title(format!("Dependencies of {}.{}", database, table));
+    // The tree order of the query
+    inner.set_query_order();
+    inner.set_value_unit("total_bytes", Unit::Bytes);
+    inner.set_on_submit(
+        |app: &mut App, columns: Vec<&'static str>, row: QueryResultRow| {
+            show_table_actions(app, columns, row, TABLE_LOGGER_NAMES_PATTERNS);
+        },
+    );
+
+    app.add_layer(
+        Dialog::around(
+            view.with_name(view_name)
+                .resized(SizeConstraint::AtLeast(140), SizeConstraint::AtLeast(20)),
+        )
+        .title("Dependencies"),
+    );
+}
+
+fn show_table_partitions(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
+    let row_data = row.0;
+    let mut map = HashMap::<String, String>::new();
+    columns.iter().zip(row_data.iter()).for_each(|(c, r)| {
+        let value = r.to_string();
+        map.insert(c.to_string(), value);
+    });
+
+    let database = map.get("database").cloned().unwrap_or_default();
+    let table = map.get("table").cloned().unwrap_or_default();
+
+    // parts_to_delay_insert of the table is required
+    let context = app.user_data::<ContextArc>().unwrap().clone();
+    context
+        .lock()
+        .unwrap()
+        .worker
+        .send(true, WorkerEvent::TablePartitions(database, table));
+}
+
 fn show_table_asynchronous_inserts(app: &mut App, columns: Vec<&'static str>, row: QueryResultRow) {
     let row_data = row.0;
     let mut map = HashMap::<String, String>::new();
@@ -12,11 +12,11 @@ use std::collections::{HashMap, HashSet};
 use std::mem::take;
 use std::sync::{Arc, Mutex};
 
-use crate::common::RelativeDateTime;
+use crate::common::{BenchmarkSpec, RelativeDateTime, analyze_query, query_analysis_report};
 use crate::interpreter::{
     BackgroundRunner, ContextArc, Query, TextLogArguments, WorkerEvent,
     clickhouse::{Columns, QueriesFilter, TraceType},
-    options::ViewOptions,
+    options::{ProfileEventColumn, ViewOptions},
 };
 use crate::tui::app::App;
 use crate::tui::component::{Canvas, Component, DummyView, Nameable, NamedView, OnEventView};
@@ -29,8 +29,9 @@ use crate::tui::resize::{Resizable, SizeConstraint};
 use crate::tui::scroll::Scrollable;
 use crate::tui::style::{Color, Modifier, Style, StyledString};
 use crate::tui::text::TextView;
-use crate::tui::views::query_view::QueryView;
-use crate::tui::views::sql_query_view::SQLQueryView;
+use crate::tui::views::providers::accessed_objects_condition;
+use crate::tui::views::query_view::{QueryView, format_profile_event, format_profile_event_rate};
+use crate::tui::views::sql_query_view::{SQLQueryView, Unit};
 use crate::tui::views::table_view::{TableColumn, TableView, TableViewItem};
 use crate::tui::views::text_log_view::TextLogView;
 use crate::utils::{edit_query, find_common_hostname_prefix_and_suffix, get_query};
@@ -39,9 +40,57 @@ use crate::utils::{edit_query, find_common_hostname_prefix_and_suffix, get_query
 // error, so 1 second should be enough.
 const QUERY_TIME_DRIFT_BUFFER_SECONDS: i64 = 1;
 
+// Widget names of the query threads dialogs (see RESERVED_VIEW_NAMES).
+const QUERY_THREADS_VIEW_NAME: &str = "query_threads";
+const QUERY_THREAD_PROFILE_EVENTS_VIEW_NAME: &str = "query_thread_profile_events";
+
 // count() OVER (PARTITION BY initial_query_id)
 type QueryKey = (String, String); // (query_id, host_name)
 
+/// Asks for the number of runs and settings variants, and benchmarks the
+/// query with them.
+fn show_benchmark_prompt(
+    app: &mut App,
+    database: String,
+    query: String,
+    settings: Arc<HashMap<String, String>>,
+) {
+    let benchmark_cb = move |app: &mut App, text: &str| {
+        app.pop_layer();
+        let spec = match BenchmarkSpec::parse(text) {
+            Ok(spec) => spec,
+            Err(err) => {
+                app.add_layer(Dialog::info(err.to_string()));
+                return;
+            }
+        };
+        let context = app.user_data::<ContextArc>().unwrap().clone();
+        let owner = context.lock().unwrap().worker.event_owner();
+        context.lock().unwrap().worker.send_owned(
+            &owner,
+            true,
+            WorkerEvent::BenchmarkQuery(database.clone(), query.clone(), settings.clone(), spec),
+        );
+
+        // The dialog holds the EventOwner of the benchmark, dismissing it
+        // (Cancel/Esc/q) aborts the runs
+        app.add_layer(
+            Dialog::text("Benchmarking...")
+                .title("Please wait")
+                .button("Cancel", move |app: &mut App| {
+                    let _ = &owner;
+                    app.pop_layer();
+                })
+                .with_name("benchmark_progress"),
+        );
+    };
+    show_bottom_prompt(
+        app,
+        "runs [setting=value ...] [| setting=value ...]: ",
+        benchmark_cb,
+    );
+}
+
 fn query_key(q: &Query) -> QueryKey {
     (q.query_id.clone(), q.host_name.clone())
 }
@@ -120,15 +169,18 @@ pub enum QueriesColumn {
     LogComment,
     Exception,
     Query,
+    // ProfileEvents['X'] (`profile_events` of the view in the config)
+    ProfileEvent(ProfileEventColumn),
 }
 
 /// Stable label for each user-configurable queries column. Matches the header
 /// strings passed to `TableView::add_column` so the settings dialog can show
 /// exactly what the user sees in the table. `Selection` is excluded — it is
-/// toggled implicitly when the user selects rows.
+/// toggled implicitly when the user selects rows, and so are the ProfileEvents
+/// ones — they are configured per view.
 pub fn query_column_id(column: QueriesColumn) -> Option<&'static str> {
     Some(match column {
-        QueriesColumn::Selection => return None,
+        QueriesColumn::Selection | QueriesColumn::ProfileEvent(_) => return None,
         QueriesColumn::HostName => "host",
         QueriesColumn::SubQueries => "Q#",
         QueriesColumn::Cpu => "cpu",
@@ -278,6 +330,13 @@ impl TableViewItem<QueriesColumn> for Query {
                 .unwrap_or_default(),
             QueriesColumn::Exception => self.exception.replace('\n', " "),
             QueriesColumn::Query => self.normalized_query.clone(),
+            QueriesColumn::ProfileEvent(column) => {
+                if column.rate {
+                    format_profile_event_rate(column.name, self.profile_event_rate(column.name))
+                } else {
+                    format_profile_event(column.name, self.profile_event(column.name))
+                }
+            }
         }
     }
 
@@ -315,6 +374,15 @@ impl TableViewItem<QueriesColumn> for Query {
                 .cmp(&other.settings.get("log_comment")),
             QueriesColumn::Exception => self.exception.cmp(&other.exception),
             QueriesColumn::Query => self.normalized_query.cmp(&other.normalized_query),
+            QueriesColumn::ProfileEvent(column) => {
+                if column.rate {
+                    self.profile_event_rate(column.name)
+                        .total_cmp(&other.profile_event_rate(column.name))
+                } else {
+                    self.profile_event(column.name)
+                        .cmp(&other.profile_event(column.name))
+                }
+            }
         }
     }
 
@@ -723,6 +791,38 @@ impl QueriesView {
         Ok(Some(EventResult::consumed()))
     }
 
+    fn action_show_trace_spans(&mut self) -> Result<Option<EventResult>> {
+        let (query_ids, min_query_start_microseconds, max_query_end_microseconds) =
+            self.get_query_ids()?;
+        let mut context_locked = self.context.lock().unwrap();
+        context_locked.worker.send(
+            true,
+            WorkerEvent::ShowTraceSpans(
+                query_ids,
+                min_query_start_microseconds,
+                max_query_end_microseconds,
+            ),
+        );
+        Ok(Some(EventResult::consumed()))
+    }
+
+    fn action_query_views_chain(&mut self) -> Result<Option<EventResult>> {
+        let (query_ids, min_query_start_microseconds, max_query_end_microseconds) =
+            self.get_query_ids()?;
+        let max_query_end_with_buffer = max_query_end_microseconds.unwrap_or(Local::now())
+            + TimeDelta::seconds(QUERY_TIME_DRIFT_BUFFER_SECONDS);
+        let mut context_locked = self.context.lock().unwrap();
+        context_locked.worker.send(
+            true,
+            WorkerEvent::ShowQueryViewsChain(
+                query_ids,
+                min_query_start_microseconds,
+                max_query_end_with_buffer,
+            ),
+        );
+        Ok(Some(EventResult::consumed()))
+    }
+
     fn action_show_flamegraph(
         &mut self,
         tui: bool,
@@ -741,6 +841,12 @@ impl QueriesView {
     }
 
     fn action_query_profile_events(&mut self) -> Result<Option<EventResult>> {
+        let descriptions = self
+            .context
+            .lock()
+            .unwrap()
+            .clickhouse
+            .metric_descriptions();
         // Check if multiple queries are selected
         if self.selected_query_ids.len() > 1 {
             // Get the queries for diff view
@@ -762,7 +868,7 @@ impl QueriesView {
                 .send(Box::new(move |app: &mut App| {
                     app.add_layer(
                         Dialog::around(
-                            QueryView::new_diff(queries, "process")
+                            QueryView::new_diff(queries, descriptions, "process")
                                 .resized(SizeConstraint::AtLeast(120), SizeConstraint::AtLeast(35)),
                         )
                         .title("Profile Events Diff"),
@@ -778,7 +884,7 @@ impl QueriesView {
                 .ui_sink
                 .send(Box::new(move |app: &mut App| {
                     app.add_layer(Dialog::around(
-                        QueryView::new(selected_query, "process")
+                        QueryView::new(selected_query, descriptions, "process")
                             .resized(SizeConstraint::AtLeast(120), SizeConstraint::AtLeast(35)),
                     ));
                 }))
@@ -794,6 +900,17 @@ impl QueriesView {
         })))
     }
 
+    fn action_query_analysis(&mut self) -> Result<Option<EventResult>> {
+        let selected_query = self.get_selected_query()?;
+        let findings = analyze_query(&selected_query.profile_events);
+        let report = query_analysis_report(selected_query.elapsed, &findings);
+        Ok(Some(EventResult::with_cb_once(move |app: &mut App| {
+            app.add_layer(
+                Dialog::around(TextView::new(report).scrollable()).title("Why is the query slow"),
+            );
+        })))
+    }
+
     fn action_edit_query_and_execute(&mut self) -> Result<Option<EventResult>> {
         let selected_query = self.get_selected_query()?;
         let query = selected_query.original_query.clone();
@@ -813,11 +930,11 @@ impl QueriesView {
 
     fn action_show_query(&mut self) -> Result<Option<EventResult>> {
         let selected_query = self.get_selected_query()?;
-        let query = selected_query.original_query.clone();
+        let original_query = selected_query.original_query.clone();
         let database = selected_query.current_database.clone();
         let settings = selected_query.settings.clone();
 
-        let query = get_query(&query, &settings);
+        let query = get_query(&original_query, &settings);
         let query = format!("USE {};\n{}", database, query);
         let query = crate::tui::highlight_sql(&query)
             .unwrap_or_else(|_| StyledString::plain(query.clone()));
@@ -827,18 +944,64 @@ impl QueriesView {
             .unwrap()
             .ui_sink
             .send(Box::new(move |app: &mut App| {
-                app.add_layer(Dialog::around(
+                let view = OnEventView::new(
                     LinearLayout::vertical()
-                        .child(TextView::new("Query:").center())
+                        .child(TextView::new("Query (B - benchmark):").center())
                         .child(DummyView.fixed_height(1))
                         .child(TextView::new(query).scrollable()),
-                ));
+                )
+                .on_event('B', move |app: &mut App| {
+                    show_benchmark_prompt(
+                        app,
+                        database.clone(),
+                        original_query.clone(),
+                        settings.clone(),
+                    );
+                });
+                app.add_layer(Dialog::around(view));
             }))
             .unwrap();
 
         Ok(Some(EventResult::consumed()))
     }
 
+    fn action_show_formatted_query(&mut self) -> Result<Option<EventResult>> {
+        let selected_query = self.get_selected_query()?;
+        let query = get_query(&selected_query.original_query, &selected_query.settings);
+        let database = selected_query.current_database.clone();
+        let mut context_locked = self.context.lock().unwrap();
+        context_locked
+            .worker
+            .send(true, WorkerEvent::ShowFormattedQuery(database, query));
+        Ok(Some(EventResult::consumed()))
+    }
+
+    fn action_diff_queries(&mut self) -> Result<Option<EventResult>> {
+        let mut queries: Vec<&Query> = self
+            .items
+            .values()
+            .filter(|q| self.selected_query_ids.contains(&query_key(q)))
+            .collect();
+        if queries.len() != 2 {
+            return Err(Error::msg(format!(
+                "Query diff requires exactly 2 queries selected with <Space>, got {}",
+                queries.len()
+            )));
+        }
+        queries.sort_by_key(|q| q.query_start_time_microseconds);
+        let [left, right] = [queries[0], queries[1]].map(|q| {
+            (
+                q.query_id.clone(),
+                get_query(&q.original_query, &q.settings),
+            )
+        });
+        let mut context_locked = self.context.lock().unwrap();
+        context_locked
+            .worker
+            .send(true, WorkerEvent::DiffQueries(left, right));
+        Ok(Some(EventResult::consumed()))
+    }
+
     fn action_copy_query(&mut self) -> Result<Option<EventResult>> {
         let selected_query = self.get_selected_query()?;
         let query = selected_query.original_query.clone();
@@ -862,6 +1025,16 @@ impl QueriesView {
         Ok(Some(EventResult::consumed()))
     }
 
+    fn action_benchmark_query(&mut self) -> Result<Option<EventResult>> {
+        let selected_query = self.get_selected_query()?;
+        let query = selected_query.original_query.clone();
+        let database = selected_query.current_database.clone();
+        let settings = selected_query.settings.clone();
+        Ok(Some(EventResult::with_cb_once(move |app: &mut App| {
+            show_benchmark_prompt(app, database, query, settings);
+        })))
+    }
+
     fn action_explain_syntax(&mut self) -> Result<Option<EventResult>> {
         let selected_query = self.get_selected_query()?;
         let query = selected_query.original_query.clone();
@@ -885,6 +1058,17 @@ impl QueriesView {
         Ok(Some(EventResult::consumed()))
     }
 
+    fn action_explain_plan_tree(&mut self) -> Result<Option<EventResult>> {
+        let selected_query = self.get_selected_query()?;
+        let query = selected_query.original_query.clone();
+        let database = selected_query.current_database.clone();
+        let mut context_locked = self.context.lock().unwrap();
+        context_locked
+            .worker
+            .send(true, WorkerEvent::ExplainPlanTree(database, query));
+        Ok(Some(EventResult::consumed()))
+    }
+
     fn action_explain_pipeline(&mut self) -> Result<Option<EventResult>> {
         let selected_query = self.get_selected_query()?;
         let query = selected_query.original_query.clone();
@@ -965,6 +1149,27 @@ impl QueriesView {
         Ok(Some(EventResult::consumed()))
     }
 
+    fn action_show_pipeline_graph(&mut self) -> Result<Option<EventResult>> {
+        let selected_query = self.get_selected_query()?;
+        let query = selected_query.original_query.clone();
+        let database = selected_query.current_database.clone();
+        // processors_profile_log is written only for finished queries
+        let finished = (!selected_query.running).then(|| {
+            (
+                selected_query.query_id.clone(),
+                selected_query.query_start_time_microseconds,
+                selected_query.query_end_time_microseconds
+                    + TimeDelta::seconds(QUERY_TIME_DRIFT_BUFFER_SECONDS),
+            )
+        });
+        let mut context_locked = self.context.lock().unwrap();
+        context_locked.worker.send(
+            true,
+            WorkerEvent::ExplainPipelineGraph(database, query, finished),
+        );
+        Ok(Some(EventResult::consumed()))
+    }
+
     fn action_kill_query(&mut self) -> Result<Option<EventResult>> {
         let selected_query = self.get_selected_query()?;
         let query_id = selected_query.query_id.clone();
@@ -1115,6 +1320,147 @@ impl QueriesView {
         Ok(Some(EventResult::consumed()))
     }
 
+    fn action_query_threads(&mut self) -> Result<Option<EventResult>> {
+        let (query_ids, min_query_start_microseconds, max_query_end_microseconds) =
+            self.get_query_ids()?;
+        let columns = vec![
+            "thread_name",
+            // String, since UInt64 are formatted as sizes
+            "toString(thread_id) thread_id",
+            "query_id",
+            "query_duration_ms duration",
+            "(ProfileEvents['UserTimeMicroseconds'] + ProfileEvents['SystemTimeMicroseconds']) cpu",
+            "ProfileEvents['OSIOWaitMicroseconds'] io_wait",
+            "peak_memory_usage memory",
+            "read_rows",
+            "read_bytes",
+            "written_rows",
+            "written_bytes",
+        ];
+        let sort_by = "cpu";
+        let table = "query_thread_log";
+        let dbtable = self
+            .context
+            .lock()
+            .unwrap()
+            .clickhouse
+            .get_log_table_name(table);
+
+        let max_query_end_with_buffer = max_query_end_microseconds.unwrap_or(Local::now())
+            + TimeDelta::seconds(QUERY_TIME_DRIFT_BUFFER_SECONDS);
+        let window = format!(
+            r#"
+            WITH
+                fromUnixTimestamp64Nano({}) AS start_time_,
+                fromUnixTimestamp64Nano({}) AS end_time_
+            "#,
+            min_query_start_microseconds
+                .timestamp_nanos_opt()
+                .ok_or(Error::msg("Invalid time"))?,
+            max_query_end_with_buffer
+                .timestamp_nanos_opt()
+                .ok_or(Error::msg("Invalid time"))?,
+        );
+        let condition = format!(
+            r#"
+                    event_date >= toDate(start_time_) AND event_time >  toDateTime(start_time_) AND event_time_microseconds > start_time_
+                AND event_date <= toDate(end_time_)   AND event_time <= toDateTime(end_time_)   AND event_time_microseconds <= end_time_
+                AND query_id IN ('{}')
+            "#,
+            query_ids.join("','"),
+        );
+
+        let query = format!(
+            "{} SELECT {} FROM {} WHERE {}",
+            window,
+            columns.join(", "),
+            dbtable,
+            condition,
+        );
+
+        let context_copy = self.context.clone();
+        self.context
+            .lock()
+            .unwrap()
+            .ui_sink
+            .send(Box::new(move |app: &mut App| {
+                let mut view = SQLQueryView::new(
+                    context_copy,
+                    QUERY_THREADS_VIEW_NAME,
+                    sort_by,
+                    columns,
+                    vec!["query_id", "thread_id"],
+                    vec!["thread_name"],
+                    query,
+                )
+                .unwrap_or_else(|_| panic!("Cannot get {}", table));
+                let v = view.get_inner_mut();
+                v.set_value_unit("duration", Unit::Milliseconds);
+                v.set_value_unit("cpu", Unit::Microseconds);
+                v.set_value_unit("io_wait", Unit::Microseconds);
+                v.set_value_unit("memory", Unit::Bytes);
+                v.set_value_unit("read_rows", Unit::Count);
+                v.set_value_unit("read_bytes", Unit::Bytes);
+                v.set_value_unit("written_rows", Unit::Count);
+                v.set_value_unit("written_bytes", Unit::Bytes);
+                // ProfileEvents of the thread
+                v.set_on_submit(move |app: &mut App, columns, row| {
+                    let mut map = HashMap::new();
+                    columns.iter().zip(row.0.iter()).for_each(|(c, r)| {
+                        map.insert(*c, r.to_string());
+                    });
+                    let (Some(query_id), Some(thread_id)) =
+                        (map.get("query_id"), map.get("thread_id"))
+                    else {
+                        return;
+                    };
+                    let query = format!(
+                        r#"
+                        {} SELECT pe.1 name, pe.2 value
+                        FROM {}
+                        ARRAY JOIN arrayZip(mapKeys(ProfileEvents), mapValues(ProfileEvents)) AS pe
+                        WHERE {} AND query_id = '{}' AND toString(thread_id) = '{}' AND pe.2 != 0
+                        "#,
+                        window, dbtable, condition, query_id, thread_id,
+                    );
+                    let context = app.user_data::<ContextArc>().unwrap().clone();
+                    let mut view = SQLQueryView::new(
+                        context,
+                        QUERY_THREAD_PROFILE_EVENTS_VIEW_NAME,
+                        "value",
+                        vec!["pe.1 name", "pe.2 value"],
+                        vec!["name"],
+                        vec!["name"],
+                        query,
+                    )
+                    .unwrap_or_else(|_| {
+                        panic!("Cannot get {}", QUERY_THREAD_PROFILE_EVENTS_VIEW_NAME)
+                    });
+                    view.get_inner_mut().set_value_unit("value", Unit::Count);
+                    app.add_layer(
+                        Dialog::around(
+                            view.with_name(QUERY_THREAD_PROFILE_EVENTS_VIEW_NAME)
+                                .resized(SizeConstraint::AtLeast(80), SizeConstraint::AtLeast(30)),
+                        )
+                        .title(format!("ProfileEvents of thread {}", thread_id)),
+                    );
+                });
+
+                app.add_layer(Dialog::around(
+                    LinearLayout::vertical()
+                        .child(TextView::new("Threads:").center())
+                        .child(DummyView.fixed_height(1))
+                        .child(
+                            view.with_name(QUERY_THREADS_VIEW_NAME)
+                                .resized(SizeConstraint::AtLeast(160), SizeConstraint::AtLeast(40)),
+                        ),
+                ));
+            }))
+            .unwrap();
+
+        Ok(Some(EventResult::consumed()))
+    }
+
     fn action_query_views(&mut self) -> Result<Option<EventResult>> {
         let (query_ids, min_query_start_microseconds, max_query_end_microseconds) =
             self.get_query_ids()?;
@@ -1186,13 +1532,26 @@ impl QueriesView {
         Ok(Some(EventResult::consumed()))
     }
 
+    pub fn new(
+        context: ContextArc,
+        processes_type: Type,
+        view_name: &str,
+        title: &str,
+    ) -> OnEventView<Self> {
+        return Self::new_with_interval_view(context, processes_type, view_name, title, view_name);
+    }
+
+    /// Like new(), but the time interval follows the one of `interval_view`
+    /// (e.g. executions of a pattern opened from the query patterns view).
+    ///
     /// Ignore rustfmt max_width, otherwise callback actions looks ugly
     #[rustfmt::skip]
-    pub fn new(
+    pub fn new_with_interval_view(
         context: ContextArc,
         processes_type: Type,
         view_name: &str,
         title: &str,
+        interval_view: &str,
     ) -> OnEventView<Self> {
         let view_name: Arc<str> = Arc::from(view_name);
 
@@ -1236,16 +1595,19 @@ impl QueriesView {
         let update_callback_limit = limit.clone();
         let update_callback_process_type = processes_type.clone();
         let update_callback_view_name = view_name.clone();
+        let update_callback_interval_view = interval_view.to_string();
         let update_callback = move |force: bool| {
             let view_name = &update_callback_view_name;
             let mut context = update_callback_context.lock().unwrap();
             let filter = QueriesFilter {
                 like: update_callback_filter.lock().unwrap().clone(),
                 query_kind: context.view_query_kind(view_name),
+                condition: context.queries_condition(view_name),
+                accessed: context.queries_accessed_condition(view_name),
             };
             let limit = context.view_limit(view_name, *update_callback_limit.lock().unwrap());
 
-            let (start_time, end_time) = context.view_interval(view_name);
+            let (start_time, end_time) = context.view_interval(&update_callback_interval_view);
 
             match update_callback_process_type {
                 Type::ProcessList => context.worker.send_owned(
@@ -1274,6 +1636,15 @@ impl QueriesView {
             }
         };
 
+        // Go before the (wide) query column, or to the end if it is hidden
+        let mut profile_event_columns = context.lock().unwrap().view_profile_event_columns(&view_name);
+        let mut add_profile_event_columns = |table: &mut TableView<Query, QueriesColumn>| {
+            for column in profile_event_columns.drain(..) {
+                let width = column.label.len();
+                table.add_column(QueriesColumn::ProfileEvent(column), column.label, |c| c.width_min_max(width, width.max(12)));
+            }
+        };
+
         let is_last_query_log = matches!(processes_type, Type::LastQueryLog);
         let mut table = TableView::<Query, QueriesColumn>::new();
         for &(col, width) in QUERY_COLUMNS_WIDTH {
@@ -1281,6 +1652,9 @@ impl QueriesView {
             if col == QueriesColumn::QueryEnd && !is_last_query_log {
                 continue;
             }
+            if col == QueriesColumn::Query {
+                add_profile_event_columns(&mut table);
+            }
             if !visible(col) {
                 continue;
             }
@@ -1289,6 +1663,7 @@ impl QueriesView {
             };
             table.add_column(col, label, width);
         }
+        add_profile_event_columns(&mut table);
         // Keep the options in sync on column removal via middle mouse press,
         // so that the settings dialog (F3) shows the column as hidden
         table.set_on_remove_column(|app, col| {
@@ -1404,6 +1779,7 @@ impl QueriesView {
         //
         add_action!(context, &mut event_view, "Query logs", 'l', action_show_query_logs);
         add_action!(context, &mut event_view, "Query live flamegraph", 'L', action_show_flamegraph(true, None));
+        add_action!(context, &mut event_view, "Trace spans", 'O', action_show_trace_spans);
         add_action!(context, &mut event_view, "Query profile events", action_query_profile_events);
         add_action!(context, &mut event_view, "Query details", action_query_details);
         add_action!(context, &mut event_view, "Query CPU flamegraph", action_show_flamegraph(true, Some(TraceType::CPU)));
@@ -1416,10 +1792,14 @@ impl QueriesView {
         add_action!(context, &mut event_view, "Export to Perfetto", action_export_perfetto);
         add_action!(context, &mut event_view, "Edit query and execute", Event::AltChar('E'), action_edit_query_and_execute);
         add_action!(context, &mut event_view, "Show query", 'S', action_show_query);
+        add_action!(context, &mut event_view, "Show formatted query", 'f', action_show_formatted_query);
+        add_action!(context, &mut event_view, "Diff queries (select 2 with <Space>)", 'D', action_diff_queries);
         add_action!(context, &mut event_view, "Copy query to clipboard", 'y', action_copy_query);
+        add_action!(context, &mut event_view, "Benchmark query", 'B', action_benchmark_query);
         add_action!(context, &mut event_view, "EXPLAIN SYNTAX", 's', action_explain_syntax);
-        add_action!(context, &mut event_view, "EXPLAIN PLAN", 'e', action_explain_plan);
+        add_action!(context, &mut event_view, "EXPLAIN PLAN", 'e', action_explain_plan_tree);
         add_action!(context, &mut event_view, "EXPLAIN PIPELINE", 'E', action_explain_pipeline);
+        add_action!(context, &mut event_view, "EXPLAIN PIPELINE graph=1", 'g', action_show_pipeline_graph);
         let filter_view_name = view_name.clone();
         context.add_view_action(&mut event_view, view_name.clone(), "Filter", '/', move |_v| {
             let view_name = filter_view_name.clone();
@@ -1442,6 +1822,29 @@ impl QueriesView {
                 show_bottom_prompt(app, "/", filter_cb);
             })));
         });
+        // system.processes does not have the databases/tables/columns arrays
+        if !is_system_processes {
+            let accessed_view_name = view_name.clone();
+            context.add_view_action(&mut event_view, view_name.clone(), "Find queries by table/column", 'u', move |_v| {
+                let view_name = accessed_view_name.clone();
+                return Ok(Some(EventResult::with_cb(move |app: &mut App| {
+                    let view_name = view_name.clone();
+                    let accessed_cb = move |app: &mut App, text: &str| {
+                        // An empty input clears only this condition, the one of the
+                        // drill-down (if any) stays
+                        let condition = accessed_objects_condition(text).unwrap_or_default();
+                        log::info!("Set accessed objects condition to '{}'", condition);
+                        let context = app.user_data::<ContextArc>().unwrap().clone();
+                        context.lock().unwrap().set_queries_accessed_condition(&view_name, condition);
+                        app.call_on_name(&view_name, |v: &mut OnEventView<QueriesView>| {
+                            v.get_inner_mut().bg_runner.schedule();
+                        });
+                        app.pop_layer();
+                    };
+                    show_bottom_prompt(app, "db.table[.column]: ", accessed_cb);
+                })));
+            });
+        }
         add_action!(context, &mut event_view, "Select", ' ', action_select);
         add_action!(context, &mut event_view, "Select all (toggle)", 'A', action_select_all);
         add_action!(context, &mut event_view, "Show all queries", '-', action_show_all_queries);
@@ -1450,6 +1853,9 @@ impl QueriesView {
         add_action!(context, &mut event_view, "Show queries on shards", '+', action_show_queries_on_shards);
         add_action!(context, &mut event_view, "Query processors", action_query_processors);
         add_action!(context, &mut event_view, "Query views", action_query_views);
+        add_action!(context, &mut event_view, "Query views chain", 'V', action_query_views_chain);
+        add_action!(context, &mut event_view, "Query threads", action_query_threads);
+        add_action!(context, &mut event_view, "Why is the query slow", 'W', action_query_analysis);
         add_action!(context, &mut event_view, "Share Query CPU flamegraph", action_show_flamegraph(false, Some(TraceType::CPU)));
         add_action!(context, &mut event_view, "Share Query Real flamegraph", action_show_flamegraph(false, Some(TraceType::Real)));
         add_action!(context, &mut event_view, "Share Query memory flamegraph", action_show_flamegraph(false, Some(TraceType::Memory)));
@@ -1465,6 +1871,7 @@ impl QueriesView {
         add_action!(context, &mut event_view, "Query jemalloc sample flamegraph diff (select 2 with <Space>)", action_show_flamegraph_diff(TraceType::JemallocSample));
         add_action!(context, &mut event_view, "Query MemoryAllocatedWithoutCheck flamegraph diff (select 2 with <Space>)", action_show_flamegraph_diff(TraceType::MemoryAllocatedWithoutCheck));
         add_action!(context, &mut event_view, "Query events flamegraph diff (select 2 with <Space>)", action_show_flamegraph_diff(TraceType::ProfileEvent));
+        add_action!(context, &mut event_view, "EXPLAIN PLAN (text)", action_explain_plan);
         add_action!(context, &mut event_view, "EXPLAIN INDEXES", 'I', action_explain_indexes);
         add_action!(context, &mut event_view, "EXPLAIN PIPELINE graph=1 (share)", 'G', action_explain_pipeline_graph);
         add_action!(context, &mut event_view, "KILL query", 'K', action_kill_query);
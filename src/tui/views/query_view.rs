@@ -1,11 +1,12 @@
 // Port of src/view/query_view.rs onto the in-repo ratatui component
 // framework (src/tui). Callers arrive with the queries view port.
 
+use crate::common::MetricDescriptions;
 use crate::interpreter::Query;
 use crate::tui::app::App;
 use crate::tui::component::{Canvas, Component, Nameable, NamedView, OnEventView};
 use crate::tui::event::{Event, EventResult};
-use crate::tui::style::{Color, StyledString};
+use crate::tui::style::{Color, Modifier, Style, StyledString, print_str};
 use crate::tui::views::table_view::{TableView, TableViewItem};
 use humantime::format_duration;
 use ratatui::layout::{Rect, Size};
@@ -39,6 +40,54 @@ impl PartialEq<QueryProcessDetails> for QueryProcessDetails {
     }
 }
 
+/// Formats the value of a ProfileEvent with the unit inferred from its name.
+pub fn format_profile_event(name: &str, value: u64) -> String {
+    let fmt_bytes = SizeFormatter::new()
+        .with_base(Base::Base2)
+        .with_style(SizeStyle::Abbreviated);
+    let fmt_rows = SizeFormatter::new()
+        .with_base(Base::Base10)
+        .with_style(SizeStyle::Abbreviated);
+
+    if name.contains("Microseconds") {
+        format!("{}", format_duration(Duration::from_micros(value)))
+    } else if name.contains("Millisecond") {
+        format!("{}", format_duration(Duration::from_millis(value)))
+    } else if name.contains("Ns") || name.contains("Nanoseconds") {
+        format!("{}", format_duration(Duration::from_nanos(value)))
+    } else if name.contains("Bytes") || name.contains("Chars") {
+        fmt_bytes.format(value as i64)
+    } else if value > 1_000 {
+        fmt_rows.format(value as i64)
+    } else {
+        value.to_string()
+    }
+}
+
+/// Same as format_profile_event(), but for the per-second rate.
+pub fn format_profile_event_rate(name: &str, rate: f64) -> String {
+    let fmt_bytes = SizeFormatter::new()
+        .with_base(Base::Base2)
+        .with_style(SizeStyle::Abbreviated);
+    let fmt_rows = SizeFormatter::new()
+        .with_base(Base::Base10)
+        .with_style(SizeStyle::Abbreviated);
+
+    if name.contains("Microseconds") {
+        format!("{}/s", format_duration(Duration::from_micros(rate as u64)))
+    } else if name.contains("Millisecond") {
+        format!("{}/s", format_duration(Duration::from_millis(rate as u64)))
+    } else if name.contains("Ns") || name.contains("Nanoseconds") {
+        format!("{}/s", format_duration(Duration::from_nanos(rate as u64)))
+    } else if name.contains("Bytes") || name.contains("Chars") {
+        fmt_bytes.format(rate as i64) + "/s"
+    } else if rate > 1e3 {
+        fmt_rows.format(rate as i64) + "/s"
+    } else {
+        format!("{:.2}", rate)
+    }
+}
+
 // TODO:
 // - human print
 // - colored print
@@ -46,26 +95,7 @@ impl PartialEq<QueryProcessDetails> for QueryProcessDetails {
 // - implement loadavg like with moving average
 impl QueryProcessDetails {
     fn format_value(&self, value: u64) -> String {
-        let fmt_bytes = SizeFormatter::new()
-            .with_base(Base::Base2)
-            .with_style(SizeStyle::Abbreviated);
-        let fmt_rows = SizeFormatter::new()
-            .with_base(Base::Base10)
-            .with_style(SizeStyle::Abbreviated);
-
-        if self.name.contains("Microseconds") {
-            format!("{}", format_duration(Duration::from_micros(value)))
-        } else if self.name.contains("Millisecond") {
-            format!("{}", format_duration(Duration::from_millis(value)))
-        } else if self.name.contains("Ns") || self.name.contains("Nanoseconds") {
-            format!("{}", format_duration(Duration::from_nanos(value)))
-        } else if self.name.contains("Bytes") || self.name.contains("Chars") {
-            fmt_bytes.format(value as i64)
-        } else if value > 1_000 {
-            fmt_rows.format(value as i64)
-        } else {
-            value.to_string()
-        }
+        format_profile_event(&self.name, value)
     }
 
     // Time events are displayed in normalized units, so they should be compared in normalized
@@ -86,26 +116,7 @@ impl QueryProcessDetails {
     }
 
     fn format_rate(&self, rate: f64) -> String {
-        let fmt_bytes = SizeFormatter::new()
-            .with_base(Base::Base2)
-            .with_style(SizeStyle::Abbreviated);
-        let fmt_rows = SizeFormatter::new()
-            .with_base(Base::Base10)
-            .with_style(SizeStyle::Abbreviated);
-
-        if self.name.contains("Microseconds") {
-            format!("{}/s", format_duration(Duration::from_micros(rate as u64)))
-        } else if self.name.contains("Millisecond") {
-            format!("{}/s", format_duration(Duration::from_millis(rate as u64)))
-        } else if self.name.contains("Ns") || self.name.contains("Nanoseconds") {
-            format!("{}/s", format_duration(Duration::from_nanos(rate as u64)))
-        } else if self.name.contains("Bytes") || self.name.contains("Chars") {
-            fmt_bytes.format(rate as i64) + "/s"
-        } else if rate > 1e3 {
-            fmt_rows.format(rate as i64) + "/s"
-        } else {
-            format!("{:.2}", rate)
-        }
+        format_profile_event_rate(&self.name, rate)
     }
 }
 
@@ -183,6 +194,7 @@ pub struct QueryView {
     table: TableView<QueryProcessDetails, QueryDetailsColumn>,
     all_items: Vec<QueryProcessDetails>,
     filter: Arc<Mutex<String>>,
+    descriptions: Arc<MetricDescriptions>,
 }
 
 impl QueryView {
@@ -195,7 +207,13 @@ impl QueryView {
         } else {
             self.all_items
                 .iter()
-                .filter(|item| item.name.to_lowercase().contains(&filter_lower))
+                .filter(|item| {
+                    item.name.to_lowercase().contains(&filter_lower)
+                        || self
+                            .descriptions
+                            .get(&item.name)
+                            .is_some_and(|d| d.to_lowercase().contains(&filter_lower))
+                })
                 .cloned()
                 .collect()
         };
@@ -203,15 +221,27 @@ impl QueryView {
         self.table.set_items_stable(filtered_items);
     }
 
-    pub fn new(query: Query, view_name: &'static str) -> NamedView<OnEventView<Self>> {
-        Self::new_internal(vec![query], view_name)
+    pub fn new(
+        query: Query,
+        descriptions: Arc<MetricDescriptions>,
+        view_name: &'static str,
+    ) -> NamedView<OnEventView<Self>> {
+        Self::new_internal(vec![query], descriptions, view_name)
     }
 
-    pub fn new_diff(queries: Vec<Query>, view_name: &'static str) -> NamedView<OnEventView<Self>> {
-        Self::new_internal(queries, view_name)
+    pub fn new_diff(
+        queries: Vec<Query>,
+        descriptions: Arc<MetricDescriptions>,
+        view_name: &'static str,
+    ) -> NamedView<OnEventView<Self>> {
+        Self::new_internal(queries, descriptions, view_name)
     }
 
-    fn new_internal(queries: Vec<Query>, view_name: &'static str) -> NamedView<OnEventView<Self>> {
+    fn new_internal(
+        queries: Vec<Query>,
+        descriptions: Arc<MetricDescriptions>,
+        view_name: &'static str,
+    ) -> NamedView<OnEventView<Self>> {
         let mut table = TableView::<QueryProcessDetails, QueryDetailsColumn>::new();
         table.add_column(QueryDetailsColumn::Name, "Name", |c| c.width_min(20));
 
@@ -305,6 +335,7 @@ impl QueryView {
             table,
             all_items: items,
             filter: filter.clone(),
+            descriptions,
         };
 
         let event_view = OnEventView::new(view).on_event('/', move |app: &mut App| {
@@ -326,11 +357,38 @@ impl QueryView {
 
 impl Component for QueryView {
     fn draw(&mut self, canvas: &mut Canvas<'_>, area: Rect, focused: bool) {
-        self.table.draw(canvas, area, focused);
+        if area.height < 2 {
+            self.table.draw(canvas, area, focused);
+            return;
+        }
+
+        // Description of the selected event in the last line
+        let table_area = Rect {
+            height: area.height - 1,
+            ..area
+        };
+        self.table.draw(canvas, table_area, focused);
+        let description = self
+            .table
+            .item()
+            .and_then(|index| self.table.borrow_item(index))
+            .and_then(|item| self.descriptions.get(&item.name))
+            .unwrap_or_default()
+            .to_string();
+        print_str(
+            canvas.buf,
+            area.x,
+            area.bottom() - 1,
+            area,
+            &description,
+            Style::new().add_modifier(Modifier::ITALIC),
+        );
     }
 
     fn required_size(&mut self, max: Size) -> Size {
-        self.table.required_size(max)
+        let mut size = self.table.required_size(max);
+        size.height = size.height.saturating_add(1).min(max.height);
+        size
     }
 
     fn on_event(&mut self, event: &Event) -> EventResult {
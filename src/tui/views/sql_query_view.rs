@@ -7,6 +7,7 @@ use std::sync::{Arc, Mutex};
 use anyhow::{Result, anyhow};
 use size::{Base, SizeFormatter, Style as SizeStyle};
 
+use crate::common::MetricDescriptions;
 use crate::interpreter::{
     BackgroundRunner, ContextArc, WorkerEvent,
     clickhouse::{Columns, column_as_string},
@@ -14,7 +15,7 @@ use crate::interpreter::{
 use crate::tui::app::App;
 use crate::tui::component::{Canvas, Component, OnEventView};
 use crate::tui::event::{Event, EventResult};
-use crate::tui::style::{Color, StyledString};
+use crate::tui::style::{Color, Modifier, Style, StyledString, print_str};
 use crate::tui::views::table_view::{TableView, TableViewItem};
 use chrono::{DateTime, Local};
 use chrono_tz::Tz;
@@ -62,6 +63,10 @@ fn format_count(v: f64) -> String {
 }
 
 fn format_duration_ms(ms: f64) -> String {
+    // Deltas (e.g. the Query patterns comparison) can be negative
+    if ms < 0.0 {
+        return format!("-{}", format_duration_ms(-ms));
+    }
     if ms < 1000.0 {
         return format!("{:.0}ms", ms);
     }
@@ -208,12 +213,21 @@ type BarColumnConfig = (&'static str, &'static str);
 /// current result set, so the coloring is relative to the shown rows.
 type ColorScaleConfig = (&'static str, Vec<Color>);
 
+/// (source_column_name, [(threshold, color)] in ascending order) - rows get
+/// the color of the highest threshold reached by the value of the column
+/// (e.g. parts of a partition against parts_to_delay_insert).
+type ColorThresholdsConfig = (&'static str, Vec<(f64, Color)>);
+
 /// (heatmap_column_name, values_column_name) - the values column holds
 /// comma-separated per-time-bucket sums (clickhouse-rs cannot read arrays),
 /// rendered as one colored cell per bucket, normalized by the global max
 /// across the current result set.
 type HeatmapColumnConfig = (&'static str, &'static str);
 
+/// (name_column, descriptions) - the description of the name of the selected
+/// row is shown in the line below the table (and is matched by the filter).
+type DescriptionsConfig = (&'static str, Arc<MetricDescriptions>);
+
 const BAR_WIDTH: usize = 10;
 const BAR_FILLED: char = '█';
 const BAR_EMPTY: char = '░';
@@ -271,7 +285,9 @@ pub struct SQLQueryView {
 
     bar_columns: Vec<BarColumnConfig>,
     color_scale: Option<ColorScaleConfig>,
+    color_thresholds: Option<ColorThresholdsConfig>,
     heatmap_column: Option<HeatmapColumnConfig>,
+    descriptions: Option<DescriptionsConfig>,
     value_units: Vec<(&'static str, Unit)>,
     value_sources: Vec<(&'static str, &'static str)>,
 
@@ -354,6 +370,9 @@ impl SQLQueryView {
                     row.0
                         .iter()
                         .any(|field| field.to_string().to_lowercase().contains(&filter_lower))
+                        || self
+                            .row_description(row)
+                            .is_some_and(|d| d.to_lowercase().contains(&filter_lower))
                 })
                 .cloned()
                 .collect()
@@ -362,6 +381,31 @@ impl SQLQueryView {
         self.table.set_items_stable(filtered_items);
     }
 
+    pub fn set_descriptions(
+        &mut self,
+        column: &'static str,
+        descriptions: Arc<MetricDescriptions>,
+    ) {
+        self.descriptions = Some((column, descriptions));
+    }
+
+    fn row_description(&self, row: &Row) -> Option<&str> {
+        let (column, descriptions) = self.descriptions.as_ref()?;
+        let index = self.columns.iter().position(|c| c == column)?;
+        descriptions.get(&row.0.get(index)?.to_string())
+    }
+
+    fn selected_description(&self) -> Option<&str> {
+        let item = self.table.borrow_item(self.table.item()?)?;
+        self.row_description(item)
+    }
+
+    /// Shows the rows in the order of the query (e.g. a tree) instead of
+    /// sorting them by `sort_by`, until a column is selected for sorting.
+    pub fn set_query_order(&mut self) {
+        self.table.clear_order();
+    }
+
     pub fn set_bar_columns(&mut self, configs: Vec<BarColumnConfig>) {
         self.bar_columns = configs;
     }
@@ -370,6 +414,10 @@ impl SQLQueryView {
         self.color_scale = Some((column, palette));
     }
 
+    pub fn set_color_thresholds(&mut self, column: &'static str, thresholds: Vec<(f64, Color)>) {
+        self.color_thresholds = Some((column, thresholds));
+    }
+
     pub fn set_heatmap_column(&mut self, heatmap: &'static str, values: &'static str) {
         self.heatmap_column = Some((heatmap, values));
     }
@@ -444,6 +492,19 @@ impl SQLQueryView {
         if self.context.lock().unwrap().options.view.no_color {
             return;
         }
+        if let Some((column, ref thresholds)) = self.color_thresholds
+            && let Some(src_idx) = self.columns.iter().position(|c| *c == column)
+        {
+            for row in &mut self.all_items {
+                let value = field_to_f64(&row.0[src_idx]);
+                row.2 = thresholds
+                    .iter()
+                    .rev()
+                    .find(|(threshold, _)| value >= *threshold)
+                    .map(|(_, color)| *color);
+            }
+            return;
+        }
         let Some((column, ref palette)) = self.color_scale else {
             return;
         };
@@ -683,7 +744,9 @@ impl SQLQueryView {
             filter: filter.clone(),
             bar_columns: Vec::new(),
             color_scale: None,
+            color_thresholds: None,
             heatmap_column: None,
+            descriptions: None,
             value_units: Vec::new(),
             value_sources: Vec::new(),
             bg_runner,
@@ -711,11 +774,34 @@ impl SQLQueryView {
 
 impl Component for SQLQueryView {
     fn draw(&mut self, canvas: &mut Canvas<'_>, area: Rect, focused: bool) {
-        self.table.draw(canvas, area, focused);
+        if self.descriptions.is_none() || area.height < 2 {
+            self.table.draw(canvas, area, focused);
+            return;
+        }
+
+        // Description of the selected row in the last line
+        let table_area = Rect {
+            height: area.height - 1,
+            ..area
+        };
+        self.table.draw(canvas, table_area, focused);
+        let description = self.selected_description().unwrap_or_default().to_string();
+        print_str(
+            canvas.buf,
+            area.x,
+            area.bottom() - 1,
+            area,
+            &description,
+            Style::new().add_modifier(Modifier::ITALIC),
+        );
     }
 
     fn required_size(&mut self, max: Size) -> Size {
-        self.table.required_size(max)
+        let mut size = self.table.required_size(max);
+        if self.descriptions.is_some() {
+            size.height = size.height.saturating_add(1).min(max.height);
+        }
+        size
     }
 
     fn on_event(&mut self, event: &Event) -> EventResult {
@@ -293,6 +293,14 @@ where
         self.sort_items(column, order);
     }
 
+    /// Disables sorting, the items are kept in the order they are set in,
+    /// until a column is selected for sorting.
+    pub fn clear_order(&mut self) {
+        for c in &mut self.columns {
+            c.order = Ordering::Equal;
+        }
+    }
+
     /// Sorts the table using the currently active column and its
     /// ordering.
     pub fn sort(&mut self) {
@@ -0,0 +1,372 @@
+use std::collections::{HashMap, HashSet};
+
+use ratatui::layout::Rect;
+
+use crate::common::{TraceSpan, spans_tree_order};
+use crate::tui::component::{Canvas, Component};
+use crate::tui::event::{Event, EventResult, Key, MouseButton, MouseEvent};
+use crate::tui::scroll::keep_row_visible;
+use crate::tui::style::{Color, Modifier, Style, highlight, highlight_inactive, print_str};
+
+// Lines of the attributes panel (with the header)
+const DETAILS_HEIGHT: u16 = 10;
+const DURATION_WIDTH: usize = 10;
+const HOST_COLORS: &[Color] = &[
+    Color::Cyan,
+    Color::Green,
+    Color::Yellow,
+    Color::Magenta,
+    Color::Blue,
+    Color::Red,
+];
+
+fn format_us(us: u64) -> String {
+    if us < 1_000 {
+        format!("{}us", us)
+    } else if us < 1_000_000 {
+        format!("{:.2}ms", us as f64 / 1e3)
+    } else {
+        format!("{:.3}s", us as f64 / 1e6)
+    }
+}
+
+/// Spans of a trace as an indented tree with the timeline bars of the spans
+/// on the right and the attributes of the selected one at the bottom.
+pub struct TraceSpansView {
+    spans: Vec<TraceSpan>,
+    /// (span, depth, parent item) in the tree order.
+    items: Vec<(usize, usize, Option<usize>)>,
+    has_children: Vec<bool>,
+    collapsed: HashSet<usize>,
+    /// Visible items (descendants of the collapsed ones are hidden).
+    rows: Vec<usize>,
+    /// Color of every host (if there are several of them).
+    host_colors: HashMap<String, Color>,
+    trace_start_us: u64,
+    trace_end_us: u64,
+    selected: usize,
+    offset: usize,
+    area: Rect,
+    tree_area: Rect,
+}
+
+impl TraceSpansView {
+    pub fn new(spans: Vec<TraceSpan>) -> Self {
+        let mut items: Vec<(usize, usize, Option<usize>)> = Vec::with_capacity(spans.len());
+        // Last item of every depth
+        let mut path: Vec<usize> = Vec::new();
+        for (span, depth) in spans_tree_order(&spans) {
+            path.truncate(depth);
+            let parent = path.last().copied();
+            path.push(items.len());
+            items.push((span, depth, parent));
+        }
+        let mut has_children = vec![false; items.len()];
+        for &(_, _, parent) in &items {
+            if let Some(parent) = parent {
+                has_children[parent] = true;
+            }
+        }
+
+        let mut hosts: Vec<&str> = spans.iter().map(|s| s.host_name.as_str()).collect();
+        hosts.sort_unstable();
+        hosts.dedup();
+        let host_colors = if hosts.len() > 1 {
+            hosts
+                .iter()
+                .enumerate()
+                .map(|(i, host)| (host.to_string(), HOST_COLORS[i % HOST_COLORS.len()]))
+                .collect()
+        } else {
+            HashMap::new()
+        };
+
+        let trace_start_us = spans.iter().map(|s| s.start_time_us).min().unwrap_or(0);
+        let trace_end_us = spans.iter().map(|s| s.finish_time_us).max().unwrap_or(0);
+
+        let mut view = Self {
+            spans,
+            items,
+            has_children,
+            collapsed: HashSet::new(),
+            rows: Vec::new(),
+            host_colors,
+            trace_start_us,
+            trace_end_us,
+            selected: 0,
+            offset: 0,
+            area: Rect::default(),
+            tree_area: Rect::default(),
+        };
+        view.rebuild_rows();
+        view
+    }
+
+    fn rebuild_rows(&mut self) {
+        let selected = self.rows.get(self.selected).copied();
+        self.rows.clear();
+        // Depth of the collapsed item whose descendants are being skipped
+        let mut skip_deeper: Option<usize> = None;
+        for (i, &(_, depth, _)) in self.items.iter().enumerate() {
+            if let Some(skip) = skip_deeper {
+                if depth > skip {
+                    continue;
+                }
+                skip_deeper = None;
+            }
+            self.rows.push(i);
+            if self.collapsed.contains(&i) {
+                skip_deeper = Some(depth);
+            }
+        }
+        self.selected = selected
+            .and_then(|item| self.rows.iter().position(|&i| i == item))
+            .unwrap_or(0);
+    }
+
+    fn label(&self, item: usize) -> String {
+        let (span, depth, _) = self.items[item];
+        let marker = match (self.has_children[item], self.collapsed.contains(&item)) {
+            (false, _) => "  ",
+            (true, false) => "▾ ",
+            (true, true) => "▸ ",
+        };
+        format!(
+            "{}{}{}",
+            "  ".repeat(depth),
+            marker,
+            self.spans[span].operation_name
+        )
+    }
+
+    fn details(&self) -> Vec<(String, Style)> {
+        let Some(&(span, _, _)) = self.rows.get(self.selected).map(|&i| &self.items[i]) else {
+            return vec![("No spans".to_string(), Style::new())];
+        };
+        let span = &self.spans[span];
+        let mut lines = vec![(
+            format!(
+                "{} on {} (+{}, {}) span_id: {} trace_id: {}",
+                span.operation_name,
+                span.host_name,
+                format_us(span.start_time_us.saturating_sub(self.trace_start_us)),
+                format_us(span.duration_us()),
+                span.span_id,
+                span.trace_id,
+            ),
+            Style::new().add_modifier(Modifier::BOLD),
+        )];
+        let width = span
+            .attributes
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .max()
+            .unwrap_or(0);
+        lines.extend(span.attributes.iter().map(|(name, value)| {
+            (
+                format!("{:width$} {}", name, value, width = width),
+                Style::new(),
+            )
+        }));
+        lines
+    }
+
+    fn select(&mut self, row: usize) -> EventResult {
+        self.selected = row.min(self.rows.len().saturating_sub(1));
+        EventResult::consumed()
+    }
+
+    fn select_relative(&mut self, delta: isize) -> EventResult {
+        self.select(self.selected.saturating_add_signed(delta))
+    }
+
+    fn set_collapsed(&mut self, collapsed: bool) -> EventResult {
+        let Some(&item) = self.rows.get(self.selected) else {
+            return EventResult::Ignored;
+        };
+        if !self.has_children[item] || self.collapsed.contains(&item) == collapsed {
+            // Left on a leaf or a collapsed span goes to the parent
+            if collapsed
+                && let Some(parent) = self.items[item].2
+                && let Some(row) = self.rows.iter().position(|&i| i == parent)
+            {
+                return self.select(row);
+            }
+            return EventResult::consumed();
+        }
+        if collapsed {
+            self.collapsed.insert(item);
+        } else {
+            self.collapsed.remove(&item);
+        }
+        self.rebuild_rows();
+        EventResult::consumed()
+    }
+
+    fn toggle_selected(&mut self) -> EventResult {
+        let Some(&item) = self.rows.get(self.selected) else {
+            return EventResult::Ignored;
+        };
+        let collapsed = self.collapsed.contains(&item);
+        self.set_collapsed(!collapsed)
+    }
+}
+
+impl Component for TraceSpansView {
+    fn draw(&mut self, canvas: &mut Canvas<'_>, area: Rect, focused: bool) {
+        self.area = area;
+        // Attributes panel and the status line at the bottom
+        let details_height = DETAILS_HEIGHT.min(area.height / 2);
+        let tree_height = area.height.saturating_sub(details_height + 1);
+        self.tree_area = Rect {
+            height: tree_height,
+            ..area
+        };
+
+        let labels: Vec<String> = self.rows.iter().map(|&i| self.label(i)).collect();
+        let label_width = labels
+            .iter()
+            .map(|l| l.chars().count())
+            .max()
+            .unwrap_or(0)
+            .min(area.width as usize * 2 / 5);
+        let text_width = label_width + 1 + DURATION_WIDTH;
+        let bar_x = area.x + (text_width as u16 + 1).min(area.width);
+        let bar_width = area.right().saturating_sub(bar_x) as u64;
+        let trace_us = self.trace_end_us.saturating_sub(self.trace_start_us).max(1);
+
+        let viewport = tree_height as usize;
+        self.offset = keep_row_visible(self.offset, self.selected, viewport);
+        for (y, row) in (self.offset..self.rows.len()).take(viewport).enumerate() {
+            let (item, label) = (self.rows[row], &labels[row]);
+            let span = &self.spans[self.items[item].0];
+            let y = area.y + y as u16;
+
+            let text = format!(
+                "{:label_width$} {:>DURATION_WIDTH$}",
+                label.chars().take(label_width).collect::<String>(),
+                format_us(span.duration_us()),
+            );
+            let style = if row == self.selected {
+                if focused {
+                    highlight()
+                } else {
+                    highlight_inactive()
+                }
+            } else {
+                Style::new()
+            };
+            print_str(canvas.buf, area.x, y, self.tree_area, &text, style);
+
+            if bar_width > 0 {
+                let from = span.start_time_us.saturating_sub(self.trace_start_us);
+                let start = from * bar_width / trace_us;
+                let len = (span.duration_us() * bar_width / trace_us).max(1);
+                let start = start.min(bar_width - 1);
+                let len = len.min(bar_width - start);
+                let color = self
+                    .host_colors
+                    .get(&span.host_name)
+                    .copied()
+                    .unwrap_or(Color::Cyan);
+                print_str(
+                    canvas.buf,
+                    bar_x + start as u16,
+                    y,
+                    self.tree_area,
+                    &"█".repeat(len as usize),
+                    Style::new().fg(color),
+                );
+            }
+        }
+
+        let details_y = area.y + tree_height;
+        let details_area = Rect {
+            y: details_y,
+            height: details_height,
+            ..area
+        };
+        print_str(
+            canvas.buf,
+            area.x,
+            details_y,
+            details_area,
+            &"─".repeat(area.width as usize),
+            Style::new().fg(Color::DarkGray),
+        );
+        for (i, (text, style)) in self
+            .details()
+            .iter()
+            .take(details_height.saturating_sub(1) as usize)
+            .enumerate()
+        {
+            print_str(
+                canvas.buf,
+                area.x,
+                details_y + 1 + i as u16,
+                details_area,
+                text,
+                *style,
+            );
+        }
+
+        if area.height > 1 {
+            let mut status = format!(
+                "{} spans, {} | <Enter> - expand/collapse",
+                self.spans.len(),
+                format_us(trace_us)
+            );
+            if !self.host_colors.is_empty() {
+                status.push_str(&format!(" | {} hosts", self.host_colors.len()));
+            }
+            let status = format!("{:width$}", status, width = area.width as usize);
+            print_str(
+                canvas.buf,
+                area.x,
+                area.bottom() - 1,
+                area,
+                &status,
+                Style::new().add_modifier(Modifier::REVERSED),
+            );
+        }
+    }
+
+    fn on_event(&mut self, event: &Event) -> EventResult {
+        let page = self.tree_area.height.max(1) as isize;
+        match event {
+            Event::Key(Key::Up) => self.select_relative(-1),
+            Event::Key(Key::Down) => self.select_relative(1),
+            Event::Key(Key::PageUp) => self.select_relative(-page),
+            Event::Key(Key::PageDown) => self.select_relative(page),
+            Event::Key(Key::Home) => self.select(0),
+            Event::Key(Key::End) => self.select(usize::MAX),
+            Event::Key(Key::Left) => self.set_collapsed(true),
+            Event::Key(Key::Right) => self.set_collapsed(false),
+            Event::Key(Key::Enter) | Event::Char(' ') => self.toggle_selected(),
+            Event::Mouse {
+                event: MouseEvent::WheelUp,
+                ..
+            } => self.select_relative(-3),
+            Event::Mouse {
+                event: MouseEvent::WheelDown,
+                ..
+            } => self.select_relative(3),
+            Event::Mouse {
+                position,
+                event: MouseEvent::Press(MouseButton::Left),
+            } if self.tree_area.contains(*position) => {
+                let row = self.offset + (position.y - self.tree_area.y) as usize;
+                if row < self.rows.len() {
+                    self.select(row)
+                } else {
+                    EventResult::Ignored
+                }
+            }
+            _ => EventResult::Ignored,
+        }
+    }
+
+    fn take_focus(&mut self) -> bool {
+        true
+    }
+}
@@ -351,6 +351,37 @@ impl ClickHouseServer {
         user: &str,
         duration_ms: u64,
         query: &str,
+    ) {
+        self.insert_query_log_row(table, query_id, user, duration_ms, query, 1);
+    }
+
+    /// Same, but `minutes_ago` (e.g. for the baseline window of the comparison).
+    pub fn insert_query_log_ago(
+        &self,
+        query_id: &str,
+        user: &str,
+        duration_ms: u64,
+        query: &str,
+        minutes_ago: u64,
+    ) {
+        self.insert_query_log_row(
+            "system.query_log",
+            query_id,
+            user,
+            duration_ms,
+            query,
+            minutes_ago,
+        );
+    }
+
+    fn insert_query_log_row(
+        &self,
+        table: &str,
+        query_id: &str,
+        user: &str,
+        duration_ms: u64,
+        query: &str,
+        minutes_ago: u64,
     ) {
         self.query(&format!(
             r#"
@@ -362,10 +393,10 @@ impl ClickHouseServer {
                  peak_threads_usage, exception_code, client_name)
             VALUES
                 (hostName(), 'QueryFinish',
-                 toDate(now() - INTERVAL 1 MINUTE),
-                 now() - INTERVAL 1 MINUTE,
-                 now64(6) - INTERVAL 1 MINUTE,
-                 now() - INTERVAL 1 MINUTE, now64(6) - INTERVAL 1 MINUTE, {duration_ms},
+                 toDate(now() - INTERVAL {minutes_ago} MINUTE),
+                 now() - INTERVAL {minutes_ago} MINUTE,
+                 now64(6) - INTERVAL {minutes_ago} MINUTE,
+                 now() - INTERVAL {minutes_ago} MINUTE, now64(6) - INTERVAL {minutes_ago} MINUTE, {duration_ms},
                  1048576, 'default', '{query}', normalizedQueryHash('{query}'),
                  '{query_id}', '{query_id}', 1, '{user}', '{user}',
                  2, 0, '')
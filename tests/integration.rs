@@ -1,6 +1,6 @@
 mod common;
 
-use chdig::common::RelativeDateTime;
+use chdig::common::{PipelineGraph, PlanNode, RelativeDateTime, spans_tree_order};
 use chdig::interpreter::clickhouse::{
     QueriesFilter, TraceType, column_as_string, parse_metric_log_block,
     parse_query_metric_log_block,
@@ -68,6 +68,23 @@ async fn test_connect_and_version() {
     );
 }
 
+async fn test_metric_descriptions() {
+    let Some(server) = common::server() else {
+        return;
+    };
+    let chdig = server.chdig().await;
+    let descriptions = chdig.metric_descriptions();
+    // "Query" is both a ProfileEvent and a CurrentMetric
+    let event = descriptions
+        .get("Query")
+        .expect("no ProfileEvent description");
+    let metric = descriptions
+        .get("CurrentMetric_Query")
+        .expect("no CurrentMetric description");
+    assert_eq!(descriptions.get("ProfileEvent_Query"), Some(event));
+    assert_ne!(event, metric);
+}
+
 async fn test_summary() {
     let Some(server) = common::server() else {
         return;
@@ -169,6 +186,7 @@ async fn test_last_query_log_query_kind() {
     let kinds = |kinds: &[&str]| QueriesFilter {
         like: "it-kind-%".to_string(),
         query_kind: kinds.iter().map(|kind| kind.to_string()).collect(),
+        ..Default::default()
     };
 
     let block = chdig
@@ -262,6 +280,91 @@ async fn test_query_patterns() {
     assert_eq!(hm.iter().sum::<i64>(), 3);
 }
 
+// Same idea as test_query_patterns, for the baseline/current comparison SQL.
+async fn test_query_patterns_compare() {
+    let Some(server) = common::server() else {
+        return;
+    };
+    // One 1s query 25 minutes ago (the baseline), two 3s ones a minute ago.
+    server.insert_query_log_ago(
+        "it-qpc-base",
+        "it_user_qpc",
+        1000,
+        "SELECT 1 FROM it_qpc",
+        25,
+    );
+    for i in 0..2 {
+        server.insert_query_log(
+            &format!("it-qpc-{i}"),
+            "it_user_qpc",
+            3000,
+            "SELECT 1 FROM it_qpc",
+        );
+    }
+
+    let run = |baseline: (&str, &str)| -> Vec<(String, String)> {
+        let sql = chdig::query_patterns_compare_sql(
+            ("now() - INTERVAL 10 MINUTE", "now()"),
+            baseline,
+            "system.query_log",
+            "AND user = 'it_user_qpc'",
+            "",
+            1000,
+        );
+        let out = server.query(&format!("{sql}\nFORMAT TSVWithNames"));
+        let mut lines = out.lines();
+        let header: Vec<String> = lines
+            .next()
+            .unwrap()
+            .split('\t')
+            .map(str::to_string)
+            .collect();
+        let row: Vec<String> = lines
+            .next()
+            .expect("one pattern row")
+            .split('\t')
+            .map(str::to_string)
+            .collect();
+        assert!(lines.next().is_none(), "expected exactly one pattern");
+        header.into_iter().zip(row).collect()
+    };
+    let col = |row: &[(String, String)], name: &str| -> String {
+        row.iter()
+            .find(|(h, _)| h == name)
+            .unwrap_or_else(|| panic!("missing column {name}"))
+            .1
+            .clone()
+    };
+
+    // Baseline without the queries: the pattern is new, no deltas.
+    let row = run(("now() - INTERVAL 50 MINUTE", "now() - INTERVAL 40 MINUTE"));
+    assert_eq!(col(&row, "flag"), "new");
+    assert_eq!(col(&row, "cnt_base"), "0");
+    assert_eq!(col(&row, "cnt_cur"), "2");
+    assert_eq!(col(&row, "cnt_delta"), "0");
+    assert_eq!(col(&row, "p95_delta"), "0");
+
+    // Both windows: the deltas are current minus baseline.
+    let row = run(("now() - INTERVAL 30 MINUTE", "now() - INTERVAL 20 MINUTE"));
+    assert_eq!(col(&row, "flag"), "");
+    assert_eq!(col(&row, "cnt_base"), "1");
+    assert_eq!(col(&row, "cnt_cur"), "2");
+    assert_eq!(col(&row, "cnt_delta"), "1");
+    assert_eq!(col(&row, "cnt_rel"), "100");
+    assert_eq!(col(&row, "p50_delta"), "2");
+    assert_eq!(col(&row, "p95_delta"), "2");
+    assert_eq!(col(&row, "p95_rel"), "200");
+    assert_eq!(col(&row, "p99_delta"), "2");
+    assert_eq!(col(&row, "mem_delta"), "0");
+
+    // Overlapping windows: the rows of the current one are not in the
+    // baseline, so the deltas are the same.
+    let row = run(("now() - INTERVAL 30 MINUTE", "now()"));
+    assert_eq!(col(&row, "cnt_base"), "1");
+    assert_eq!(col(&row, "cnt_cur"), "2");
+    assert_eq!(col(&row, "p95_delta"), "2");
+}
+
 async fn test_slow_query_log() {
     let Some(server) = common::server() else {
         return;
@@ -418,6 +521,36 @@ async fn test_execute_query() {
     );
 }
 
+async fn test_benchmark_query() {
+    let Some(server) = common::server() else {
+        return;
+    };
+    let chdig = server.chdig().await;
+    let start = Local::now() - TimeDelta::seconds(1);
+    let settings = vec![("max_threads".to_string(), "1".to_string())];
+    for run in 0..2 {
+        chdig
+            .benchmark_query(
+                "default",
+                "SELECT number FROM numbers(100000)",
+                &settings,
+                &format!("it-benchmark-{}", run),
+            )
+            .await
+            .unwrap();
+    }
+    chdig.flush_logs().await.unwrap();
+
+    let runs = chdig
+        .get_benchmark_runs("it-benchmark-", start)
+        .await
+        .unwrap();
+    assert_eq!(runs.len(), 2, "{runs:?}");
+    let run = &runs["it-benchmark-0"];
+    assert_eq!(run.read_rows, 100000);
+    assert!(!run.profile_events.is_empty());
+}
+
 async fn test_explains_and_show_create_table() {
     let Some(server) = common::server() else {
         return;
@@ -451,6 +584,25 @@ async fn test_explains_and_show_create_table() {
         .unwrap();
     assert!(plan.join("\n").contains("ReadFromMergeTree"), "{plan:?}");
 
+    let json = chdig
+        .explain_plan_json("default", "SELECT key FROM it_explain WHERE key = 1")
+        .await
+        .unwrap();
+    let plans = PlanNode::parse_explain_json(&json).unwrap();
+    let mut steps: Vec<&PlanNode> = plans.iter().collect();
+    let mut read = None;
+    while let Some(step) = steps.pop() {
+        if step.node_type == "ReadFromMergeTree" {
+            read = Some(step);
+        }
+        steps.extend(step.children.iter());
+    }
+    let read = read.unwrap_or_else(|| panic!("{json}"));
+    assert!(
+        read.indexes.iter().any(|i| i.index_type == "PrimaryKey"),
+        "{json}"
+    );
+
     let pipeline = chdig
         .explain_pipeline("default", "SELECT key FROM it_explain")
         .await
@@ -458,11 +610,22 @@ async fn test_explains_and_show_create_table() {
     assert!(!pipeline.is_empty());
 
     let graph = chdig
-        .explain_pipeline_graph("default", "SELECT key FROM it_explain")
+        .explain_pipeline_graph("default", "SELECT key FROM it_explain", true)
         .await
         .unwrap();
     assert!(graph.join("\n").contains("digraph"), "{graph:?}");
 
+    let graph = chdig
+        .explain_pipeline_graph("default", "SELECT key FROM it_explain", false)
+        .await
+        .unwrap();
+    let parsed = PipelineGraph::parse(&graph.join("\n"));
+    assert!(
+        parsed.nodes.iter().any(|n| n.step == "ReadFromMergeTree"),
+        "{graph:?}"
+    );
+    assert!(!parsed.edges.is_empty(), "{graph:?}");
+
     let indexes = chdig
         .explain_plan_indexes("default", "SELECT key FROM it_explain WHERE key = 1")
         .await
@@ -970,6 +1133,46 @@ async fn test_otel_spans_for_perfetto() {
     assert_eq!(block.get::<String, _>(0, "query_id").unwrap(), "it-otel-1");
 }
 
+async fn test_query_trace_spans() {
+    let Some(server) = common::server_with_table("opentelemetry_span_log") else {
+        return;
+    };
+    // Only the root span has the query_id, the child one is found by trace_id
+    server.query(
+        r#"
+        INSERT INTO system.opentelemetry_span_log
+            (trace_id, span_id, parent_span_id, operation_name,
+             start_time_us, finish_time_us, finish_date, attribute)
+        VALUES
+            ('5f5c7d52-7d6a-4f3c-9f6e-2a8f0c1b2d3e', 1, 0, 'ITRootSpan',
+             toUnixTimestamp64Micro(now64(6) - INTERVAL 1 MINUTE),
+             toUnixTimestamp64Micro(now64(6) - INTERVAL 1 MINUTE) + 1000,
+             today(), map('clickhouse.query_id', 'it-trace-spans-1')),
+            ('5f5c7d52-7d6a-4f3c-9f6e-2a8f0c1b2d3e', 2, 1, 'ITChildSpan',
+             toUnixTimestamp64Micro(now64(6) - INTERVAL 1 MINUTE) + 100,
+             toUnixTimestamp64Micro(now64(6) - INTERVAL 1 MINUTE) + 500,
+             today(), map('it.attribute', 'value'))
+        "#,
+    );
+
+    let chdig = server.chdig().await;
+    let (start, end) = perfetto_window();
+    let spans = chdig
+        .get_query_trace_spans(&["it-trace-spans-1".to_string()], start, end)
+        .await
+        .unwrap();
+    let order: Vec<(&str, usize)> = spans_tree_order(&spans)
+        .into_iter()
+        .map(|(i, depth)| (spans[i].operation_name.as_str(), depth))
+        .collect();
+    assert_eq!(order, vec![("ITRootSpan", 0), ("ITChildSpan", 1)]);
+    assert!(
+        spans[1]
+            .attributes
+            .contains(&("it.attribute".to_string(), "value".to_string()))
+    );
+}
+
 async fn test_text_log_android_for_perfetto() {
     let Some(server) = common::server_with_table("text_log") else {
         return;
@@ -1547,16 +1750,19 @@ async fn test_database_from_url() {
 
 common::integration_tests!(
     test_connect_and_version,
+    test_metric_descriptions,
     test_summary,
     test_last_query_log,
     test_last_query_log_normalized_query,
     test_last_query_log_query_kind,
     test_query_patterns,
+    test_query_patterns_compare,
     test_slow_query_log,
     test_query_log_additional_table_filters_quirk_variations,
     test_query_log_out_of_window,
     test_processlist_and_kill_query,
     test_execute_query,
+    test_benchmark_query,
     test_explains_and_show_create_table,
     test_text_log,
     test_flamegraph,
@@ -1570,6 +1776,7 @@ common::integration_tests!(
     test_asynchronous_metric_log_for_perfetto,
     test_part_log_for_perfetto,
     test_otel_spans_for_perfetto,
+    test_query_trace_spans,
     test_text_log_android_for_perfetto,
     test_asynchronous_insert_log_for_perfetto,
     test_error_log_for_perfetto,